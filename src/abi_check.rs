@@ -0,0 +1,106 @@
+use crate::diff::{Diff, MemberChange};
+use crate::errors::Error;
+use crate::layout::{ClassIndex, ClassLayout};
+use crate::search_filter::SearchFilter;
+
+/// A single change found when comparing two binaries, classified by
+/// whether it breaks code compiled against the old layout.
+struct Finding {
+    breaking: bool,
+    description: String,
+}
+
+/// Compare two binaries, classifying each change to the classes that
+/// match the search filter.  Removed classes, size changes, and
+/// members that were removed, moved, or changed type are breaking.
+/// Added classes and members added without moving any existing
+/// member, such as new trailing members, are compatible.  Returns an
+/// error if any change is breaking, so that the exit status can be
+/// checked by scripts.
+pub fn check_abi(
+    old: impl Iterator<Item = ClassLayout>,
+    new: impl Iterator<Item = ClassLayout>,
+    search_filter: &SearchFilter,
+) -> Result<(), Error> {
+    let old = ClassIndex::new(old);
+    let new = ClassIndex::new(new);
+    let diff = Diff::new(&old, &new, search_filter);
+
+    let mut findings = Vec::new();
+    for class in &diff.removed {
+        findings.push(Finding {
+            breaking: true,
+            description: format!("{}: class removed", class.qualified_name()),
+        });
+    }
+    for class in &diff.added {
+        findings.push(Finding {
+            breaking: false,
+            description: format!("{}: class added", class.qualified_name()),
+        });
+    }
+
+    for class_diff in &diff.changed {
+        let (old, new) = (class_diff.old, class_diff.new);
+        let name = new.qualified_name();
+        if old.size_bytes != new.size_bytes {
+            findings.push(Finding {
+                breaking: true,
+                description: format!(
+                    "{name}: size changed from {} to {} bytes",
+                    old.size_bytes, new.size_bytes
+                ),
+            });
+        }
+
+        for change in &class_diff.members {
+            let finding = match change {
+                MemberChange::Removed(member) => Finding {
+                    breaking: true,
+                    description: format!("{name}::{}: member removed", member.name),
+                },
+                MemberChange::Added(member) if member.offset >= old.size_bytes => Finding {
+                    breaking: false,
+                    description: format!(
+                        "{name}::{}: new trailing member at 0x{:x}",
+                        member.name, member.offset
+                    ),
+                },
+                MemberChange::Added(member) => Finding {
+                    breaking: false,
+                    description: format!(
+                        "{name}::{}: new member at 0x{:x}, within existing padding",
+                        member.name, member.offset
+                    ),
+                },
+                MemberChange::Changed { old, new } if old.offset != new.offset => Finding {
+                    breaking: true,
+                    description: format!(
+                        "{name}::{}: member moved from 0x{:x} to 0x{:x}",
+                        new.name, old.offset, new.offset
+                    ),
+                },
+                MemberChange::Changed { old, new } => Finding {
+                    breaking: true,
+                    description: format!(
+                        "{name}::{}: type changed from {} ({} bytes) to {} ({} bytes)",
+                        new.name, old.type_name, old.size_bytes, new.type_name, new.size_bytes
+                    ),
+                },
+            };
+            findings.push(finding);
+        }
+    }
+
+    for finding in &findings {
+        let label = if finding.breaking { "BREAKING" } else { "ok" };
+        println!("{label:<8}  {}", finding.description);
+    }
+
+    let num_breaking = findings.iter().filter(|finding| finding.breaking).count();
+    if num_breaking > 0 {
+        Err(Error::AbiBreak(num_breaking))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+/// Decode a name as written by GNAT into its Ada form.  GNAT writes
+/// the `.` between the enclosing packages and the name as `__`, and
+/// may add a suffix after `___` that encodes how the type is
+/// represented, such as `___XVE` for a record of dynamic size, or
+/// `___PAD` for a padded wrapper.  For example, `pkg__rec___XVE`
+/// decodes to `pkg.rec`.
+pub fn decode_name(name: &str) -> String {
+    let name = name.find("___").map_or(name, |suffix| &name[..suffix]);
+    name.replace("__", ".")
+}
+
+/// Split a decoded name into its enclosing package and the name
+/// within the package, such as `pkg.child` and `rec` for
+/// `pkg.child.rec`.  Returns `None` for names at library level.
+pub fn split_package(name: &str) -> Option<(&str, &str)> {
+    name.rsplit_once('.')
+}
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::layout::{
+    Bitfield, ClassKind, ClassLayout, Enumerator, Language, MemberLayout, SizeKind, TypeKind,
+};
+
+const BTF_MAGIC: u16 = 0xeb9f;
+
+const BTF_KIND_INT: u32 = 1;
+const BTF_KIND_PTR: u32 = 2;
+const BTF_KIND_ARRAY: u32 = 3;
+const BTF_KIND_STRUCT: u32 = 4;
+const BTF_KIND_UNION: u32 = 5;
+const BTF_KIND_ENUM: u32 = 6;
+const BTF_KIND_FWD: u32 = 7;
+const BTF_KIND_TYPEDEF: u32 = 8;
+const BTF_KIND_VOLATILE: u32 = 9;
+const BTF_KIND_CONST: u32 = 10;
+const BTF_KIND_RESTRICT: u32 = 11;
+const BTF_KIND_FUNC: u32 = 12;
+const BTF_KIND_FUNC_PROTO: u32 = 13;
+const BTF_KIND_VAR: u32 = 14;
+const BTF_KIND_DATASEC: u32 = 15;
+const BTF_KIND_FLOAT: u32 = 16;
+const BTF_KIND_DECL_TAG: u32 = 17;
+const BTF_KIND_TYPE_TAG: u32 = 18;
+const BTF_KIND_ENUM64: u32 = 19;
+
+const BTF_INT_SIGNED: u32 = 1 << 0;
+const BTF_INT_CHAR: u32 = 1 << 1;
+const BTF_INT_BOOL: u32 = 1 << 2;
+
+/// Returns true if the bytes start with the BTF magic number, in
+/// either byte order.
+pub fn is_btf(bytes: &[u8]) -> bool {
+    bytes.len() >= 2
+        && (u16::from_le_bytes([bytes[0], bytes[1]]) == BTF_MAGIC
+            || u16::from_be_bytes([bytes[0], bytes[1]]) == BTF_MAGIC)
+}
+
+/// A single member of a `BTF_KIND_STRUCT` or `BTF_KIND_UNION`.
+struct BtfMember {
+    name_off: u32,
+    type_id: u32,
+    offset_bits: u32,
+    bitfield_size: u32,
+}
+
+/// A single decoded BTF type.  Only the information needed to
+/// produce a `ClassLayout` is retained.
+struct BtfType {
+    name_off: u32,
+    kind: u32,
+    size_or_type: u32,
+
+    /// For `BTF_KIND_INT`, the encoding flags.
+    int_encoding: u32,
+
+    /// For `BTF_KIND_ARRAY`, the element type and number of
+    /// elements.
+    array: Option<(u32, u32)>,
+
+    /// For `BTF_KIND_STRUCT` and `BTF_KIND_UNION`, the members.
+    members: Vec<BtfMember>,
+
+    /// For `BTF_KIND_ENUM` and `BTF_KIND_ENUM64`, the name offset
+    /// and value of each enumerator.
+    enumerators: Vec<(u32, i64)>,
+}
+
+/// Unpacks a BPF Type Format (BTF) blob, either from a standalone
+/// file such as `/sys/kernel/btf/vmlinux`, or from the `.BTF`
+/// section of an object file.
+///
+/// The BTF of a kernel module is split BTF, which extends the BTF of
+/// the kernel itself.  Its type ids continue from the last type id of
+/// the base, and its string offsets from the end of the base's
+/// strings.
+pub struct Btf<'a> {
+    /// The strings of the base BTF, or empty if this isn't split BTF.
+    base_strings: &'a [u8],
+
+    strings: &'a [u8],
+
+    /// All types, including those of the base BTF.  Index zero is the
+    /// implicit `void` type.
+    types: Vec<Option<BtfType>>,
+
+    /// The id of the first type described by this blob, rather than
+    /// by the base BTF.
+    first_type_id: usize,
+}
+
+/// Reads little- or big-endian integers from a byte slice.
+struct BtfReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> BtfReader<'a> {
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes: [u8; 4] = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| Error::InvalidBtf("unexpected end of data".into()))?
+            .try_into()
+            .unwrap();
+        self.pos += 4;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn skip(&mut self, num_bytes: usize) {
+        self.pos += num_bytes;
+    }
+}
+
+impl<'a> Btf<'a> {
+    /// Parse a BTF blob.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, Error> {
+        Self::parse_split(bytes, None)
+    }
+
+    /// Parse a BTF blob that extends the `base` BTF, if any.
+    pub fn parse_split(bytes: &'a [u8], base: Option<Btf<'a>>) -> Result<Self, Error> {
+        if !is_btf(bytes) {
+            return Err(Error::InvalidBtf("missing BTF magic number".into()));
+        }
+        let big_endian = u16::from_be_bytes([bytes[0], bytes[1]]) == BTF_MAGIC;
+
+        let mut header = BtfReader {
+            bytes,
+            pos: 4,
+            big_endian,
+        };
+        let hdr_len = header.u32()? as usize;
+        let type_off = header.u32()? as usize;
+        let type_len = header.u32()? as usize;
+        let str_off = header.u32()? as usize;
+        let str_len = header.u32()? as usize;
+
+        let section = |offset: usize, len: usize| {
+            let start = hdr_len.checked_add(offset);
+            let end = start.and_then(|start| start.checked_add(len));
+            start
+                .zip(end)
+                .and_then(|(start, end)| bytes.get(start..end))
+                .ok_or_else(|| Error::InvalidBtf("section extends past end of data".into()))
+        };
+        let type_bytes = section(type_off, type_len)?;
+        let strings = section(str_off, str_len)?;
+
+        let mut reader = BtfReader {
+            bytes: type_bytes,
+            pos: 0,
+            big_endian,
+        };
+        let (base_strings, mut types) = match base {
+            Some(base) => (base.strings, base.types),
+            None => (&[][..], vec![None]),
+        };
+        let first_type_id = types.len();
+        while reader.pos < type_bytes.len() {
+            let name_off = reader.u32()?;
+            let info = reader.u32()?;
+            let size_or_type = reader.u32()?;
+
+            let vlen = (info & 0xffff) as usize;
+            let kind = (info >> 24) & 0x1f;
+            let kind_flag = (info >> 31) != 0;
+
+            let mut btf_type = BtfType {
+                name_off,
+                kind,
+                size_or_type,
+                int_encoding: 0,
+                array: None,
+                members: Vec::new(),
+                enumerators: Vec::new(),
+            };
+
+            match kind {
+                BTF_KIND_INT => {
+                    btf_type.int_encoding = reader.u32()? >> 24;
+                }
+                BTF_KIND_ARRAY => {
+                    let element_type = reader.u32()?;
+                    let _index_type = reader.u32()?;
+                    let num_elements = reader.u32()?;
+                    btf_type.array = Some((element_type, num_elements));
+                }
+                BTF_KIND_STRUCT | BTF_KIND_UNION => {
+                    btf_type.members = (0..vlen)
+                        .map(|_| -> Result<_, Error> {
+                            let name_off = reader.u32()?;
+                            let type_id = reader.u32()?;
+                            let offset = reader.u32()?;
+                            let (offset_bits, bitfield_size) = if kind_flag {
+                                (offset & 0xffffff, offset >> 24)
+                            } else {
+                                (offset, 0)
+                            };
+                            Ok(BtfMember {
+                                name_off,
+                                type_id,
+                                offset_bits,
+                                bitfield_size,
+                            })
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                BTF_KIND_ENUM => {
+                    btf_type.enumerators = (0..vlen)
+                        .map(|_| -> Result<_, Error> {
+                            let name_off = reader.u32()?;
+                            let value = reader.u32()?;
+                            let value = if kind_flag {
+                                value as i32 as i64
+                            } else {
+                                value as i64
+                            };
+                            Ok((name_off, value))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                BTF_KIND_ENUM64 => {
+                    btf_type.enumerators = (0..vlen)
+                        .map(|_| -> Result<_, Error> {
+                            let name_off = reader.u32()?;
+                            let lo = reader.u32()? as u64;
+                            let hi = reader.u32()? as u64;
+                            Ok((name_off, ((hi << 32) | lo) as i64))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                BTF_KIND_FUNC_PROTO => reader.skip(8 * vlen),
+                BTF_KIND_VAR => reader.skip(4),
+                BTF_KIND_DATASEC => reader.skip(12 * vlen),
+                BTF_KIND_DECL_TAG => reader.skip(4),
+                BTF_KIND_PTR | BTF_KIND_FWD | BTF_KIND_TYPEDEF | BTF_KIND_VOLATILE
+                | BTF_KIND_CONST | BTF_KIND_RESTRICT | BTF_KIND_FUNC | BTF_KIND_FLOAT
+                | BTF_KIND_TYPE_TAG => {}
+                other => {
+                    return Err(Error::InvalidBtf(format!("unknown BTF kind {other}")));
+                }
+            }
+
+            types.push(Some(btf_type));
+        }
+        check_type_loops(&types)?;
+
+        Ok(Self {
+            base_strings,
+            strings,
+            types,
+            first_type_id,
+        })
+    }
+
+    fn string(&self, offset: u32) -> Option<String> {
+        let offset = offset as usize;
+        let bytes = match offset.checked_sub(self.base_strings.len()) {
+            Some(offset) => self.strings.get(offset..)?,
+            None => &self.base_strings[offset..],
+        };
+        let len = bytes.iter().position(|&b| b == 0)?;
+        (len > 0).then(|| String::from_utf8_lossy(&bytes[..len]).into())
+    }
+
+    fn get(&self, type_id: u32) -> Option<&BtfType> {
+        self.types.get(type_id as usize)?.as_ref()
+    }
+
+    /// Follow typedefs and type modifiers to the underlying type.  The
+    /// chain ends, since `check_type_loops` rejects BTF in which it
+    /// would lead back to itself.
+    fn resolve(&self, type_id: u32) -> u32 {
+        std::iter::successors(Some(type_id), |&type_id| {
+            self.get(type_id)
+                .filter(|btf_type| {
+                    matches!(
+                        btf_type.kind,
+                        BTF_KIND_TYPEDEF
+                            | BTF_KIND_VOLATILE
+                            | BTF_KIND_CONST
+                            | BTF_KIND_RESTRICT
+                            | BTF_KIND_TYPE_TAG
+                    )
+                })
+                .map(|btf_type| btf_type.size_or_type)
+        })
+        .last()
+        .unwrap()
+    }
+
+    /// Returns the name of the type, in C syntax.
+    fn type_name(&self, type_id: u32) -> String {
+        let Some(btf_type) = self.get(type_id) else {
+            return "void".into();
+        };
+        match btf_type.kind {
+            BTF_KIND_PTR => format!("{}*", self.type_name(btf_type.size_or_type)),
+            BTF_KIND_ARRAY => {
+                let (element_type, num_elements) = btf_type.array.unwrap();
+                format!("{}[{num_elements}]", self.type_name(element_type))
+            }
+            BTF_KIND_CONST => format!("const {}", self.type_name(btf_type.size_or_type)),
+            BTF_KIND_VOLATILE => format!("volatile {}", self.type_name(btf_type.size_or_type)),
+            BTF_KIND_RESTRICT | BTF_KIND_TYPE_TAG => self.type_name(btf_type.size_or_type),
+            _ => self
+                .string(btf_type.name_off)
+                .unwrap_or_else(|| "unknown_class".into()),
+        }
+    }
+
+    /// Returns the size of the type, in bytes.
+    fn size_bytes(&self, type_id: u32) -> Option<usize> {
+        let type_id = self.resolve(type_id);
+        let btf_type = self.get(type_id)?;
+        match btf_type.kind {
+            BTF_KIND_INT | BTF_KIND_STRUCT | BTF_KIND_UNION | BTF_KIND_ENUM | BTF_KIND_ENUM64
+            | BTF_KIND_FLOAT => Some(btf_type.size_or_type as usize),
+            BTF_KIND_PTR => Some(std::mem::size_of::<usize>()),
+            BTF_KIND_ARRAY => {
+                let (element_type, num_elements) = btf_type.array.unwrap();
+                Some(self.size_bytes(element_type)? * num_elements as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the broad category of the type.
+    fn type_kind(&self, type_id: u32) -> TypeKind {
+        let type_id = self.resolve(type_id);
+        let Some(btf_type) = self.get(type_id) else {
+            return TypeKind::Unknown;
+        };
+        match btf_type.kind {
+            BTF_KIND_PTR => TypeKind::Pointer,
+            BTF_KIND_ENUM | BTF_KIND_ENUM64 => TypeKind::Enumeration,
+            BTF_KIND_STRUCT | BTF_KIND_UNION => TypeKind::Aggregate,
+            BTF_KIND_FLOAT => TypeKind::Float,
+            BTF_KIND_INT if btf_type.int_encoding & BTF_INT_BOOL != 0 => TypeKind::Boolean,
+            BTF_KIND_INT if btf_type.int_encoding & BTF_INT_CHAR != 0 => TypeKind::Character,
+            BTF_KIND_INT if btf_type.int_encoding & BTF_INT_SIGNED != 0 => TypeKind::Signed,
+            BTF_KIND_INT => TypeKind::Unsigned,
+            _ => TypeKind::Unknown,
+        }
+    }
+
+    /// If the type is a pointer, returns the type ultimately pointed
+    /// to.
+    fn pointee(&self, type_id: u32) -> Option<&BtfType> {
+        let btf_type = self.get(self.resolve(type_id))?;
+        if btf_type.kind != BTF_KIND_PTR {
+            return None;
+        }
+        let pointee = std::iter::successors(Some(btf_type.size_or_type), |&type_id| {
+            let btf_type = self.get(self.resolve(type_id))?;
+            (btf_type.kind == BTF_KIND_PTR).then_some(btf_type.size_or_type)
+        })
+        .last()
+        .unwrap();
+        self.get(self.resolve(pointee))
+    }
+
+    /// If the type is a pointer, returns the name of the type
+    /// ultimately pointed to.
+    fn pointee_name(&self, type_id: u32) -> Option<String> {
+        self.string(self.pointee(type_id)?.name_off)
+    }
+
+    /// If the type is a pointer to a struct or union, returns the
+    /// kind of the type pointed to.
+    fn pointee_class(&self, type_id: u32) -> Option<ClassKind> {
+        match self.pointee(type_id)?.kind {
+            BTF_KIND_STRUCT => Some(ClassKind::Struct),
+            BTF_KIND_UNION => Some(ClassKind::Union),
+            _ => None,
+        }
+    }
+
+    /// Returns the enumerators of an enumeration type.
+    fn enumerators(&self, type_id: u32) -> Vec<Enumerator> {
+        self.get(self.resolve(type_id))
+            .map(|btf_type| {
+                btf_type
+                    .enumerators
+                    .iter()
+                    .filter_map(|&(name_off, value)| {
+                        Some(Enumerator {
+                            name: self.string(name_off)?,
+                            value,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Collect the layout of every named struct and union.  Bitfield
+    /// members are placed at their storage unit, as for DWARF input.
+    /// Bitfields of structs without `kind_flag`, whose widths are only
+    /// given by their integer type, are skipped.  For split BTF, only
+    /// the types that it adds to the base are collected.
+    pub fn classes(&self) -> impl Iterator<Item = ClassLayout> + '_ {
+        // The typedef names of each struct, keyed by type id.
+        let mut aliases: HashMap<u32, Vec<String>> = HashMap::new();
+        for btf_type in self.types.iter().flatten() {
+            if btf_type.kind == BTF_KIND_TYPEDEF {
+                if let Some(alias) = self.string(btf_type.name_off) {
+                    let target = self.resolve(btf_type.size_or_type);
+                    aliases.entry(target).or_default().push(alias);
+                }
+            }
+        }
+
+        self.types
+            .iter()
+            .enumerate()
+            .skip(self.first_type_id)
+            .filter_map(|(type_id, btf_type)| Some((type_id as u32, btf_type.as_ref()?)))
+            .filter(|(_, btf_type)| {
+                btf_type.kind == BTF_KIND_STRUCT || btf_type.kind == BTF_KIND_UNION
+            })
+            .filter_map(move |(type_id, btf_type)| {
+                let aliases = aliases.remove(&type_id).unwrap_or_default();
+                let name = self
+                    .string(btf_type.name_off)
+                    .or_else(|| aliases.first().cloned())?;
+                let mut members: Vec<MemberLayout> = btf_type
+                    .members
+                    .iter()
+                    .filter(|member| member.bitfield_size > 0 || member.offset_bits % 8 == 0)
+                    .map(|member| {
+                        let underlying = self.resolve(member.type_id);
+                        let size_bytes = self.size_bytes(underlying).unwrap_or(0);
+                        let bitfield = (member.bitfield_size > 0).then_some(Bitfield {
+                            bit_offset: member.offset_bits as usize,
+                            bit_size: member.bitfield_size as usize,
+                        });
+                        let offset = match bitfield {
+                            Some(bitfield) => bitfield.storage_offset(size_bytes),
+                            None => (member.offset_bits / 8) as usize,
+                        };
+                        MemberLayout {
+                            name: self
+                                .string(member.name_off)
+                                .unwrap_or_else(|| "unknown_name".into()),
+                            type_name: self.type_name(underlying).into(),
+                            kind: self.type_kind(underlying),
+                            is_base_class: false,
+                            offset,
+                            size_bytes,
+                            alignment: None,
+                            enumerators: self.enumerators(underlying),
+                            pointee: self.pointee_name(underlying),
+                            pointee_class: self.pointee_class(underlying),
+                            is_flexible_array: false,
+                            is_incomplete: false,
+                            bitfield,
+                            decl_file: None,
+                            decl_line: None,
+                        }
+                    })
+                    .collect();
+                // A flexible array member is recorded as an array of
+                // zero elements.
+                if let (Some(last), Some(btf_member)) =
+                    (members.last_mut(), btf_type.members.last())
+                {
+                    let underlying = self.resolve(btf_member.type_id);
+                    last.is_flexible_array = last.offset == (btf_member.offset_bits / 8) as usize
+                        && self
+                            .get(underlying)
+                            .and_then(|array_type| array_type.array)
+                            .is_some_and(|(_, num_elements)| num_elements <= 1);
+                }
+                Some(ClassLayout {
+                    name,
+                    namespace: String::new(),
+                    binary: None,
+                    unit_name: None,
+                    decl_file: None,
+                    decl_line: None,
+                    unit_offset: None,
+                    die_offset: None,
+                    has_vtable: false,
+                    aliases,
+                    linkage_names: Vec::new(),
+                    size_bytes: btf_type.size_or_type as usize,
+                    size_kind: SizeKind::Exact,
+                    alignment: None,
+                    members,
+                    variant_part: None,
+                    language: Language::C,
+                    kind: if btf_type.kind == BTF_KIND_UNION {
+                        ClassKind::Union
+                    } else {
+                        ClassKind::Struct
+                    },
+                })
+            })
+    }
+}
+
+/// Returns an error if following typedefs, modifiers, pointers, or
+/// array elements from some type leads back to that type.  Valid BTF
+/// only refers back to a type through the members of a struct or
+/// union, which aren't followed when resolving or naming a type.
+fn check_type_loops(types: &[Option<BtfType>]) -> Result<(), Error> {
+    // Whether each type has been checked, or is on the chain being
+    // followed.
+    let mut checked = vec![false; types.len()];
+    let mut on_chain = vec![false; types.len()];
+    for start in 0..types.len() {
+        let mut chain = Vec::new();
+        let mut type_id = start;
+        while type_id < types.len() && !checked[type_id] {
+            if on_chain[type_id] {
+                return Err(Error::InvalidBtf(format!(
+                    "type {type_id} refers back to itself"
+                )));
+            }
+            on_chain[type_id] = true;
+            chain.push(type_id);
+            let next = types[type_id]
+                .as_ref()
+                .and_then(|btf_type| match btf_type.kind {
+                    BTF_KIND_PTR | BTF_KIND_TYPEDEF | BTF_KIND_VOLATILE | BTF_KIND_CONST
+                    | BTF_KIND_RESTRICT | BTF_KIND_TYPE_TAG => Some(btf_type.size_or_type),
+                    BTF_KIND_ARRAY => btf_type.array.map(|(element_type, _)| element_type),
+                    _ => None,
+                });
+            match next {
+                Some(next) => type_id = next as usize,
+                None => break,
+            }
+        }
+        for type_id in chain {
+            checked[type_id] = true;
+        }
+    }
+    Ok(())
+}
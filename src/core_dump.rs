@@ -0,0 +1,241 @@
+use std::io::{Read as _, Seek as _};
+use std::path::{Path, PathBuf};
+
+use object::elf;
+use object::read::elf::{FileHeader, ProgramHeader};
+use object::Endian as _;
+
+use crate::errors::Error;
+use crate::file_contents::FileContents;
+use crate::inspect::Memory;
+
+/// A `PT_LOAD` segment of a core dump, which holds the memory of one
+/// mapping in the crashed process.
+struct Segment {
+    address: u64,
+    size: u64,
+    file_offset: u64,
+
+    /// The number of bytes stored in the core dump.  The kernel omits
+    /// the contents of unmodified file-backed mappings, such as the
+    /// code of the binary and its libraries, leaving this shorter than
+    /// the segment.
+    file_size: u64,
+}
+
+/// A file mapped into the crashed process, as listed by the `NT_FILE`
+/// note.
+struct MappedFile {
+    start: u64,
+    end: u64,
+    file_offset: u64,
+    path: PathBuf,
+}
+
+/// The memory of a crashed process, read from an ELF core dump.
+/// Memory that the kernel didn't write to the core dump is read from
+/// the files that were mapped there, preferring the binaries given on
+/// the command line to the paths recorded in the core dump, since a
+/// core dump is often inspected on a different machine.
+pub struct CoreDump {
+    bytes: FileContents,
+    little_endian: bool,
+    segments: Vec<Segment>,
+    mapped_files: Vec<MappedFile>,
+    binaries: Vec<PathBuf>,
+}
+
+impl CoreDump {
+    /// Open a core dump, along with the binaries that were loaded by
+    /// the crashed process.
+    pub fn open(path: &Path, binaries: &[PathBuf], use_mmap: bool) -> Result<Self, Error> {
+        let bytes = FileContents::open(path, use_mmap)?;
+        let (little_endian, segments, mapped_files) = match object::FileKind::parse(&*bytes)? {
+            object::FileKind::Elf32 => {
+                parse_core::<elf::FileHeader32<object::Endianness>>(&bytes, path)?
+            }
+            object::FileKind::Elf64 => {
+                parse_core::<elf::FileHeader64<object::Endianness>>(&bytes, path)?
+            }
+            _ => return Err(Error::NotCoreDump(path.to_path_buf())),
+        };
+        Ok(Self {
+            bytes,
+            little_endian,
+            segments,
+            mapped_files,
+            binaries: binaries.to_vec(),
+        })
+    }
+
+    /// Read bytes starting at the address, stopping at the end of the
+    /// segment or mapped file that contains it.  Returns the number of
+    /// bytes read.
+    fn read_partial(&self, address: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| segment.address <= address && address - segment.address < segment.size)
+            .ok_or(Error::UnreadableMemory(address))?;
+        let offset = address - segment.address;
+        if offset < segment.file_size {
+            let num_bytes = buf.len().min((segment.file_size - offset) as usize);
+            let data = segment
+                .file_offset
+                .checked_add(offset)
+                .and_then(|start| usize::try_from(start).ok())
+                .and_then(|start| self.bytes.get(start..start.checked_add(num_bytes)?))
+                .ok_or(Error::UnreadableMemory(address))?;
+            buf[..num_bytes].copy_from_slice(data);
+            return Ok(num_bytes);
+        }
+
+        let mapped = self
+            .mapped_files
+            .iter()
+            .find(|mapped| mapped.start <= address && address < mapped.end)
+            .ok_or(Error::UnreadableMemory(address))?;
+        let num_bytes = buf
+            .len()
+            .min((segment.size - offset) as usize)
+            .min((mapped.end - address) as usize);
+        let buf = &mut buf[..num_bytes];
+
+        // Bytes past the end of the file, such as the `.bss` of a
+        // binary, are zero.
+        buf.fill(0);
+        let file_offset = mapped
+            .file_offset
+            .checked_add(address - mapped.start)
+            .ok_or(Error::UnreadableMemory(address))?;
+        let mut file = std::fs::File::open(self.mapped_path(mapped))?;
+        file.seek(std::io::SeekFrom::Start(file_offset))?;
+        let mut num_read = 0;
+        while num_read < num_bytes {
+            match file.read(&mut buf[num_read..])? {
+                0 => break,
+                n => num_read += n,
+            }
+        }
+        Ok(num_bytes)
+    }
+
+    /// Returns the file to read a mapping from: a binary from the
+    /// command line with the same file name, if any, and otherwise the
+    /// path recorded in the core dump.
+    fn mapped_path<'a>(&'a self, mapped: &'a MappedFile) -> &'a Path {
+        self.binaries
+            .iter()
+            .find(|binary| binary.file_name() == mapped.path.file_name())
+            .unwrap_or(&mapped.path)
+    }
+}
+
+impl Memory for CoreDump {
+    fn read(&self, address: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let mut num_read = 0;
+        while num_read < buf.len() {
+            let next = address
+                .checked_add(num_read as u64)
+                .ok_or(Error::UnreadableMemory(address))?;
+            num_read += self.read_partial(next, &mut buf[num_read..])?;
+        }
+        Ok(())
+    }
+
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+}
+
+/// Read the segments and mapped files of the core dump at `path`.
+fn parse_core<Elf: FileHeader<Endian = object::Endianness>>(
+    data: &[u8],
+    path: &Path,
+) -> Result<(bool, Vec<Segment>, Vec<MappedFile>), Error> {
+    let header = Elf::parse(data)?;
+    let endian = header.endian()?;
+    if header.e_type(endian) != elf::ET_CORE {
+        return Err(Error::NotCoreDump(path.to_path_buf()));
+    }
+
+    let mut segments = Vec::new();
+    let mut mapped_files = Vec::new();
+    for program_header in header.program_headers(endian, data)? {
+        match program_header.p_type(endian) {
+            elf::PT_LOAD => segments.push(Segment {
+                address: program_header.p_vaddr(endian).into(),
+                size: program_header.p_memsz(endian).into(),
+                file_offset: program_header.p_offset(endian).into(),
+                file_size: program_header.p_filesz(endian).into(),
+            }),
+            elf::PT_NOTE => {
+                let Ok(Some(mut notes)) = program_header.notes(endian, data) else {
+                    continue;
+                };
+                while let Ok(Some(note)) = notes.next() {
+                    if note.name() == b"CORE" && note.n_type(endian) == elf::NT_FILE {
+                        let files = parse_nt_file(
+                            note.desc(),
+                            header.is_type_64(),
+                            endian.is_little_endian(),
+                        )
+                        .ok_or_else(|| {
+                            Error::InvalidCoreDump(
+                                path.to_path_buf(),
+                                "the NT_FILE note is truncated".into(),
+                            )
+                        })?;
+                        mapped_files.extend(files);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((endian.is_little_endian(), segments, mapped_files))
+}
+
+/// Parse the `NT_FILE` note, which holds the number of mappings and
+/// the page size, followed by the start, end, and page offset of each
+/// mapping, followed by the null-terminated path of each mapping.
+/// Returns `None` if the note is too short to hold the mappings that
+/// it counts.
+fn parse_nt_file(desc: &[u8], is_64: bool, little_endian: bool) -> Option<Vec<MappedFile>> {
+    let word_size = if is_64 { 8 } else { 4 };
+    let word = |index: usize| -> Option<u64> {
+        let bytes = desc.get(index * word_size..(index + 1) * word_size)?;
+        let mut buf = [0; 8];
+        Some(if little_endian {
+            buf[..word_size].copy_from_slice(bytes);
+            u64::from_le_bytes(buf)
+        } else {
+            buf[8 - word_size..].copy_from_slice(bytes);
+            u64::from_be_bytes(buf)
+        })
+    };
+
+    let count = usize::try_from(word(0)?).ok()?;
+    let page_size = word(1)?;
+    let names_start = count
+        .checked_mul(3)
+        .and_then(|words| words.checked_add(2))
+        .and_then(|words| words.checked_mul(word_size))?;
+    let names = desc
+        .get(names_start..)?
+        .split(|&b| b == 0)
+        .map(|name| PathBuf::from(String::from_utf8_lossy(name).into_owned()));
+
+    (0..count)
+        .zip(names)
+        .map(|(i, path)| {
+            let entry = 2 + 3 * i;
+            Some(MappedFile {
+                start: word(entry)?,
+                end: word(entry + 1)?,
+                file_offset: word(entry + 2)?.checked_mul(page_size)?,
+                path,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,344 @@
+use std::path::{Path, PathBuf};
+
+use object::{Object as _, ObjectSection as _};
+
+use crate::errors::Error;
+use crate::LoadOptions;
+
+/// Find the separate file that holds the debug information of an
+/// object file, if any.  A file given by `--debug-file` is always
+/// used.  Mach-O objects use the `.dSYM` bundle found by `find_dsym`,
+/// and PE objects without DWARF use the PDB file found by `find_pdb`.
+/// Otherwise, the locations are tried in order:
+///
+/// - The file named by `.gnu_debuglink`, in the object's directory,
+///   followed by the same directory under the debug root, such as
+///   `/usr/lib/debug/usr/bin`, and then each `--debug-dir`.  For an
+///   object within the `--sysroot`, its directory under the debug
+///   root is that of its path on the target.  Files whose CRC
+///   doesn't match the one recorded in the debuglink are from a
+///   different build, and are skipped with a warning unless
+///   `--ignore-debuglink-crc` is given.
+/// - `<debug root>/.build-id/xx/yyyy.debug`, where `xxyyyy` is the
+///   object's build-id.  This is where most distributions install
+///   their debug packages.
+/// - If the object has no DWARF of its own, the servers listed in
+///   `$DEBUGINFOD_URLS`.
+pub fn find_debug_file<'data, R: object::ReadRef<'data>>(
+    path: &Path,
+    object: &object::File<'data, R>,
+    load_options: &LoadOptions,
+) -> Result<Option<PathBuf>, Error> {
+    if let Some(local_path) = find_local_debug_file(path, object, load_options)? {
+        return Ok(Some(local_path));
+    }
+
+    match object.build_id()? {
+        Some(build_id) if crate::debug_section_by_name(object, ".debug_info").is_none() => {
+            crate::debuginfod::find_debug_file(build_id)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Find the separate debug file of an object file, as for
+/// `find_debug_file`, without querying any debuginfod servers.
+pub fn find_local_debug_file<'data, R: object::ReadRef<'data>>(
+    path: &Path,
+    object: &object::File<'data, R>,
+    load_options: &LoadOptions,
+) -> Result<Option<PathBuf>, Error> {
+    if let Some(debug_file) = &load_options.debug_file {
+        return Ok(Some(debug_file.clone()));
+    }
+
+    if object.format() == object::BinaryFormat::MachO {
+        return find_dsym(path, object, load_options);
+    }
+
+    if object.format() == object::BinaryFormat::Pe
+        && crate::debug_section_by_name(object, ".debug_info").is_none()
+    {
+        if let Some(pdb_path) = find_pdb(path, object, load_options)? {
+            return Ok(Some(pdb_path));
+        }
+    }
+
+    if let Some((name, crc)) = gnu_debuglink(object)? {
+        let name = String::from_utf8_lossy(name);
+        let object_dir = path.parent().unwrap_or(Path::new(""));
+        let debug_root_dir = target_relative_path(object_dir, load_options)
+            .map(|target_dir| load_options.debug_root.join(target_dir));
+        let candidates = std::iter::once(object_dir.to_path_buf())
+            .chain(debug_root_dir)
+            .chain(load_options.debug_dirs.iter().cloned())
+            .map(|dir| dir.join(&*name))
+            .filter(|path| path.exists());
+        for candidate in candidates {
+            if load_options.ignore_debuglink_crc {
+                return Ok(Some(candidate));
+            }
+            let actual_crc = file_crc(&candidate)?;
+            if actual_crc == crc {
+                return Ok(Some(candidate));
+            }
+            eprintln!(
+                "warning: skipping {}, whose CRC {actual_crc:08x} doesn't match \
+                 the expected {crc:08x} from .gnu_debuglink.  \
+                 Use --ignore-debuglink-crc to use it anyway.",
+                candidate.display()
+            );
+        }
+    }
+
+    let Some(build_id) = object.build_id()? else {
+        return Ok(None);
+    };
+    let build_id_path = build_id_path(&load_options.debug_root, build_id);
+    Ok(build_id_path.filter(|path| path.exists()))
+}
+
+/// Returns the path of a file or directory on the target's
+/// filesystem, relative to its root, or `None` if its absolute path
+/// can't be determined.  Paths within the `--sysroot` are relative to
+/// the sysroot, with symbolic links followed as on the target, and
+/// all others to the host's root.
+fn target_relative_path(path: &Path, load_options: &LoadOptions) -> Option<PathBuf> {
+    if let Some(sysroot) = &load_options.sysroot {
+        let absolute_path = std::path::absolute(path).ok()?;
+        let absolute_sysroot = std::path::absolute(sysroot).ok()?;
+        if let Ok(relative) = absolute_path.strip_prefix(absolute_sysroot) {
+            return Some(crate::resolve_within(sysroot, relative));
+        }
+    }
+    let path = path.canonicalize().ok()?;
+    Some(path.strip_prefix("/").ok()?.to_path_buf())
+}
+
+/// Returns the file name and CRC recorded in `.gnu_debuglink`.  The
+/// `object` crate only reads the section from ELF files, but MinGW's
+/// `objcopy --add-gnu-debuglink` adds the same section to PE files,
+/// so it is read directly for other formats.
+fn gnu_debuglink<'data, R: object::ReadRef<'data>>(
+    object: &object::File<'data, R>,
+) -> Result<Option<(&'data [u8], u32)>, Error> {
+    if object.format() == object::BinaryFormat::Elf {
+        return Ok(object.gnu_debuglink()?);
+    }
+    let Some(section) = object.section_by_name(".gnu_debuglink") else {
+        return Ok(None);
+    };
+
+    // The section holds a null-terminated file name, padded to a
+    // multiple of four bytes, followed by the CRC.
+    let data = section.data()?;
+    let Some(name_len) = data.iter().position(|&byte| byte == 0) else {
+        return Ok(None);
+    };
+    let crc_offset = (name_len + 4) & !3;
+    let Some(crc) = data.get(crc_offset..crc_offset + 4) else {
+        return Ok(None);
+    };
+    let crc = if object.is_little_endian() {
+        u32::from_le_bytes(crc.try_into().unwrap())
+    } else {
+        u32::from_be_bytes(crc.try_into().unwrap())
+    };
+    Ok(Some((&data[..name_len], crc)))
+}
+
+/// Find the DWARF file of a Mach-O object, which `dsymutil` places
+/// in a `.dSYM` bundle as `X.dSYM/Contents/Resources/DWARF/X`.  The
+/// bundle given by `--dsym` is always used.  Otherwise, the bundle
+/// named after the object is tried first, followed by every other
+/// bundle in the object's directory.  Only a DWARF file whose UUID
+/// matches the object's is used, since a bundle left over from an
+/// earlier build would give the wrong layouts.
+fn find_dsym<'data, R: object::ReadRef<'data>>(
+    path: &Path,
+    object: &object::File<'data, R>,
+    load_options: &LoadOptions,
+) -> Result<Option<PathBuf>, Error> {
+    if let Some(dsym) = &load_options.dsym {
+        return Ok(dsym_dwarf_files(dsym)?
+            .into_iter()
+            .next()
+            .or(Some(dsym.clone())));
+    }
+
+    let Some(uuid) = object.mach_uuid()? else {
+        return Ok(None);
+    };
+
+    let mut named_bundle = path.as_os_str().to_owned();
+    named_bundle.push(".dSYM");
+    let named_bundle = PathBuf::from(named_bundle);
+    let object_dir = path.parent().unwrap_or(Path::new(""));
+    let other_bundles = std::fs::read_dir(object_dir.join("."))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "dSYM"))
+        .filter(|bundle| bundle.file_name() != named_bundle.file_name());
+
+    for bundle in std::iter::once(named_bundle.clone()).chain(other_bundles) {
+        for dwarf_file in dsym_dwarf_files(&bundle)? {
+            if mach_uuid(&dwarf_file)? == Some(uuid) {
+                return Ok(Some(dwarf_file));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the PDB file of a PE object, as named by the CodeView record
+/// of its debug directory.  The recorded path is usually an absolute
+/// path on the machine that built the object, so the file name is
+/// first looked for in the object's directory, then at the recorded
+/// path, then in each `--debug-dir`.  As for `.dSYM` bundles, only a
+/// PDB file whose GUID matches the object's is used.
+fn find_pdb<'data, R: object::ReadRef<'data>>(
+    path: &Path,
+    object: &object::File<'data, R>,
+    load_options: &LoadOptions,
+) -> Result<Option<PathBuf>, Error> {
+    let Some(code_view) = object.pdb_info()? else {
+        return Ok(None);
+    };
+    let recorded_path = String::from_utf8_lossy(code_view.path());
+    let file_name = recorded_path
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or(&recorded_path);
+
+    let object_dir = path.parent().unwrap_or(Path::new(""));
+    let candidates = std::iter::once(object_dir.join(file_name))
+        .chain(std::iter::once(PathBuf::from(&*recorded_path)))
+        .chain(
+            load_options
+                .debug_dirs
+                .iter()
+                .map(|dir| dir.join(file_name)),
+        )
+        .filter(|path| path.is_file());
+    for candidate in candidates {
+        let pdb_bytes = crate::FileContents::open(&candidate, load_options.use_mmap)?;
+        let guid = crate::pdb::guid(&pdb_bytes)?;
+        if guid == code_view.guid() {
+            return Ok(Some(candidate));
+        }
+        eprintln!(
+            "warning: skipping {}, whose GUID doesn't match the one recorded in {}",
+            candidate.display(),
+            path.display()
+        );
+    }
+    Ok(None)
+}
+
+/// Returns the DWARF files within a `.dSYM` bundle, or an empty list
+/// if the path isn't a bundle.
+fn dsym_dwarf_files(bundle: &Path) -> Result<Vec<PathBuf>, Error> {
+    let dwarf_dir = bundle.join("Contents").join("Resources").join("DWARF");
+    if !dwarf_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = std::fs::read_dir(dwarf_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    files.sort();
+    Ok(files)
+}
+
+/// Returns the UUID of a Mach-O file, reading only the parts of the
+/// file needed to find it.  Returns `None` for other files.
+fn mach_uuid(path: &Path) -> Result<Option<[u8; 16]>, Error> {
+    let cache = object::ReadCache::new(std::fs::File::open(path)?);
+    match object::File::parse(&cache) {
+        Ok(object) => Ok(object.mach_uuid()?),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Find the supplementary file named by `.gnu_debugaltlink`, which
+/// holds the entries that `dwz` moved out of the debug information
+/// so that they can be shared between binaries.  The section is read
+/// from the separate debug file if there is one, and otherwise from
+/// the object file.  The file is looked for at the path given in the
+/// section, relative to the file that contains the section or within
+/// the `--sysroot` if absolute, and then by its build-id, as for
+/// `find_debug_file`.  Since the debug information can't be read
+/// without it, a supplementary file that is named but can't be found
+/// is an error.
+pub fn find_sup_file(
+    path: &Path,
+    object: &object::File,
+    debug_file: Option<(&Path, &object::File)>,
+    load_options: &LoadOptions,
+) -> Result<Option<PathBuf>, Error> {
+    let altlink = match debug_file {
+        Some((debug_path, debug_obj)) => debug_obj
+            .gnu_debugaltlink()?
+            .map(|altlink| (debug_path, altlink)),
+        None => None,
+    };
+    let altlink = match altlink {
+        Some(altlink) => Some(altlink),
+        None => object.gnu_debugaltlink()?.map(|altlink| (path, altlink)),
+    };
+    let Some((linking_path, (name, build_id))) = altlink else {
+        return Ok(None);
+    };
+
+    // The name is usually an absolute path on the target, such as
+    // `/usr/lib/debug/.dwz/x86_64-linux-gnu/libfoo.debug`.
+    let name = String::from_utf8_lossy(name);
+    let linked_path = linking_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(load_options.target_path(&*name));
+    if linked_path.exists() {
+        return Ok(Some(linked_path));
+    }
+
+    let build_id_path = build_id_path(&load_options.debug_root, build_id);
+    if let Some(build_id_path) = build_id_path.filter(|path| path.exists()) {
+        return Ok(Some(build_id_path));
+    }
+
+    crate::debuginfod::find_debug_file(build_id)?
+        .map(Some)
+        .ok_or(Error::MissingSupplementaryFile(linked_path))
+}
+
+/// Computes the CRC-32 of a file's contents, as recorded in
+/// `.gnu_debuglink`.  The file is read in chunks, since debug files
+/// may be larger than would be reasonable to hold in memory.
+fn file_crc(path: &Path) -> Result<u32, Error> {
+    use std::io::Read as _;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let num_read = file.read(&mut buffer)?;
+        if num_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..num_read]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Returns the path of the debug file for a build-id within a debug
+/// root such as `/usr/lib/debug`.  The first byte of the build-id
+/// names a subdirectory, and the remaining bytes name the file.
+fn build_id_path(debug_root: &Path, build_id: &[u8]) -> Option<PathBuf> {
+    let (first, rest) = build_id.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+    let dir = crate::hex_string(&[*first]);
+    let file_name = crate::hex_string(rest) + ".debug";
+    Some(debug_root.join(".build-id").join(dir).join(file_name))
+}
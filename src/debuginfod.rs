@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use crate::download::download;
+use crate::errors::Error;
+
+/// Returns the servers listed in `$DEBUGINFOD_URLS`, which is a
+/// whitespace-separated list of base URLs.  Empty if the variable is
+/// unset.
+fn server_urls() -> Vec<String> {
+    std::env::var("DEBUGINFOD_URLS")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|url| url.trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// Returns the directory in which downloaded debug files are cached.
+/// This is the same layout as used by elfutils' `debuginfod-find`,
+/// so that files fetched by either tool are shared: the directory is
+/// `$DEBUGINFOD_CACHE_PATH` if set, and otherwise
+/// `$XDG_CACHE_HOME/debuginfod_client`.
+fn cache_dir() -> Result<PathBuf, Error> {
+    match std::env::var_os("DEBUGINFOD_CACHE_PATH") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(crate::cache_home()?.join("debuginfod_client")),
+    }
+}
+
+/// Find the separate debug file for the given build-id, downloading
+/// it from the servers in `$DEBUGINFOD_URLS` if it hasn't already
+/// been cached.  Servers are tried in order, and the first to have
+/// the file is used.  Returns `None` if no server is configured, or
+/// if none of them have the file.
+pub fn find_debug_file(build_id: &[u8]) -> Result<Option<PathBuf>, Error> {
+    let urls = server_urls();
+    if urls.is_empty() {
+        return Ok(None);
+    }
+
+    let build_id = crate::hex_string(build_id);
+    let path = cache_dir()?.join(&build_id).join("debuginfo");
+    if path.exists() {
+        return Ok(Some(path));
+    }
+
+    for url in &urls {
+        let url = format!("{url}/buildid/{build_id}/debuginfo");
+        eprintln!("Downloading {url}");
+        match download(&url, &path) {
+            Ok(()) => return Ok(Some(path)),
+            Err(err) => eprintln!("warning: {err}"),
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,443 @@
+/// Returns the `::`-separated scope that encloses a symbol, such as
+/// `game::Npc` for the method `game::Npc::update(int)`.  The symbol
+/// may be either an Itanium-mangled name, as printed by `nm`, or an
+/// already-demangled name, as printed in a backtrace.
+///
+/// Template arguments are omitted from the scope, since recovering
+/// them from a mangled name requires a full demangler.  The scope
+/// should therefore be compared against `strip_template_args` of a
+/// class name.  Returns `None` if the symbol cannot be interpreted.
+pub fn symbol_scope(symbol: &str) -> Option<String> {
+    let symbol = symbol.trim();
+    let components = if let Some(mangled) = symbol.strip_prefix("_Z") {
+        MangledName::new(mangled).name()?
+    } else {
+        demangled_components(symbol)
+    };
+
+    let (_, scope) = components.split_last()?;
+    (!scope.is_empty()).then(|| scope.join("::"))
+}
+
+/// Remove all template arguments from a name, so that
+/// `std::vector<int>::iterator` becomes `std::vector::iterator`.
+pub fn strip_template_args(name: &str) -> String {
+    let mut depth = 0usize;
+    name.chars()
+        .filter(|&c| match c {
+            '<' => {
+                depth += 1;
+                false
+            }
+            '>' => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+/// Split a demangled name into its `::`-separated components,
+/// ignoring any argument list and template arguments.
+fn demangled_components(symbol: &str) -> Vec<String> {
+    // Drop the argument list, along with anything after it such as
+    // cv-qualifiers or an offset within the function.
+    let mut depth = 0usize;
+    let end = symbol
+        .char_indices()
+        .find(|&(_, c)| match c {
+            '<' => {
+                depth += 1;
+                false
+            }
+            '>' => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            '(' | '+' => depth == 0,
+            _ => false,
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(symbol.len());
+
+    strip_template_args(&symbol[..end])
+        .split("::")
+        .map(|component| component.trim().to_string())
+        .collect()
+}
+
+/// A minimal parser for the `<name>` production of the Itanium C++
+/// ABI mangling scheme.  Only the components of the name are
+/// recovered.  Template arguments and function parameters are
+/// skipped over without being decoded.
+struct MangledName<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+
+    /// The prefixes of the names parsed so far, which later
+    /// components refer to as `S_`, `S0_`, and so on.
+    substitutions: Vec<Vec<String>>,
+
+    /// Whether template arguments that were skipped may have added
+    /// substitutions that are missing from `substitutions`, so that
+    /// later references can't be resolved.
+    substitutions_lost: bool,
+}
+
+impl<'a> MangledName<'a> {
+    fn new(mangled: &'a str) -> Self {
+        Self {
+            bytes: mangled.as_bytes(),
+            pos: 0,
+            substitutions: Vec::new(),
+            substitutions_lost: false,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    /// Parse a `<name>`, returning its components.
+    fn name(&mut self) -> Option<Vec<String>> {
+        let mut components = Vec::new();
+        if self.peek()? == b'N' {
+            self.pos += 1;
+            // Skip cv-qualifiers and ref-qualifiers of a method.
+            while matches!(self.peek()?, b'r' | b'V' | b'K' | b'R' | b'O') {
+                self.pos += 1;
+            }
+            while self.peek()? != b'E' {
+                self.prefix_component(&mut components)?;
+            }
+            self.pos += 1;
+        } else {
+            // An unscoped name in the `std` namespace, such as
+            // `St4swap`, is written with the `St` abbreviation before
+            // its unqualified name.
+            let is_std = self.bytes[self.pos..].starts_with(b"St");
+            self.component(&mut components)?;
+            if is_std {
+                self.component(&mut components)?;
+            }
+            if self.peek() == Some(b'I') {
+                self.substitutions.push(components.clone());
+                self.skip_template_args()?;
+                self.substitutions.push(components.clone());
+            }
+        }
+        Some(components)
+    }
+
+    /// Parse one component of a nested name, as for `component`, and
+    /// record the prefix that it completes as a substitution.  A
+    /// prefix given by a substitution, or by one of the abbreviations
+    /// such as `St`, isn't recorded again, and a prefix followed by
+    /// template arguments is recorded both with and without them.
+    fn prefix_component(&mut self, components: &mut Vec<String>) -> Option<()> {
+        let is_substitution = self.peek()? == b'S';
+        let is_template_args = self.peek()? == b'I';
+        self.component(components)?;
+        if is_template_args || (!is_substitution && !components.is_empty()) {
+            self.substitutions.push(components.clone());
+        }
+        Some(())
+    }
+
+    /// Skip a list of template arguments.  Arguments that name a class
+    /// add substitutions of their own, which aren't tracked.
+    fn skip_template_args(&mut self) -> Option<()> {
+        let start = self.pos;
+        self.skip_balanced()?;
+        let args = &self.bytes[start..self.pos];
+        if args
+            .iter()
+            .any(|byte| matches!(byte, b'0'..=b'9' | b'N' | b'S'))
+        {
+            self.substitutions_lost = true;
+        }
+        Some(())
+    }
+
+    /// Parse one component of a nested name, appending it to the
+    /// list.  Template arguments and ABI tags do not add a component.
+    fn component(&mut self, components: &mut Vec<String>) -> Option<()> {
+        match self.peek()? {
+            b'0'..=b'9' => {
+                let name = self.source_name()?;
+                components.push(name);
+            }
+            b'S' => {
+                self.pos += 1;
+                let expansion: &[&str] = match self.peek()? {
+                    b't' => &["std"],
+                    b'a' => &["std", "allocator"],
+                    b'b' => &["std", "basic_string"],
+                    b's' => &["std", "basic_string"],
+                    b'i' => &["std", "basic_istream"],
+                    b'o' => &["std", "basic_ostream"],
+                    b'd' => &["std", "basic_iostream"],
+                    _ => {
+                        let substitution = self.substitution()?;
+                        components.extend(substitution);
+                        return Some(());
+                    }
+                };
+                self.pos += 1;
+                components.extend(expansion.iter().map(|s| s.to_string()));
+            }
+            b'I' => self.skip_template_args()?,
+            b'B' => {
+                self.pos += 1;
+                self.source_name()?;
+            }
+            b'L' => self.pos += 1,
+            b'C' => {
+                self.pos += 1;
+                if self.next()? == b'I' {
+                    self.pos += 1;
+                }
+                let class_name = components.last()?.clone();
+                components.push(class_name);
+            }
+            b'D' => {
+                self.pos += 1;
+                if !matches!(self.next()?, b'0' | b'1' | b'2') {
+                    return None;
+                }
+                let class_name = components.last()?.clone();
+                components.push(format!("~{class_name}"));
+            }
+            b'a'..=b'z' => {
+                let op = [self.next()?, self.next()?];
+                if &op == b"cv" {
+                    // A conversion operator is followed by a type.
+                    return None;
+                }
+                components.push("operator".into());
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Parse the `<seq-id>_` of a substitution that follows its `S`,
+    /// returning the prefix that it refers to.  `S_` refers to the
+    /// first substitution, and `S<n>_` to the one after the `n`th,
+    /// counting in base 36.
+    fn substitution(&mut self) -> Option<Vec<String>> {
+        let mut index = 0usize;
+        if self.peek()? != b'_' {
+            while self.peek()? != b'_' {
+                let digit = match self.next()? {
+                    byte @ b'0'..=b'9' => byte - b'0',
+                    byte @ b'A'..=b'Z' => byte - b'A' + 10,
+                    _ => return None,
+                };
+                index = index.checked_mul(36)?.checked_add(digit as usize)?;
+            }
+            index += 1;
+        }
+        self.pos += 1;
+        if self.substitutions_lost {
+            return None;
+        }
+        self.substitutions.get(index).cloned()
+    }
+
+    /// Parse a `<source-name>`, which is a length-prefixed identifier.
+    fn source_name(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self.peek()?.is_ascii_digit() {
+            self.pos += 1;
+        }
+        let len: usize = std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()?;
+        let name = self.bytes.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        Some(String::from_utf8_lossy(name).into())
+    }
+
+    /// Skip a construct that is terminated by a matching `E`, such as
+    /// a list of template arguments.  Nested constructs that are also
+    /// terminated by `E` are skipped along with it.
+    fn skip_balanced(&mut self) -> Option<()> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                b'0'..=b'9' => {
+                    self.source_name()?;
+                    continue;
+                }
+                // A literal, such as `Li5E`, is a type followed by its
+                // value, whose digits must not be read as a source
+                // name.  An external name, `L_Z<encoding>E`, is
+                // skipped as a nested construct.
+                b'L' if self.bytes.get(self.pos + 1) != Some(&b'_') => {
+                    self.skip_literal()?;
+                    if depth == 0 {
+                        return Some(());
+                    }
+                    continue;
+                }
+                b'I' | b'N' | b'L' | b'X' | b'J' | b'F' => depth += 1,
+                b'E' => depth = depth.checked_sub(1)?,
+                b'D' if matches!(self.bytes.get(self.pos + 1), Some(b't' | b'T')) => {
+                    self.pos += 1;
+                    depth += 1;
+                }
+                // Substitutions, template parameters, and array
+                // bounds are terminated by an underscore.  Digits
+                // within them must not be read as a source name.
+                b'S' | b'T' | b'A'
+                    if matches!(
+                        self.bytes.get(self.pos + 1),
+                        Some(b'0'..=b'9' | b'A'..=b'Z' | b'_')
+                    ) =>
+                {
+                    while self.next()? != b'_' {}
+                    continue;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+            if depth == 0 {
+                return Some(());
+            }
+        }
+    }
+
+    /// Skip a `L<type><value>E` literal.  The type is a builtin type,
+    /// written as a lowercase letter, or the name of an enumeration.
+    /// The value is a number, which may be negative or, for floating
+    /// point types, hexadecimal.
+    fn skip_literal(&mut self) -> Option<()> {
+        self.pos += 1;
+        match self.peek()? {
+            b'0'..=b'9' => {
+                self.source_name()?;
+            }
+            b'N' => self.skip_balanced()?,
+            b'D' | b'u' => self.pos += 2,
+            _ => self.pos += 1,
+        }
+        while self.next()? != b'E' {}
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_of_method() {
+        assert_eq!(
+            symbol_scope("_ZN4game3Npc6updateEi").as_deref(),
+            Some("game::Npc")
+        );
+    }
+
+    #[test]
+    fn literal_template_arguments() {
+        assert_eq!(symbol_scope("_ZN3FooILi5EE3barEv").as_deref(), Some("Foo"));
+        assert_eq!(
+            symbol_scope("_ZN3FooILin12EE3barEv").as_deref(),
+            Some("Foo")
+        );
+        assert_eq!(
+            symbol_scope("_ZN4game5ArrayIiLm16EE3getEm").as_deref(),
+            Some("game::Array")
+        );
+        assert_eq!(
+            symbol_scope("_ZN3FooIL4Kind2EE3barEv").as_deref(),
+            Some("Foo")
+        );
+    }
+
+    #[test]
+    fn substitutions_in_template_arguments() {
+        assert_eq!(
+            symbol_scope("_ZNSt6vectorIiSaIiEE9push_backEOi").as_deref(),
+            Some("std::vector")
+        );
+        assert_eq!(
+            symbol_scope("_ZN3foo3BarINS_3BazEE6methodEv").as_deref(),
+            Some("foo::Bar")
+        );
+    }
+
+    #[test]
+    fn substitutions_of_earlier_prefixes() {
+        let mut name = MangledName::new("N3foo3BarENS_3BazES0_S1_");
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Bar".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Baz".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Bar".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Baz".into()]));
+
+        let mut name = MangledName::new("N3foo3BarIN3baz3QuxEEES1_");
+        assert!(name.name().is_some());
+        assert_eq!(name.name(), None);
+    }
+
+    #[test]
+    fn std_abbreviation() {
+        assert_eq!(symbol_scope("_ZSt4swapIiEvRT_S1_").as_deref(), Some("std"));
+        assert_eq!(
+            symbol_scope("_ZNSt6vectorINSt7__cxx1112basic_stringIcSt11char_traitsIcESaIcEEESaIS5_EE9push_backEOS5_")
+                .as_deref(),
+            Some("std::vector")
+        );
+        assert_eq!(
+            symbol_scope("_ZNSt3mapINSt7__cxx1112basic_stringIcSt11char_traitsIcESaIcEEEiSt4lessIS5_ESaISt4pairIKS5_iEEE4findERS9_")
+                .as_deref(),
+            Some("std::map")
+        );
+    }
+
+    #[test]
+    fn substitution_of_template_prefix() {
+        // `S_` is `foo`, `S0_` is `foo::Bar`, and `S1_` is
+        // `foo::Bar<int>`, whose template arguments aren't recovered.
+        let mut name = MangledName::new("N3foo3BarIiEES1_S0_S_");
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Bar".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Bar".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into(), "Bar".into()]));
+        assert_eq!(name.name(), Some(vec!["foo".into()]));
+    }
+
+    #[test]
+    fn function_types_in_template_arguments() {
+        assert_eq!(
+            symbol_scope("_ZNKSt8functionIFviEEclEi").as_deref(),
+            Some("std::function")
+        );
+        assert_eq!(
+            symbol_scope("_ZN3foo3BarIFvNS_3BazEEEC2ERKS3_").as_deref(),
+            Some("foo::Bar")
+        );
+        assert_eq!(
+            symbol_scope("_ZN3foo3BarIFvNS_3BazEEED1Ev").as_deref(),
+            Some("foo::Bar")
+        );
+        assert_eq!(
+            symbol_scope("_ZN3foo3BarIPFiNS_3BazES1_EE5InnerIS1_E3getEv").as_deref(),
+            Some("foo::Bar::Inner")
+        );
+    }
+
+    #[test]
+    fn unbalanced_end_is_rejected() {
+        assert_eq!(symbol_scope("_ZN3FooIiEEE3barEv"), None);
+        assert_eq!(symbol_scope("_ZN3FooIEE"), None);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use object::read::elf::{Dyn as _, ElfFile, FileHeader};
+use object::{elf, Object as _};
+
+use crate::errors::Error;
+use crate::file_contents::FileContents;
+use crate::layout::{ClassLayout, TypeKind};
+use crate::LoadOptions;
+
+/// The directories searched after those listed in `/etc/ld.so.conf`,
+/// as by the dynamic linker.
+const DEFAULT_LIBRARY_DIRS: &[&str] = &["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// The dynamic linking information of an ELF file.
+#[derive(Default)]
+struct DynamicInfo {
+    /// The libraries listed by `DT_NEEDED`.
+    needed: Vec<String>,
+
+    /// The directories listed by `DT_RPATH`, which are searched before
+    /// `$LD_LIBRARY_PATH`.  Ignored if `DT_RUNPATH` is present.
+    rpath: Vec<String>,
+
+    /// The directories listed by `DT_RUNPATH`, which are searched
+    /// after `$LD_LIBRARY_PATH`.
+    runpath: Option<Vec<String>>,
+}
+
+/// Find the shared libraries that the binary depends on, directly or
+/// indirectly, as the dynamic linker would when loading it.  Each
+/// library is listed once, in breadth-first order, and the binary
+/// itself is not included.  Libraries that can't be found are
+/// reported with a warning.  With `--sysroot`, the search paths are
+/// those of the target, within the sysroot, and `$LD_LIBRARY_PATH`
+/// is ignored since it belongs to the host.
+pub fn find_dependencies(path: &Path, load_options: &LoadOptions) -> Result<Vec<PathBuf>, Error> {
+    let system_dirs = system_library_dirs(load_options);
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    visited.insert(path.canonicalize()?);
+    let mut pending = VecDeque::from([path.to_path_buf()]);
+    let mut dependencies = Vec::new();
+
+    while let Some(binary) = pending.pop_front() {
+        let bytes = FileContents::open(&binary, load_options.use_mmap)?;
+        let object = object::File::parse(&*bytes)?;
+        let dynamic = match &object {
+            object::File::Elf32(elf) => dynamic_info(elf)?,
+            object::File::Elf64(elf) => dynamic_info(elf)?,
+            _ => DynamicInfo::default(),
+        };
+
+        let origin = binary.parent().unwrap_or(Path::new("."));
+        let expand_origin = |dirs: &[String]| -> Vec<PathBuf> {
+            dirs.iter()
+                .map(|dir| {
+                    if !dir.contains("$ORIGIN") && !dir.contains("${ORIGIN}") {
+                        return load_options.target_path(dir);
+                    }
+                    let origin = origin.to_string_lossy();
+                    PathBuf::from(
+                        dir.replace("${ORIGIN}", &origin)
+                            .replace("$ORIGIN", &origin),
+                    )
+                })
+                .collect()
+        };
+        let ld_library_path: Vec<PathBuf> = std::env::var_os("LD_LIBRARY_PATH")
+            .filter(|_| load_options.sysroot.is_none())
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        let search_dirs: Vec<PathBuf> = match &dynamic.runpath {
+            Some(runpath) => ld_library_path
+                .into_iter()
+                .chain(expand_origin(runpath))
+                .collect(),
+            None => expand_origin(&dynamic.rpath)
+                .into_iter()
+                .chain(ld_library_path)
+                .collect(),
+        };
+
+        for needed in &dynamic.needed {
+            let found = if needed.contains('/') {
+                Some(load_options.target_path(needed)).filter(|path| path.is_file())
+            } else {
+                search_dirs
+                    .iter()
+                    .chain(&system_dirs)
+                    .map(|dir| dir.join(needed))
+                    .find(|candidate| is_compatible(candidate, &object, load_options))
+            };
+            let Some(found) = found else {
+                eprintln!(
+                    "warning: could not find {needed}, needed by {}",
+                    binary.display()
+                );
+                continue;
+            };
+            if visited.insert(found.canonicalize()?) {
+                dependencies.push(found.clone());
+                pending.push_back(found);
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Read the `DT_NEEDED`, `DT_RPATH`, and `DT_RUNPATH` entries of the
+/// dynamic section.
+fn dynamic_info<'data, Elf: FileHeader>(
+    elf: &ElfFile<'data, Elf, &'data [u8]>,
+) -> Result<DynamicInfo, Error> {
+    let endian = elf.endian();
+    let section_table = elf.elf_section_table();
+    let mut info = DynamicInfo::default();
+    let Some((entries, strings_index)) = section_table.dynamic(endian, elf.data())? else {
+        return Ok(info);
+    };
+    let strings = section_table.strings(endian, elf.data(), strings_index)?;
+
+    let split_dirs = |dirs: &[u8]| -> Vec<String> {
+        String::from_utf8_lossy(dirs)
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    for entry in entries {
+        if !entry.is_string(endian) {
+            continue;
+        }
+        let value = entry.string(endian, strings)?;
+        match entry.tag32(endian) {
+            Some(elf::DT_NEEDED) => info
+                .needed
+                .push(String::from_utf8_lossy(value).into_owned()),
+            Some(elf::DT_RPATH) => info.rpath.extend(split_dirs(value)),
+            Some(elf::DT_RUNPATH) => info
+                .runpath
+                .get_or_insert_with(Vec::new)
+                .extend(split_dirs(value)),
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+/// Returns true if the file is a library that could be loaded along
+/// with the object.  The dynamic linker skips libraries built for a
+/// different architecture, such as the 32-bit libraries that share
+/// the search path with 64-bit ones.
+fn is_compatible(candidate: &Path, object: &object::File, load_options: &LoadOptions) -> bool {
+    if !candidate.is_file() {
+        return false;
+    }
+    let Ok(bytes) = FileContents::open(candidate, load_options.use_mmap) else {
+        return false;
+    };
+    object::File::parse(&*bytes).is_ok_and(|library| {
+        library.architecture() == object.architecture() && library.is_64() == object.is_64()
+    })
+}
+
+/// The directories searched for libraries that aren't found through
+/// the binary's own search path: those listed in `/etc/ld.so.conf`,
+/// followed by the default directories, each within the sysroot.
+fn system_library_dirs(load_options: &LoadOptions) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    read_ld_so_conf(
+        &load_options.target_path("/etc/ld.so.conf"),
+        load_options,
+        &mut dirs,
+    );
+    dirs.extend(
+        DEFAULT_LIBRARY_DIRS
+            .iter()
+            .map(|dir| load_options.target_path(dir)),
+    );
+    dirs
+}
+
+/// Read the directories listed in a configuration file of the
+/// dynamic linker, following its `include` directives.  An included
+/// pattern may have a `*` wildcard in its file name, as in
+/// `include /etc/ld.so.conf.d/*.conf`.  Absolute paths within the
+/// file are on the target, and so are resolved within the sysroot.
+fn read_ld_so_conf(path: &Path, load_options: &LoadOptions, dirs: &mut Vec<PathBuf>) {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
+    };
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(pattern) = line.strip_prefix("include") else {
+            dirs.push(load_options.target_path(line));
+            continue;
+        };
+
+        let pattern = path
+            .parent()
+            .unwrap_or(Path::new("/"))
+            .join(load_options.target_path(pattern.trim()));
+        let (Some(dir), Some(file_pattern)) = (pattern.parent(), pattern.file_name()) else {
+            continue;
+        };
+        let file_pattern = file_pattern.to_string_lossy();
+        let mut included: Vec<PathBuf> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|included| {
+                included.file_name().is_some_and(|name| {
+                    let name = name.to_string_lossy();
+                    match file_pattern.split_once('*') {
+                        Some((prefix, suffix)) => {
+                            name.len() >= prefix.len() + suffix.len()
+                                && name.starts_with(prefix)
+                                && name.ends_with(suffix)
+                        }
+                        None => name == file_pattern,
+                    }
+                })
+            })
+            .collect();
+        included.sort();
+        for included in included {
+            read_ld_so_conf(&included, load_options, dirs);
+        }
+    }
+}
+
+/// Complete the members whose type is only declared in the binary
+/// that they were read from, using the definition from another
+/// binary.  Compilers often omit the definition of a class from every
+/// binary but the one that defines its vtable or is otherwise
+/// responsible for it, leaving such members with an unknown size.
+pub fn complete_declarations(classes: &mut [ClassLayout]) {
+    let sizes: HashMap<String, usize> = classes
+        .iter()
+        .flat_map(|class| {
+            [class.qualified_name(), class.name.clone()]
+                .into_iter()
+                .chain(class.aliases.iter().cloned())
+                .map(|name| (name, class.size_bytes))
+        })
+        .collect();
+    for member in classes
+        .iter_mut()
+        .flat_map(|class| class.members.iter_mut())
+        .filter(|member| member.size_bytes == 0 && member.kind == TypeKind::Aggregate)
+    {
+        if let Some(&size_bytes) = sizes.get(&*member.type_name) {
+            member.size_bytes = size_bytes;
+        }
+    }
+}
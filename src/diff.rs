@@ -0,0 +1,178 @@
+use itertools::Itertools as _;
+
+use crate::errors::Error;
+use crate::layout::{ClassIndex, ClassLayout, MemberLayout};
+use crate::search_filter::SearchFilter;
+
+/// The differences between two versions of a class that share the
+/// same qualified name.
+pub struct ClassDiff<'a> {
+    pub old: &'a ClassLayout,
+    pub new: &'a ClassLayout,
+    pub members: Vec<MemberChange<'a>>,
+}
+
+/// A change to a single data member or base class.  Members are
+/// matched by name, and base classes by type.
+pub enum MemberChange<'a> {
+    Added(&'a MemberLayout),
+    Removed(&'a MemberLayout),
+    Changed {
+        old: &'a MemberLayout,
+        new: &'a MemberLayout,
+    },
+}
+
+/// The result of comparing the classes of two binaries.  Classes are
+/// listed in order of their qualified name.
+pub struct Diff<'a> {
+    /// Classes present only in the old binary.
+    pub removed: Vec<&'a ClassLayout>,
+
+    /// Classes present only in the new binary.
+    pub added: Vec<&'a ClassLayout>,
+
+    /// Classes present in both binaries, whose size or members differ.
+    pub changed: Vec<ClassDiff<'a>>,
+}
+
+impl<'a> Diff<'a> {
+    /// Compare the classes of two binaries, considering only the
+    /// classes that match the search filter in either binary.  When a
+    /// class is defined more than once, the first definition is used.
+    pub fn new(old: &'a ClassIndex, new: &'a ClassIndex, search_filter: &SearchFilter) -> Self {
+        let old_names: Vec<_> = old
+            .iter()
+            .filter(|class| search_filter.matches(class, old))
+            .map(|class| class.qualified_name())
+            .collect();
+        let new_names: Vec<_> = new
+            .iter()
+            .filter(|class| search_filter.matches(class, new))
+            .map(|class| class.qualified_name())
+            .collect();
+
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for name in old_names.iter().chain(&new_names).sorted().dedup() {
+            match (old.get(name), new.get(name)) {
+                (Some(old_class), None) => removed.push(old_class),
+                (None, Some(new_class)) => added.push(new_class),
+                (Some(old_class), Some(new_class)) => {
+                    let class_diff = ClassDiff::new(old_class, new_class);
+                    if class_diff.is_changed() {
+                        changed.push(class_diff);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        Self {
+            removed,
+            added,
+            changed,
+        }
+    }
+}
+
+impl<'a> ClassDiff<'a> {
+    fn new(old: &'a ClassLayout, new: &'a ClassLayout) -> Self {
+        let key = |member: &MemberLayout| {
+            if member.is_base_class {
+                (true, member.type_name.to_string())
+            } else {
+                (false, member.name.clone())
+            }
+        };
+
+        let mut members = Vec::new();
+        for old_member in &old.members {
+            match new.members.iter().find(|m| key(m) == key(old_member)) {
+                None => members.push(MemberChange::Removed(old_member)),
+                Some(new_member) => {
+                    let is_same = old_member.offset == new_member.offset
+                        && old_member.size_bytes == new_member.size_bytes
+                        && old_member.type_name == new_member.type_name;
+                    if !is_same {
+                        members.push(MemberChange::Changed {
+                            old: old_member,
+                            new: new_member,
+                        });
+                    }
+                }
+            }
+        }
+        for new_member in &new.members {
+            if !old.members.iter().any(|m| key(m) == key(new_member)) {
+                members.push(MemberChange::Added(new_member));
+            }
+        }
+
+        Self { old, new, members }
+    }
+
+    fn is_changed(&self) -> bool {
+        self.old.size_bytes != self.new.size_bytes || !self.members.is_empty()
+    }
+}
+
+/// Print the classes whose size or member layout differ between two
+/// binaries, along with the classes that were added or removed.
+pub fn print_diff(
+    old: impl Iterator<Item = ClassLayout>,
+    new: impl Iterator<Item = ClassLayout>,
+    search_filter: &SearchFilter,
+) -> Result<(), Error> {
+    let old = ClassIndex::new(old);
+    let new = ClassIndex::new(new);
+    let diff = Diff::new(&old, &new, search_filter);
+
+    for class in &diff.removed {
+        println!("- {} ({} bytes)", class.qualified_name(), class.size_bytes);
+    }
+    for class in &diff.added {
+        println!("+ {} ({} bytes)", class.qualified_name(), class.size_bytes);
+    }
+
+    for class_diff in &diff.changed {
+        let (old, new) = (class_diff.old, class_diff.new);
+        if old.size_bytes == new.size_bytes {
+            println!("~ {} ({} bytes)", new.qualified_name(), new.size_bytes);
+        } else {
+            println!(
+                "~ {} ({} -> {} bytes)",
+                new.qualified_name(),
+                old.size_bytes,
+                new.size_bytes
+            );
+        }
+
+        for change in &class_diff.members {
+            match change {
+                MemberChange::Removed(member) => println!(
+                    "    - {} {} at 0x{:x}, {} bytes",
+                    member.type_name, member.name, member.offset, member.size_bytes
+                ),
+                MemberChange::Added(member) => println!(
+                    "    + {} {} at 0x{:x}, {} bytes",
+                    member.type_name, member.name, member.offset, member.size_bytes
+                ),
+                MemberChange::Changed { old, new } => println!(
+                    "    ~ {} {} at 0x{:x}, {} bytes -> {} {} at 0x{:x}, {} bytes",
+                    old.type_name,
+                    old.name,
+                    old.offset,
+                    old.size_bytes,
+                    new.type_name,
+                    new.name,
+                    new.offset,
+                    new.size_bytes
+                ),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use crate::errors::Error;
+
+/// The response headers of a download that let the server tell
+/// whether its copy has changed since.
+#[derive(Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Read the validators saved by `save`.  Missing or unreadable
+    /// files have no validators.
+    fn load(path: &Path) -> Self {
+        let contents = std::fs::read_to_string(path).unwrap_or_default();
+        let mut validators = Self::default();
+        for (name, value) in contents.lines().filter_map(|line| line.split_once(": ")) {
+            match name {
+                "ETag" => validators.etag = Some(value.to_string()),
+                "Last-Modified" => validators.last_modified = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        validators
+    }
+
+    /// Save the validators to a file, one header per line, or remove
+    /// the file if there are none.
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let contents: String = [("ETag", &self.etag), ("Last-Modified", &self.last_modified)]
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{name}: {}\n", value.as_ref()?)))
+            .collect();
+        if contents.is_empty() {
+            match std::fs::remove_file(path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        } else {
+            std::fs::write(path, contents)
+        }
+    }
+}
+
+/// Download `url` into the file at `path`.  The file is written under
+/// a temporary name and renamed once complete, so that an interrupted
+/// download never leaves a partial file at `path`.
+pub fn download(url: &str, path: &Path) -> Result<(), Error> {
+    fetch(url, path, &Validators::default()).map(|_| ())
+}
+
+/// Download `url` into the file at `path`, as for `download`.  If
+/// `path` already holds an earlier download, the request is
+/// conditional on the ETag and modification time saved in the
+/// `validators_path` file, so that it is only downloaded again if the
+/// server has a newer copy.  If the server can't be reached, the
+/// earlier download is used with a warning.
+fn download_if_changed(url: &str, path: &Path, validators_path: &Path) -> Result<(), Error> {
+    let is_cached = path.is_file();
+    let validators = if is_cached {
+        Validators::load(validators_path)
+    } else {
+        Validators::default()
+    };
+    match fetch(url, path, &validators) {
+        Ok(Some(validators)) => Ok(validators.save(validators_path)?),
+        Ok(None) => Ok(()),
+        Err(Error::Download(_, reason)) if is_cached => {
+            eprintln!(
+                "warning: could not check {url} for changes, using the earlier download: {reason}"
+            );
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Request `url`, conditional on the `validators` of an earlier
+/// download, and write the response to `path`.  Returns the
+/// validators of the new download, or `None` if the server reports
+/// that the earlier download is unchanged.
+fn fetch(url: &str, path: &Path, validators: &Validators) -> Result<Option<Validators>, Error> {
+    let mut request = ureq::get(url);
+    if let Some(etag) = &validators.etag {
+        request = request.header("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header("If-Modified-Since", last_modified);
+    }
+    let response = request
+        .call()
+        .map_err(|err| Error::Download(url.to_string(), err.to_string()))?;
+    if response.status() == 304 {
+        return Ok(None);
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let received = Validators {
+        etag: header("ETag"),
+        last_modified: header("Last-Modified"),
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    let written = std::fs::File::create(&tmp_path).and_then(|mut file| {
+        std::io::copy(&mut response.into_body().into_reader(), &mut file)?;
+        std::fs::rename(&tmp_path, path)
+    });
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(Error::Download(url.to_string(), err.to_string()));
+    }
+    Ok(Some(received))
+}
+
+/// The file holding the validators of the download at `path`.  The
+/// name starts with a dot, which a downloaded file's name never does,
+/// as ensured by `sanitize_file_name`.
+fn validators_path(path: &Path) -> PathBuf {
+    path.with_file_name(".validators")
+}
+
+/// Returns the local copy of a file given on the command line.  A
+/// path that is an HTTP or HTTPS URL is downloaded into the cache
+/// directory, unless it has been downloaded before, so that binaries
+/// can be read directly from an artifact server or symbol store.  Any
+/// other path is returned unchanged.
+///
+/// Each download keeps the file name from its URL, within a directory
+/// named by a hash of the URL, so that it is recognized by its name
+/// just as a local file would be.  A file downloaded before is
+/// revalidated with the server, as described in
+/// `download_if_changed`.
+pub fn local_path(path: &Path) -> Result<PathBuf, Error> {
+    let Some(url) = path
+        .to_str()
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+    else {
+        return Ok(path.to_path_buf());
+    };
+
+    let file_name = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('/')
+        .next()
+        .map(sanitize_file_name)
+        .filter(|file_name| !file_name.is_empty())
+        .unwrap_or_else(|| "download".to_string());
+    let url_hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    let local_path = crate::cache_home()?
+        .join("dwarf-to-struct")
+        .join("downloads")
+        .join(format!("{url_hash:016x}"))
+        .join(file_name);
+    download_if_changed(url, &local_path, &validators_path(&local_path))?;
+    Ok(local_path)
+}
+
+/// Decode the percent-encoding of the last segment of a URL, and
+/// replace any character that isn't safe in a file name with `_`.
+/// Leading dots are removed, so that the name can't refer to a parent
+/// directory or be hidden.  Returns an empty string if nothing is
+/// left.
+fn sanitize_file_name(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '+') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
@@ -0,0 +1,75 @@
+use gimli::{AttributeValue, Reader};
+
+use crate::dwarf_expr;
+
+/// Returns the offset of a data member, in bytes, from its
+/// `DW_AT_data_member_location` attribute.  DWARF 4 and later give
+/// the offset as a constant, but earlier versions allowed only a
+/// location description, so older versions of GCC wrote it as an
+/// expression such as `DW_OP_plus_uconst 8`, which is evaluated here.
+/// In DWARF 2 and 3, `DW_FORM_data4` and `DW_FORM_data8` are read by
+/// `gimli` as a location list, but no producer uses a location list
+/// for a member, so the raw value is used as the offset.
+pub fn member_offset<R: Reader>(
+    attr: &gimli::Attribute<R>,
+    encoding: gimli::Encoding,
+) -> Option<usize> {
+    let offset = match attr.value() {
+        AttributeValue::Udata(offset) => offset,
+        AttributeValue::Sdata(offset) => offset as u64,
+        AttributeValue::Exprloc(expression) => {
+            dwarf_expr::evaluate(expression, encoding, Some(0)).constant()?
+        }
+        AttributeValue::LocationListsRef(_) => attr.raw_value().udata_value()?,
+        _ => return None,
+    };
+    Some(offset as usize)
+}
+
+/// Returns a size, in bytes, from a `DW_AT_byte_size` attribute.
+/// DWARF 2 allowed the size to be given as a block, which is
+/// evaluated as an expression.  Sizes given by a reference to a
+/// variable, as for variable-length arrays, are unknown.
+pub fn byte_size<R: Reader>(value: AttributeValue<R>, encoding: gimli::Encoding) -> Option<usize> {
+    let size = match value {
+        AttributeValue::Udata(size) => size,
+        AttributeValue::Exprloc(expression) => {
+            dwarf_expr::evaluate(expression, encoding, None).constant()?
+        }
+        _ => return None,
+    };
+    Some(size as usize)
+}
+
+/// Returns a constant from an attribute that `gimli` leaves in its
+/// raw form, such as the bounds of an array, whose signedness
+/// depends on their type.  Fixed-size forms are read as unsigned,
+/// since producers write negative bounds with `DW_FORM_sdata`.
+/// Bounds given by an expression are evaluated, and those that are
+/// only known at runtime, such as a reference to a variable, are
+/// `None`.
+pub fn constant<R: Reader>(value: AttributeValue<R>, encoding: gimli::Encoding) -> Option<i64> {
+    match value {
+        AttributeValue::Sdata(value) => Some(value),
+        AttributeValue::Exprloc(expression) => dwarf_expr::evaluate(expression, encoding, None)
+            .constant()
+            .map(|value| value as i64),
+        value => value.udata_value().map(|value| value as i64),
+    }
+}
+
+/// Returns the address of a statically-allocated variable, from a
+/// `DW_AT_location` of the form `DW_OP_addr`, optionally followed by
+/// a constant offset, as written for the members of a Fortran COMMON
+/// block.  Returns `None` for other locations.
+pub fn static_address<R: Reader>(
+    value: AttributeValue<R>,
+    encoding: gimli::Encoding,
+) -> Option<u64> {
+    match value {
+        AttributeValue::Exprloc(expression) => {
+            dwarf_expr::evaluate(expression, encoding, None).constant()
+        }
+        _ => None,
+    }
+}
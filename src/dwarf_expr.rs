@@ -0,0 +1,136 @@
+use gimli::{Operation, Reader};
+
+/// The result of evaluating a DWARF expression without a running
+/// program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    /// The expression computes a value that is known statically, such
+    /// as a member offset or the address of a global variable.
+    Constant(u64),
+
+    /// The expression depends on the state of a running program, such
+    /// as the contents of memory or registers, or the address of the
+    /// object being described.  The offset of a virtual base class,
+    /// read from the vtable, and the bounds of a Fortran allocatable
+    /// array, read from its descriptor, are both of this kind.  An
+    /// expression that can't be parsed is also reported this way.
+    RequiresRuntime,
+}
+
+impl Evaluation {
+    /// Returns the value of the expression, if it is known statically.
+    pub fn constant(self) -> Option<u64> {
+        match self {
+            Evaluation::Constant(value) => Some(value),
+            Evaluation::RequiresRuntime => None,
+        }
+    }
+}
+
+/// Evaluate a DWARF expression, starting from a stack that holds the
+/// `initial` value, if any, as DWARF specifies for
+/// `DW_AT_data_member_location`, which starts from the address of the
+/// enclosing object.  Constants, addresses, arithmetic, and operations
+/// that only rearrange the stack are evaluated.  Any other operation
+/// requires a running program.
+pub fn evaluate<R: Reader>(
+    expression: gimli::Expression<R>,
+    encoding: gimli::Encoding,
+    initial: Option<u64>,
+) -> Evaluation {
+    match evaluate_stack(expression, encoding, initial) {
+        Some(value) => Evaluation::Constant(value),
+        None => Evaluation::RequiresRuntime,
+    }
+}
+
+/// Evaluate an expression as for `evaluate`, returning the value at
+/// the top of the stack, or `None` if the expression can't be
+/// evaluated statically.
+fn evaluate_stack<R: Reader>(
+    expression: gimli::Expression<R>,
+    encoding: gimli::Encoding,
+    initial: Option<u64>,
+) -> Option<u64> {
+    let mut stack: Vec<u64> = initial.into_iter().collect();
+    let mut bytecode = expression.0;
+    while !bytecode.is_empty() {
+        match Operation::parse(&mut bytecode, encoding).ok()? {
+            Operation::UnsignedConstant { value } => stack.push(value),
+            Operation::SignedConstant { value } => stack.push(value as u64),
+            Operation::Address { address } => stack.push(address),
+            Operation::PlusConstant { value } => {
+                let top = stack.pop()?;
+                stack.push(top.wrapping_add(value));
+            }
+            Operation::Neg => {
+                let top = stack.pop()?;
+                stack.push(top.wrapping_neg());
+            }
+            Operation::Not => {
+                let top = stack.pop()?;
+                stack.push(!top);
+            }
+            Operation::Drop => {
+                stack.pop()?;
+            }
+            Operation::Pick { index } => {
+                let index = stack.len().checked_sub(1 + index as usize)?;
+                stack.push(stack[index]);
+            }
+            Operation::Swap => {
+                let len = stack.len();
+                if len < 2 {
+                    return None;
+                }
+                stack.swap(len - 1, len - 2);
+            }
+            Operation::Rot => {
+                let len = stack.len();
+                if len < 3 {
+                    return None;
+                }
+                stack[len - 3..].rotate_right(1);
+            }
+            operation @ (Operation::Plus
+            | Operation::Minus
+            | Operation::Mul
+            | Operation::Div
+            | Operation::Mod
+            | Operation::And
+            | Operation::Or
+            | Operation::Xor
+            | Operation::Shl
+            | Operation::Shr
+            | Operation::Shra) => {
+                let rhs = stack.pop()?;
+                let lhs = stack.pop()?;
+                stack.push(binary(&operation, lhs, rhs)?);
+            }
+            Operation::StackValue | Operation::Nop => {}
+            _ => return None,
+        }
+    }
+    stack.pop()
+}
+
+/// Apply an arithmetic or bitwise operation to the two values at the
+/// top of the stack.  `DW_OP_div` is a signed division, while
+/// `DW_OP_mod` is unsigned.  Returns `None` for a division by zero.
+fn binary<R: Reader>(operation: &Operation<R>, lhs: u64, rhs: u64) -> Option<u64> {
+    let value = match operation {
+        Operation::Plus => lhs.wrapping_add(rhs),
+        Operation::Minus => lhs.wrapping_sub(rhs),
+        Operation::Mul => lhs.wrapping_mul(rhs),
+        Operation::Div => (lhs as i64).checked_div(rhs as i64)? as u64,
+        Operation::Mod => lhs.checked_rem(rhs)?,
+        Operation::And => lhs & rhs,
+        Operation::Or => lhs | rhs,
+        Operation::Xor => lhs ^ rhs,
+        Operation::Shl => lhs.checked_shl(rhs.try_into().ok()?).unwrap_or(0),
+        Operation::Shr => lhs.checked_shr(rhs.try_into().ok()?).unwrap_or(0),
+        Operation::Shra => ((lhs as i64) >> rhs.min(63)) as u64,
+        _ => return None,
+    };
+    Some(value)
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use object::macho::DyldCacheHeader;
+use object::read::macho::{DyldCache, DyldSubCacheSlice};
+use object::Endianness;
+
+use crate::errors::Error;
+
+/// Returns true if the bytes are the main file of a dyld shared
+/// cache, in which macOS and iOS keep their system libraries.
+pub fn is_dyld_cache(bytes: &[u8]) -> bool {
+    matches!(
+        object::FileKind::parse(bytes),
+        Ok(object::FileKind::DyldCache)
+    )
+}
+
+/// Returns the paths of the subcache files that accompany the main
+/// file of a dyld shared cache, in the order expected by
+/// `DyldCache::parse`.  Caches from macOS 12 onward are split into
+/// numbered subcaches, such as `dyld_shared_cache_arm64e.01`, followed
+/// by a `.symbols` subcache.
+pub fn subcache_paths(path: &Path, bytes: &[u8]) -> Result<Vec<PathBuf>, Error> {
+    let header = DyldCacheHeader::<Endianness>::parse(bytes)?;
+    let (_, endian) = header.parse_magic()?;
+    let suffixes: Vec<String> = match header.subcaches(endian, bytes)? {
+        Some(DyldSubCacheSlice::V1(subcaches)) => {
+            (1..=subcaches.len()).map(|i| format!(".{i}")).collect()
+        }
+        Some(DyldSubCacheSlice::V2(subcaches)) => subcaches
+            .iter()
+            .map(|subcache| {
+                let suffix = subcache.file_suffix.split(|&b| b == 0).next().unwrap();
+                String::from_utf8_lossy(suffix).into_owned()
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let symbols = header
+        .symbols_subcache_uuid(endian)
+        .map(|_| ".symbols".to_string());
+
+    Ok(suffixes
+        .into_iter()
+        .chain(symbols)
+        .map(|suffix| {
+            let mut subcache_path = path.as_os_str().to_owned();
+            subcache_path.push(suffix);
+            PathBuf::from(subcache_path)
+        })
+        .collect())
+}
+
+/// Parse one image of a dyld shared cache, selected by either its
+/// full install path, such as `/usr/lib/libobjc.A.dylib`, or its file
+/// name, such as `Foundation`.  The image is returned as an ordinary
+/// object file, whose sections are read from the cache.
+pub fn parse_image<'data>(
+    path: &Path,
+    data: &'data [u8],
+    subcache_data: &[&'data [u8]],
+    image_name: Option<&str>,
+) -> Result<object::File<'data>, Error> {
+    let cache = DyldCache::<Endianness>::parse(data, subcache_data)?;
+    let Some(image_name) = image_name else {
+        return Err(Error::NoDyldImage(
+            path.to_path_buf(),
+            cache.images().count(),
+        ));
+    };
+    for image in cache.images() {
+        let image_path = image.path()?;
+        let file_name = Path::new(image_path).file_name();
+        if image_path == image_name || file_name.is_some_and(|file_name| file_name == image_name) {
+            return Ok(image.parse_object()?);
+        }
+    }
+    Err(Error::UnknownDyldImage(
+        image_name.to_string(),
+        path.to_path_buf(),
+    ))
+}
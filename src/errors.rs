@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error)]
 pub enum Error {
     #[error("Could not find home directory from $HOME env var")]
@@ -13,6 +14,68 @@ pub enum Error {
 
     #[error("gimli::Error{{ {0} }}")]
     DWARFError(#[from] gimli::Error),
+
+    #[error("Invalid BTF data: {0}")]
+    InvalidBtf(String),
+
+    #[error("Invalid PDB data: {0}")]
+    InvalidPdb(String),
+
+    #[error("{} has no build-id, which is required to cache its index", .0.display())]
+    NoBuildId(std::path::PathBuf),
+
+    #[error(
+        "Could not find the supplementary debug file {}, named by .gnu_debugaltlink",
+        .0.display()
+    )]
+    MissingSupplementaryFile(std::path::PathBuf),
+
+    #[error("{} has no DWARF debug information, which is required to index it", .0.display())]
+    NotIndexable(std::path::PathBuf),
+
+    #[error(
+        "The debug file {} has a different byte order than the binary it describes",
+        .0.display()
+    )]
+    MismatchedEndianness(std::path::PathBuf),
+
+    #[error("Could not download {0}: {1}")]
+    Download(String, String),
+
+    #[error("Could not find a vmlinux for the running kernel, {0}")]
+    NoKernelImage(String),
+
+    #[error(
+        "{} is a dyld shared cache of {1} images; select one with --dyld-image",
+        .0.display()
+    )]
+    NoDyldImage(std::path::PathBuf, usize),
+
+    #[error("No image named {0} in the dyld shared cache {}", .1.display())]
+    UnknownDyldImage(String, std::path::PathBuf),
+
+    #[error("{} is not an ELF core dump", .0.display())]
+    NotCoreDump(std::path::PathBuf),
+
+    #[error("Invalid core dump {}: {1}", .0.display())]
+    InvalidCoreDump(std::path::PathBuf, String),
+
+    #[error("Could not find a class named {0}")]
+    UnknownClass(String),
+
+    #[error("Could not read the memory map of process {0}: {1}")]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    ProcessMaps(u32, std::io::Error),
+
+    #[error("Reading a running process with --pid is only supported on Linux")]
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    UnsupportedProcess,
+
+    #[error("Could not read memory at {0:#x}")]
+    UnreadableMemory(u64),
+
+    #[error("Found {0} breaking ABI change(s)")]
+    AbiBreak(usize),
 }
 
 impl std::fmt::Debug for Error {
@@ -0,0 +1,42 @@
+use std::path::Path;
+
+/// The contents of an input file, either memory-mapped or read into
+/// memory.  Mapping avoids holding a copy of multi-gigabyte debug
+/// files in memory, since only the pages that are accessed are read
+/// from disk.
+pub enum FileContents {
+    Mapped(memmap2::Mmap),
+    Read(Vec<u8>),
+}
+
+impl FileContents {
+    /// Open a file, memory-mapping it if `use_mmap` is set.  Empty
+    /// files, which cannot be mapped, and files that fail to map are
+    /// read instead.
+    pub fn open(path: &Path, use_mmap: bool) -> std::io::Result<Self> {
+        if use_mmap {
+            let file = std::fs::File::open(path)?;
+            if file.metadata()?.len() > 0 {
+                // SAFETY: The mapping is only sound while no other
+                // process modifies the file, which holds for the build
+                // artifacts that are inspected.
+                if let Ok(mmap) = unsafe { memmap2::Mmap::map(&file) } {
+                    return Ok(Self::Mapped(mmap));
+                }
+            }
+        }
+
+        std::fs::read(path).map(Self::Read)
+    }
+}
+
+impl std::ops::Deref for FileContents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Mapped(mmap) => mmap,
+            Self::Read(bytes) => bytes,
+        }
+    }
+}
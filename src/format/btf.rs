@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use super::{identifier, layout_hash, Printer};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+const BTF_MAGIC: u16 = 0xeb9f;
+const BTF_VERSION: u8 = 1;
+const BTF_HEADER_LEN: u32 = 24;
+
+const BTF_KIND_INT: u32 = 1;
+const BTF_KIND_PTR: u32 = 2;
+const BTF_KIND_ARRAY: u32 = 3;
+const BTF_KIND_STRUCT: u32 = 4;
+const BTF_KIND_FLOAT: u32 = 16;
+const BTF_KIND_DECL_TAG: u32 = 17;
+
+const BTF_INT_SIGNED: u32 = 1 << 0;
+const BTF_INT_CHAR: u32 = 1 << 1;
+const BTF_INT_BOOL: u32 = 1 << 2;
+
+/// A member type, after being mapped onto the subset of BTF kinds
+/// used by this printer.  Used to avoid emitting the same BTF type
+/// more than once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum BtfKey {
+    Int { size_bytes: usize, encoding: u32 },
+    Float { size_bytes: usize },
+    VoidPointer,
+    ByteArray { size_bytes: usize },
+}
+
+/// Prints the matched classes as a BPF Type Format (BTF) blob.  Each
+/// class becomes a `BTF_KIND_STRUCT`.  Members with a primitive type
+/// reference a `BTF_KIND_INT` or `BTF_KIND_FLOAT`, pointers are
+/// emitted as `void*`, and all other members are emitted as byte
+/// arrays of the appropriate size.  Each struct is followed by a
+/// `BTF_KIND_DECL_TAG` holding its layout hash.
+///
+/// Since the type section can only be written once all types are
+/// known, the entire blob is written in the footer.
+pub struct BtfPrinter {
+    /// The encoded type section.
+    types: Vec<u8>,
+
+    /// The encoded string section.
+    strings: Vec<u8>,
+
+    /// The offset of each string in the string section.
+    string_offsets: HashMap<String, u32>,
+
+    /// The type id of each member type already emitted.
+    type_ids: HashMap<BtfKey, u32>,
+
+    /// The number of types emitted.  Type id 0 is reserved for
+    /// `void`, so this is also the id of the most recent type.
+    num_types: u32,
+}
+
+impl BtfPrinter {
+    pub fn new() -> Self {
+        Self {
+            types: Vec::new(),
+            // Offset zero must be the empty string.
+            strings: vec![0],
+            string_offsets: [(String::new(), 0)].into_iter().collect(),
+            type_ids: HashMap::new(),
+            num_types: 0,
+        }
+    }
+
+    fn string(&mut self, s: &str) -> u32 {
+        if let Some(offset) = self.string_offsets.get(s) {
+            return *offset;
+        }
+        let offset = self.strings.len() as u32;
+        self.strings.extend_from_slice(s.as_bytes());
+        self.strings.push(0);
+        self.string_offsets.insert(s.to_string(), offset);
+        offset
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.types.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Append a `struct btf_type` header, returning the new type id.
+    fn push_type(&mut self, name: &str, kind: u32, vlen: u32, size_or_type: u32) -> u32 {
+        let name_off = self.string(name);
+        self.push_u32(name_off);
+        self.push_u32((kind << 24) | vlen);
+        self.push_u32(size_or_type);
+        self.num_types += 1;
+        self.num_types
+    }
+
+    /// Return the type id used for a member, emitting the type if it
+    /// hasn't yet been emitted.
+    fn member_type(&mut self, member: &MemberLayout) -> u32 {
+        self.type_id(member.kind, member.size_bytes)
+    }
+
+    /// Return the type id used for a value of the given kind and
+    /// size, emitting the type if it hasn't yet been emitted.
+    fn type_id(&mut self, kind: TypeKind, size_bytes: usize) -> u32 {
+        let key = match (kind, size_bytes) {
+            (TypeKind::Pointer, _) => BtfKey::VoidPointer,
+            (TypeKind::Float, size_bytes @ (4 | 8 | 16)) => BtfKey::Float { size_bytes },
+            (
+                kind @ (TypeKind::Signed
+                | TypeKind::Unsigned
+                | TypeKind::Boolean
+                | TypeKind::Character
+                | TypeKind::Enumeration),
+                size_bytes @ (1 | 2 | 4 | 8 | 16),
+            ) => {
+                let encoding = match kind {
+                    TypeKind::Signed => BTF_INT_SIGNED,
+                    TypeKind::Boolean if size_bytes == 1 => BTF_INT_BOOL,
+                    TypeKind::Character if size_bytes == 1 => BTF_INT_CHAR,
+                    _ => 0,
+                };
+                BtfKey::Int {
+                    size_bytes,
+                    encoding,
+                }
+            }
+            (_, size_bytes) => BtfKey::ByteArray { size_bytes },
+        };
+
+        if let Some(type_id) = self.type_ids.get(&key) {
+            return *type_id;
+        }
+
+        let type_id = match key {
+            BtfKey::Int {
+                size_bytes,
+                encoding,
+            } => {
+                let bits = 8 * size_bytes as u32;
+                let name = match encoding {
+                    BTF_INT_BOOL => "_Bool".to_string(),
+                    BTF_INT_CHAR => "char".to_string(),
+                    BTF_INT_SIGNED => format!("int{bits}_t"),
+                    _ => format!("uint{bits}_t"),
+                };
+                let type_id = self.push_type(&name, BTF_KIND_INT, 0, size_bytes as u32);
+                self.push_u32((encoding << 24) | bits);
+                type_id
+            }
+            BtfKey::Float { size_bytes } => {
+                let name = match size_bytes {
+                    4 => "float",
+                    8 => "double",
+                    _ => "long double",
+                };
+                self.push_type(name, BTF_KIND_FLOAT, 0, size_bytes as u32)
+            }
+            BtfKey::VoidPointer => self.push_type("", BTF_KIND_PTR, 0, 0),
+            BtfKey::ByteArray { size_bytes } => {
+                let element_type = self.type_id(TypeKind::Unsigned, 1);
+                let index_type = self.type_id(TypeKind::Unsigned, 4);
+                let type_id = self.push_type("", BTF_KIND_ARRAY, 0, 0);
+                self.push_u32(element_type);
+                self.push_u32(index_type);
+                self.push_u32(size_bytes as u32);
+                type_id
+            }
+        };
+
+        self.type_ids.insert(key, type_id);
+        type_id
+    }
+}
+
+impl Printer for BtfPrinter {
+    fn class(&mut self, _out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        // The member types must be emitted before the struct itself,
+        // since the struct's members immediately follow its header.
+        let members: Vec<_> = class
+            .members
+            .iter()
+            .map(|member| {
+                let name = if member.is_base_class {
+                    String::new()
+                } else {
+                    identifier(&member.name)
+                };
+                (name, self.member_type(member), member.offset)
+            })
+            .collect();
+
+        let struct_id = self.push_type(
+            &identifier(&class.name),
+            BTF_KIND_STRUCT,
+            members.len() as u32,
+            class.size_bytes as u32,
+        );
+        for (name, type_id, offset) in members {
+            let name_off = self.string(&name);
+            self.push_u32(name_off);
+            self.push_u32(type_id);
+            self.push_u32(8 * offset as u32);
+        }
+
+        // The layout hash is attached to the struct as a declaration
+        // tag.  A component index of -1 applies the tag to the struct
+        // as a whole, rather than to one of its members.
+        let tag = format!("layout_hash:{}", layout_hash(class));
+        self.push_type(&tag, BTF_KIND_DECL_TAG, 0, struct_id);
+        self.push_u32(u32::MAX);
+
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        let type_len = self.types.len() as u32;
+        let str_len = self.strings.len() as u32;
+
+        out.write_all(&BTF_MAGIC.to_le_bytes())?;
+        out.write_all(&[BTF_VERSION, 0])?;
+        out.write_all(&BTF_HEADER_LEN.to_le_bytes())?;
+        out.write_all(&0u32.to_le_bytes())?;
+        out.write_all(&type_len.to_le_bytes())?;
+        out.write_all(&type_len.to_le_bytes())?;
+        out.write_all(&str_len.to_le_bytes())?;
+        out.write_all(&self.types)?;
+        out.write_all(&self.strings)?;
+        Ok(())
+    }
+}
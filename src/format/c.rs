@@ -0,0 +1,454 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use super::{
+    explicit_alignment, identifier, layout_hash, needs_packing, padded_members, shared_description,
+    Field, HeaderScaffold, Primitive, Printer,
+};
+use crate::layout::{ClassKind, ClassLayout, Language, MemberLayout, VariantPart};
+
+/// Prints C struct definitions.  As with the `rust` format, members
+/// are laid out at the same offsets as in the binary by inserting
+/// explicit padding fields, and any member without a primitive C
+/// equivalent is represented as a byte array of the same size.
+/// Pointers to classes are kept as pointers to the struct or union,
+/// which is declared before its first use, rather than defined, so
+/// that classes that point to each other need no particular order,
+/// and classes only reached through a pointer need not be printed.
+/// Structs whose members are misaligned are packed.  The output is a
+/// header, which reopens the namespaces of C++ classes when it is
+/// included from C++.
+pub struct CPrinter {
+    header: HeaderScaffold,
+
+    /// The structs and unions that have already been declared or
+    /// defined.
+    declared: HashSet<String>,
+
+    /// The namespaces that are currently open, outermost first.
+    namespaces: Vec<String>,
+}
+
+impl CPrinter {
+    pub fn new(header: HeaderScaffold) -> Self {
+        Self {
+            header,
+            declared: HashSet::new(),
+            namespaces: Vec::new(),
+        }
+    }
+
+    /// Close the open namespaces that don't contain the next class,
+    /// and open those that do.  Namespaces only exist in C++, and so
+    /// are only opened when compiled as C++.
+    fn enter_namespaces(
+        &mut self,
+        out: &mut dyn Write,
+        namespaces: Vec<String>,
+    ) -> std::io::Result<()> {
+        let common = self
+            .namespaces
+            .iter()
+            .zip(&namespaces)
+            .take_while(|(open, next)| open == next)
+            .count();
+        if common == self.namespaces.len() && common == namespaces.len() {
+            return Ok(());
+        }
+        writeln!(out, "#ifdef __cplusplus")?;
+        for namespace in self.namespaces[common..].iter().rev() {
+            if namespace == ANONYMOUS_NAMESPACE {
+                writeln!(out, "}} // anonymous namespace")?;
+            } else {
+                writeln!(out, "}} // namespace {namespace}")?;
+            }
+        }
+        for namespace in &namespaces[common..] {
+            if namespace == ANONYMOUS_NAMESPACE {
+                writeln!(out, "namespace {{")?;
+            } else {
+                writeln!(out, "namespace {namespace} {{")?;
+            }
+        }
+        writeln!(out, "#endif")?;
+        writeln!(out)?;
+        self.namespaces = namespaces;
+        Ok(())
+    }
+}
+
+/// The name given to the anonymous namespaces of C++.
+const ANONYMOUS_NAMESPACE: &str = "(anonymous namespace)";
+
+/// Identifiers that cannot be used as a field name in C or C++.
+const C_KEYWORDS: &[&str] = &[
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "namespace",
+    "new",
+    "operator",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "typedef",
+    "union",
+    "unsigned",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+fn c_identifier(name: &str) -> String {
+    let name = identifier(name);
+    if C_KEYWORDS.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+/// The declaration of the struct or union that a member points to,
+/// such as `struct Node`, if the member is a pointer to a class.
+/// Pointers to pointers are left as `void*`.
+fn pointee_declaration(member: &MemberLayout) -> Option<String> {
+    let is_pointer = Primitive::of(member) == Some(Primitive::Pointer);
+    let pointee = member.pointee.as_ref()?;
+    let is_direct = member
+        .type_name
+        .trim_start_matches("const ")
+        .trim_start_matches("volatile ")
+        .strip_suffix('*')
+        == Some(pointee);
+    let keyword = match member.pointee_class? {
+        ClassKind::Union => "union",
+        ClassKind::Struct | ClassKind::Class => "struct",
+    };
+    (is_pointer && is_direct).then(|| format!("{keyword} {}", c_identifier(pointee)))
+}
+
+/// The C type used to represent the member, along with the array
+/// suffix for members that are represented as bytes.
+fn c_type(member: &MemberLayout) -> (String, String) {
+    if let Some(pointee) = pointee_declaration(member) {
+        return (format!("{pointee}*"), String::new());
+    }
+    let primitive = Primitive::of(member).map(|primitive| match primitive {
+        Primitive::I8 => "int8_t",
+        Primitive::I16 => "int16_t",
+        Primitive::I32 => "int32_t",
+        Primitive::I64 => "int64_t",
+        Primitive::U8 => "uint8_t",
+        Primitive::U16 => "uint16_t",
+        Primitive::U32 => "uint32_t",
+        Primitive::U64 => "uint64_t",
+        Primitive::F32 => "float",
+        Primitive::F64 => "double",
+        Primitive::Pointer => "void*",
+    });
+    match primitive {
+        Some(c_type) => (c_type.to_string(), String::new()),
+        None => ("uint8_t".to_string(), format!("[{}]", member.size_bytes)),
+    }
+}
+
+/// The `alignas` specifier that gives a struct the alignment of its
+/// class, if its fields don't already, as described by
+/// `explicit_alignment`.  C only allows the specifier on a member, and
+/// so it is written before the struct's first field.
+fn alignment_specifier(class: &ClassLayout) -> Option<String> {
+    explicit_alignment(class).map(|alignment| format!("alignas({alignment}) "))
+}
+
+/// Print the fields of a struct that places each of the `members` at
+/// its offset, padded to `size_bytes`.  The `specifier`, if any, is
+/// written before the first field.
+fn write_fields(
+    out: &mut dyn Write,
+    indent: &str,
+    members: &[MemberLayout],
+    size_bytes: usize,
+    mut specifier: Option<String>,
+) -> std::io::Result<()> {
+    for field in padded_members(members, size_bytes, c_identifier) {
+        let indent = match &field {
+            Field::Overlapping { .. } => indent.to_string(),
+            _ => format!("{indent}{}", specifier.take().unwrap_or_default()),
+        };
+        match field {
+            Field::Member { member, name } => {
+                let (c_type, suffix) = c_type(member);
+                let incomplete = if member.is_incomplete {
+                    ", incomplete"
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "{indent}{c_type} {name}{suffix}; // {}{incomplete}",
+                    member.type_name
+                )?;
+            }
+            Field::Padding { index, size_bytes } => {
+                writeln!(out, "{indent}uint8_t _padding{index}[{size_bytes}];")?;
+            }
+            Field::Overlapping { member } => {
+                writeln!(
+                    out,
+                    "{indent}// Overlaps previous member: {} {} at {}",
+                    member.type_name, member.name, member.offset
+                )?;
+            }
+            Field::Shared {
+                index,
+                size_bytes,
+                members,
+            } => {
+                writeln!(
+                    out,
+                    "{indent}uint8_t _overlap{index}[{size_bytes}]; // Overlapping members: {}",
+                    shared_description(&members)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the members of a union, each as an alternative at offset 0.
+/// A union may be larger than its largest member, as when it is given
+/// a greater alignment, so a byte array of the union's full size is
+/// added where needed to keep its size.
+fn write_alternatives(
+    out: &mut dyn Write,
+    members: &[MemberLayout],
+    size_bytes: usize,
+    mut specifier: Option<String>,
+) -> std::io::Result<()> {
+    let mut used_names = HashSet::new();
+    for member in members {
+        let specifier = specifier.take().unwrap_or_default();
+        let base = c_identifier(&member.name);
+        let name = if used_names.contains(&base) {
+            format!("{base}_{}", used_names.len())
+        } else {
+            base
+        };
+        used_names.insert(name.clone());
+        let (c_type, suffix) = c_type(member);
+        writeln!(
+            out,
+            "    {specifier}{c_type} {name}{suffix}; // {}",
+            member.type_name
+        )?;
+    }
+    let largest = members.iter().map(|member| member.end()).max();
+    if largest.unwrap_or(0) < size_bytes {
+        let specifier = specifier.unwrap_or_default();
+        writeln!(out, "    {specifier}uint8_t _size[{size_bytes}];")?;
+    }
+    Ok(())
+}
+
+/// Print the body of a class with a variant part, such as a Rust enum
+/// or an Ada variant record, which C has no equivalent for.  The
+/// class becomes a union of structs that each span the whole class:
+/// an anonymous struct that holds the discriminant and the members
+/// shared by every variant, followed by a named struct for each
+/// variant.  The variants may overlap the discriminant, as where Rust
+/// stores the discriminant in a niche of one variant's members.
+fn write_variant_part(
+    out: &mut dyn Write,
+    class: &ClassLayout,
+    variant_part: &VariantPart,
+) -> std::io::Result<()> {
+    let mut shared = class.members.clone();
+    if let Some(discriminant) = &variant_part.discriminant {
+        let is_member = shared
+            .iter()
+            .any(|member| member.name == discriminant.name && member.offset == discriminant.offset);
+        if !is_member {
+            shared.push(discriminant.clone());
+        }
+    }
+
+    let specifier = alignment_specifier(class).unwrap_or_default();
+    writeln!(out, "    {specifier}union {{")?;
+    writeln!(out, "        struct {{")?;
+    write_fields(out, "            ", &shared, class.size_bytes, None)?;
+    writeln!(out, "        }};")?;
+
+    let mut used_names = HashSet::new();
+    for (i, variant) in variant_part.variants.iter().enumerate() {
+        let base = if variant.name.is_empty() {
+            format!("variant{i}")
+        } else {
+            c_identifier(&variant.name)
+        };
+        let name = if used_names.contains(&base) {
+            format!("{base}_{i}")
+        } else {
+            base
+        };
+        used_names.insert(name.clone());
+
+        let values = variant
+            .discriminant_values
+            .iter()
+            .map(|(first, last)| {
+                if first == last {
+                    format!("{first}")
+                } else {
+                    format!("{first}..{last}")
+                }
+            })
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            writeln!(out, "        // Active for any other discriminant")?;
+        } else {
+            writeln!(
+                out,
+                "        // Active when the discriminant is {}",
+                values.join(" | ")
+            )?;
+        }
+
+        writeln!(out, "        struct {{")?;
+        write_fields(
+            out,
+            "            ",
+            &variant.members,
+            class.size_bytes,
+            None,
+        )?;
+        writeln!(out, "        }} {name};")?;
+    }
+    writeln!(out, "    }};")
+}
+
+impl Printer for CPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.header.open(out)?;
+        writeln!(out)?;
+        writeln!(out, "#include <stdalign.h>")?;
+        writeln!(out, "#include <stddef.h>")?;
+        writeln!(out, "#include <stdint.h>")?;
+        writeln!(out)
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.enter_namespaces(out, Vec::new())?;
+        self.header.close(out)
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = c_identifier(&class.name);
+        let size_bytes = class.size_bytes;
+
+        let namespaces = if class.language == Language::Cpp && !class.namespace.is_empty() {
+            class.namespace.split("::").map(str::to_string).collect()
+        } else {
+            Vec::new()
+        };
+        self.enter_namespaces(out, namespaces)?;
+
+        let variant_members = class
+            .variant_part
+            .iter()
+            .flat_map(|variant_part| &variant_part.variants)
+            .flat_map(|variant| &variant.members);
+        // A class may point to itself without being declared first.
+        let keyword = if class.kind == ClassKind::Union {
+            "union"
+        } else {
+            "struct"
+        };
+        let own_declaration = format!("{keyword} {name}");
+        let mut num_declared = 0;
+        for member in class.members.iter().chain(variant_members) {
+            let Some(pointee) = pointee_declaration(member) else {
+                continue;
+            };
+            if pointee != own_declaration && self.declared.insert(pointee.clone()) {
+                let note = if member.is_incomplete {
+                    " // incomplete"
+                } else {
+                    ""
+                };
+                writeln!(out, "{pointee};{note}")?;
+                num_declared += 1;
+            }
+        }
+        if num_declared > 0 {
+            writeln!(out)?;
+        }
+        self.declared.insert(own_declaration);
+
+        writeln!(
+            out,
+            "// `{}`, {size_bytes} bytes, layout hash {}",
+            class.qualified_name(),
+            layout_hash(class)
+        )?;
+        // `#pragma pack` is understood by GCC, Clang, and MSVC alike,
+        // and is restored after the struct so that it affects no other
+        // definitions.
+        let is_packed = needs_packing(class);
+        if is_packed {
+            writeln!(out, "#pragma pack(push, 1)")?;
+        }
+        writeln!(out, "{keyword} {name} {{")?;
+        if class.kind == ClassKind::Union {
+            write_alternatives(out, &class.members, size_bytes, alignment_specifier(class))?;
+        } else {
+            match &class.variant_part {
+                Some(variant_part) => write_variant_part(out, class, variant_part)?,
+                None => write_fields(
+                    out,
+                    "    ",
+                    &class.members,
+                    size_bytes,
+                    alignment_specifier(class),
+                )?,
+            }
+        }
+        writeln!(out, "}};")?;
+        if is_packed {
+            writeln!(out, "#pragma pack(pop)")?;
+        }
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
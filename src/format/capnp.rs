@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use super::{layout_hash, Primitive, Printer};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+/// Prints Cap'n Proto struct schemas.  Fields are numbered in member
+/// order, so the numbering is stable as long as members are only
+/// appended to the class.
+///
+/// Cap'n Proto requires a unique file ID before any declarations.
+/// The ID is derived from the names of the printed classes, so the
+/// schema body is buffered until the footer.
+pub struct CapnpPrinter {
+    body: Vec<u8>,
+
+    /// FNV-1a hash of the class names, used for the file ID.
+    hash: u64,
+}
+
+impl CapnpPrinter {
+    pub fn new() -> Self {
+        Self {
+            body: Vec::new(),
+            hash: 0xcbf29ce484222325,
+        }
+    }
+}
+
+/// Split a C++ name into words, at underscores and any other
+/// character that isn't valid in a Cap'n Proto identifier.
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// Cap'n Proto type names must be UpperCamelCase, without
+/// underscores.
+fn type_name(name: &str) -> String {
+    let name: String = words(name).map(capitalize).collect();
+    if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        name
+    } else {
+        format!("T{name}")
+    }
+}
+
+/// Cap'n Proto field names must be lowerCamelCase, without
+/// underscores.
+fn field_name(name: &str) -> String {
+    let name: String = words(name)
+        .enumerate()
+        .map(|(i, word)| {
+            if i == 0 {
+                word.to_string()
+            } else {
+                capitalize(word)
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        name
+    } else if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        let mut chars = name.chars();
+        chars.next().unwrap().to_ascii_lowercase().to_string() + chars.as_str()
+    } else {
+        format!("f{name}")
+    }
+}
+
+/// The Cap'n Proto type used to represent the member.  Pointers are
+/// stored as their address, and members without a primitive
+/// equivalent are stored as their raw bytes.
+fn capnp_type(member: &MemberLayout) -> &'static str {
+    if member.kind == TypeKind::Boolean {
+        return "Bool";
+    }
+    match Primitive::of(member) {
+        Some(Primitive::I8) => "Int8",
+        Some(Primitive::I16) => "Int16",
+        Some(Primitive::I32) => "Int32",
+        Some(Primitive::I64) => "Int64",
+        Some(Primitive::U8) => "UInt8",
+        Some(Primitive::U16) => "UInt16",
+        Some(Primitive::U32) => "UInt32",
+        Some(Primitive::U64 | Primitive::Pointer) => "UInt64",
+        Some(Primitive::F32) => "Float32",
+        Some(Primitive::F64) => "Float64",
+        None => "Data",
+    }
+}
+
+impl Printer for CapnpPrinter {
+    fn class(&mut self, _out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        self.hash = class
+            .name
+            .bytes()
+            .chain(std::iter::once(0))
+            .fold(self.hash, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+
+        let out = &mut self.body;
+        let name = type_name(&class.name);
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "# {}, {size_bytes} bytes, layout hash {}",
+            class.name,
+            layout_hash(class)
+        )?;
+        writeln!(out, "struct {name} {{")?;
+
+        let mut used_names = HashSet::new();
+        for (i, member) in class.members.iter().enumerate() {
+            let base = field_name(&member.name);
+            let name = (0..)
+                .map(|i| {
+                    if i == 0 {
+                        base.clone()
+                    } else {
+                        format!("{base}{i}")
+                    }
+                })
+                .find(|name| !used_names.contains(name))
+                .unwrap();
+            used_names.insert(name.clone());
+
+            let capnp_type = capnp_type(member);
+            let offset = member.offset;
+            let size_bytes = member.size_bytes;
+            writeln!(
+                out,
+                "  {name} @{i} :{capnp_type}; # {}, offset {offset}, {size_bytes} bytes",
+                member.type_name,
+            )?;
+        }
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        // File IDs must have the high bit set.
+        let file_id = self.hash | (1 << 63);
+        writeln!(out, "@{file_id:#018x};")?;
+        out.write_all(&self.body)?;
+        Ok(())
+    }
+}
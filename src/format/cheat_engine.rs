@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use super::{layout_hash, xml_escape, Printer};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+/// Prints a Cheat Engine structure-dissect table.  The output can be
+/// saved as a `.CT` file, or pasted into the `<Structures>` section
+/// of an existing table.
+pub struct CheatEnginePrinter;
+
+impl Printer for CheatEnginePrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(out, r#"<CheatTable>"#)?;
+        writeln!(out, r#"  <Structures StructVersion="2">"#)?;
+        Ok(())
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = xml_escape(&class.name);
+        let size_bytes = class.size_bytes;
+        writeln!(out, "    <!-- layout hash {} -->", layout_hash(class))?;
+        writeln!(
+            out,
+            r#"    <Structure Name="{name}" AutoFill="0" AutoCreate="0" DefaultHex="0" AutoDestroy="0" DoNotSaveLocal="0" RLECompression="1" AutoCreateStructsize="{size_bytes}">"#
+        )?;
+        writeln!(out, r#"      <Elements>"#)?;
+        for member in &class.members {
+            let offset = member.offset;
+            let vartype = vartype(member);
+            let bytesize = member.size_bytes;
+            let description = xml_escape(&format!("{} {}", member.type_name, member.name));
+            let display_method = display_method(member);
+            writeln!(
+                out,
+                r#"        <Element Offset="{offset}" Vartype="{vartype}" Bytesize="{bytesize}" OffsetHex="{offset:08X}" Description="{description}" DisplayMethod="{display_method}"/>"#
+            )?;
+        }
+        writeln!(out, r#"      </Elements>"#)?;
+        writeln!(out, r#"    </Structure>"#)?;
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, r#"  </Structures>"#)?;
+        writeln!(out, r#"</CheatTable>"#)?;
+        Ok(())
+    }
+}
+
+/// The Cheat Engine variable type used to display the member.
+/// Anything that doesn't map onto one of CE's primitive types is
+/// shown as a byte array.
+fn vartype(member: &MemberLayout) -> &'static str {
+    match (member.kind, member.size_bytes) {
+        (TypeKind::Pointer, _) => "Pointer",
+        (TypeKind::Float, 4) => "Float",
+        (TypeKind::Float, 8) => "Double",
+        (
+            TypeKind::Signed
+            | TypeKind::Unsigned
+            | TypeKind::Boolean
+            | TypeKind::Character
+            | TypeKind::Enumeration,
+            size,
+        ) => match size {
+            1 => "Byte",
+            2 => "2 Bytes",
+            4 => "4 Bytes",
+            8 => "8 Bytes",
+            _ => "Array of byte",
+        },
+        _ => "Array of byte",
+    }
+}
+
+fn display_method(member: &MemberLayout) -> &'static str {
+    match vartype(member) {
+        "Array of byte" | "Pointer" => "hexadecimal",
+        _ if member.kind == TypeKind::Signed => "signed integer",
+        _ => "unsigned integer",
+    }
+}
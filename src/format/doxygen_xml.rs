@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use super::{layout_hash, xml_escape, Printer};
+use crate::layout::ClassLayout;
+
+/// Prints one Doxygen `<compounddef>` per class, with a `<memberdef>`
+/// for each data member and a `<basecompoundref>` for each base
+/// class.  All compounds are written into a single `<doxygen>`
+/// element, as produced by Doxygen's `combine.xslt`.
+pub struct DoxygenXmlPrinter;
+
+/// Escape a name into the form Doxygen uses for ids, where each
+/// character that may not appear in a filename is replaced by an
+/// underscore-prefixed code.
+fn doxygen_id(name: &str) -> String {
+    name.chars()
+        .fold(String::with_capacity(name.len()), |mut id, c| {
+            match c {
+                '_' => id.push_str("__"),
+                ':' => id.push_str("_1"),
+                '<' => id.push_str("_3"),
+                '>' => id.push_str("_4"),
+                '*' => id.push_str("_5"),
+                '&' => id.push_str("_6"),
+                ',' => id.push_str("_00"),
+                ' ' => id.push_str("_01"),
+                c if c.is_ascii_alphanumeric() => id.push(c),
+                c => id.push_str(&format!("_{:02x}", c as u32)),
+            }
+            id
+        })
+}
+
+impl Printer for DoxygenXmlPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "<?xml version='1.0' encoding='UTF-8' standalone='no'?>"
+        )?;
+        writeln!(
+            out,
+            r#"<doxygen xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="compound.xsd" version="1.9.1" xml:lang="en-US">"#
+        )?;
+        Ok(())
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let compound_id = format!("struct{}", doxygen_id(&class.name));
+        let name = xml_escape(&class.name);
+        let size_bytes = class.size_bytes;
+
+        writeln!(
+            out,
+            r#"  <compounddef id="{compound_id}" kind="struct" language="C++" prot="public">"#
+        )?;
+        writeln!(out, "    <compoundname>{name}</compoundname>")?;
+        for base_class in class.members.iter().filter(|member| member.is_base_class) {
+            writeln!(
+                out,
+                r#"    <basecompoundref prot="public" virt="non-virtual">{}</basecompoundref>"#,
+                xml_escape(&base_class.type_name)
+            )?;
+        }
+
+        writeln!(out, r#"    <sectiondef kind="public-attrib">"#)?;
+        for member in class.members.iter().filter(|member| !member.is_base_class) {
+            let member_id = format!("{compound_id}_1{}", doxygen_id(&member.name));
+            let type_name = xml_escape(&member.type_name);
+            let member_name = xml_escape(&member.name);
+            let offset = member.offset;
+            let member_size = member.size_bytes;
+            writeln!(
+                out,
+                r#"      <memberdef kind="variable" id="{member_id}" prot="public" static="no" mutable="no">"#
+            )?;
+            writeln!(out, "        <type>{type_name}</type>")?;
+            writeln!(
+                out,
+                "        <definition>{type_name} {name}::{member_name}</definition>"
+            )?;
+            writeln!(out, "        <argsstring></argsstring>")?;
+            writeln!(out, "        <name>{member_name}</name>")?;
+            writeln!(out, "        <briefdescription></briefdescription>")?;
+            writeln!(
+                out,
+                "        <detaileddescription><para>Offset {offset}, {member_size} bytes.</para></detaileddescription>"
+            )?;
+            writeln!(out, "        <inbodydescription></inbodydescription>")?;
+            writeln!(out, r#"        <location file=""/>"#)?;
+            writeln!(out, "      </memberdef>")?;
+        }
+        writeln!(out, "    </sectiondef>")?;
+
+        writeln!(out, "    <briefdescription></briefdescription>")?;
+        writeln!(
+            out,
+            "    <detaileddescription><para>{size_bytes} bytes, layout hash {}.</para></detaileddescription>",
+            layout_hash(class)
+        )?;
+        writeln!(out, r#"    <location file=""/>"#)?;
+        writeln!(out, "  </compounddef>")?;
+
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, "</doxygen>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use super::{
+    identifier, layout_hash, padded_fields, shared_description, Field, Primitive, Printer,
+};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+/// Prints a FlatBuffers schema.  Each class is emitted as a
+/// fixed-layout `struct`, with explicit padding so that each member
+/// occurs at the same offset as in the binary.  Enumerations are
+/// translated into FlatBuffers enums, each emitted once before the
+/// first struct that uses it.
+#[derive(Default)]
+pub struct FlatbuffersPrinter {
+    emitted_enums: HashSet<String>,
+}
+
+/// The name of the FlatBuffers enum generated for an enumeration
+/// member.  Anonymous enumerations are named after the class and
+/// member that use them.
+fn enum_name(class: &ClassLayout, member: &MemberLayout) -> String {
+    if &*member.type_name == "unknown_class" {
+        identifier(&format!("{}_{}", class.name, member.name))
+    } else {
+        identifier(&member.type_name)
+    }
+}
+
+/// The underlying integer type of a FlatBuffers enum.
+fn enum_underlying_type(member: &MemberLayout) -> &'static str {
+    let is_signed = member
+        .enumerators
+        .iter()
+        .any(|enumerator| enumerator.value < 0);
+    match (member.size_bytes, is_signed) {
+        (1, true) => "byte",
+        (1, false) => "ubyte",
+        (2, true) => "short",
+        (2, false) => "ushort",
+        (8, true) => "long",
+        (8, false) => "ulong",
+        (_, true) => "int",
+        (_, false) => "uint",
+    }
+}
+
+fn is_enum(member: &MemberLayout) -> bool {
+    member.kind == TypeKind::Enumeration
+        && !member.enumerators.is_empty()
+        && matches!(member.size_bytes, 1 | 2 | 4 | 8)
+}
+
+/// The FlatBuffers type used to represent the member.
+fn flatbuffers_type(class: &ClassLayout, member: &MemberLayout) -> String {
+    if is_enum(member) {
+        return enum_name(class, member);
+    }
+    if member.kind == TypeKind::Boolean && member.size_bytes == 1 {
+        return "bool".into();
+    }
+    Primitive::of(member)
+        .map(|primitive| {
+            match primitive {
+                Primitive::I8 => "byte",
+                Primitive::I16 => "short",
+                Primitive::I32 => "int",
+                Primitive::I64 => "long",
+                Primitive::U8 => "ubyte",
+                Primitive::U16 => "ushort",
+                Primitive::U32 => "uint",
+                Primitive::U64 | Primitive::Pointer => "ulong",
+                Primitive::F32 => "float",
+                Primitive::F64 => "double",
+            }
+            .to_string()
+        })
+        .unwrap_or_else(|| format!("[ubyte:{}]", member.size_bytes))
+}
+
+impl FlatbuffersPrinter {
+    fn write_enum(
+        &mut self,
+        out: &mut dyn Write,
+        class: &ClassLayout,
+        member: &MemberLayout,
+    ) -> std::io::Result<()> {
+        let name = enum_name(class, member);
+        if !self.emitted_enums.insert(name.clone()) {
+            return Ok(());
+        }
+
+        // FlatBuffers requires enum values to be unique and in
+        // ascending order.
+        let mut enumerators: Vec<_> = member.enumerators.iter().collect();
+        enumerators.sort_by_key(|enumerator| enumerator.value);
+        enumerators.dedup_by_key(|enumerator| enumerator.value);
+
+        let underlying_type = enum_underlying_type(member);
+        writeln!(out)?;
+        if &*member.type_name == "unknown_class" {
+            writeln!(out, "// Anonymous enum of {}::{}", class.name, member.name)?;
+        } else {
+            writeln!(out, "// {}", member.type_name)?;
+        }
+        writeln!(out, "enum {name} : {underlying_type} {{")?;
+        for enumerator in enumerators {
+            let enumerator_name = identifier(&enumerator.name);
+            let value = enumerator.value;
+            writeln!(out, "  {enumerator_name} = {value},")?;
+        }
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+}
+
+impl Printer for FlatbuffersPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let fields = padded_fields(class);
+
+        for field in &fields {
+            if let Field::Member { member, .. } = field {
+                if is_enum(member) {
+                    self.write_enum(out, class, member)?;
+                }
+            }
+        }
+
+        let name = identifier(&class.name);
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "// {}, {size_bytes} bytes, layout hash {}",
+            class.name,
+            layout_hash(class)
+        )?;
+        writeln!(out, "struct {name} {{")?;
+        for field in fields {
+            match field {
+                Field::Member { member, name } => {
+                    let flatbuffers_type = flatbuffers_type(class, member);
+                    let offset = member.offset;
+                    writeln!(
+                        out,
+                        "  {name}:{flatbuffers_type}; // {}, offset {offset}",
+                        member.type_name,
+                    )?;
+                }
+                Field::Padding { index, size_bytes } => {
+                    writeln!(out, "  _padding{index}:[ubyte:{size_bytes}];")?;
+                }
+                Field::Overlapping { member } => {
+                    writeln!(
+                        out,
+                        "  // Overlaps previous member: {} {} at {}",
+                        member.type_name, member.name, member.offset
+                    )?;
+                }
+                Field::Shared {
+                    index,
+                    size_bytes,
+                    members,
+                } => {
+                    writeln!(
+                        out,
+                        "  _overlap{index}:[ubyte:{size_bytes}]; // Overlapping members: {}",
+                        shared_description(&members)
+                    )?;
+                }
+            }
+        }
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::Write;
+
+use super::{identifier, layout_hash, unique_names, Printer};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+/// Prints a Frida snippet, with one wrapper class per matched class.
+/// Each wrapper holds a `NativePointer`, exposes the offset of each
+/// member as a static constant, and reads each member through a
+/// typed getter.
+pub struct FridaPrinter;
+
+impl Printer for FridaPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let ident = identifier(&class.name);
+        let size_bytes = class.size_bytes;
+
+        writeln!(out, "// {}, {size_bytes} bytes", class.name)?;
+        writeln!(out, "class {ident} {{")?;
+        writeln!(out, "    static SIZE = {size_bytes:#x};")?;
+        // The hash is a string, since a 64-bit value can't be
+        // represented exactly as a JavaScript number.
+        writeln!(out, "    static LAYOUT_HASH = \"{}\";", layout_hash(class))?;
+        let member_idents =
+            unique_names(class.members.iter().map(|member| identifier(&member.name)));
+        for (member, member_ident) in class.members.iter().zip(&member_idents) {
+            let offset = member.offset;
+            writeln!(out, "    static OFFSET_{member_ident} = {offset:#x};")?;
+        }
+        writeln!(out)?;
+        writeln!(out, "    constructor(ptr) {{")?;
+        writeln!(out, "        this.ptr = ptr;")?;
+        writeln!(out, "    }}")?;
+        for (member, member_ident) in class.members.iter().zip(&member_idents) {
+            let offset = member.offset;
+            let accessor = accessor(member)
+                .map(|read| format!(".{read}()"))
+                .unwrap_or_default();
+            writeln!(out)?;
+            writeln!(out, "    // {} {}", member.type_name, member.name)?;
+            writeln!(out, "    get {member_ident}() {{")?;
+            writeln!(out, "        return this.ptr.add({offset:#x}){accessor};")?;
+            writeln!(out, "    }}")?;
+        }
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
+
+/// The `NativePointer` method used to read the member.  Returns
+/// `None` for members that don't map onto a primitive read, in which
+/// case the getter returns a pointer to the member.
+fn accessor(member: &MemberLayout) -> Option<&'static str> {
+    match (member.kind, member.size_bytes) {
+        (TypeKind::Pointer, _) => Some("readPointer"),
+        (TypeKind::Float, 4) => Some("readFloat"),
+        (TypeKind::Float, 8) => Some("readDouble"),
+        (TypeKind::Signed, 1) => Some("readS8"),
+        (TypeKind::Signed, 2) => Some("readS16"),
+        (TypeKind::Signed, 4) => Some("readS32"),
+        (TypeKind::Signed, 8) => Some("readS64"),
+        (
+            TypeKind::Unsigned | TypeKind::Boolean | TypeKind::Character | TypeKind::Enumeration,
+            size,
+        ) => match size {
+            1 => Some("readU8"),
+            2 => Some("readU16"),
+            4 => Some("readU32"),
+            8 => Some("readU64"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
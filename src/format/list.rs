@@ -0,0 +1,26 @@
+use std::io::Write;
+
+use super::Printer;
+use crate::layout::ClassLayout;
+
+/// Prints the qualified name of each class, one per line, for use in
+/// shell pipelines.  If `with_size` is set, the size in bytes follows
+/// each name, separated by a tab.  If the class records the binary it
+/// was found in, the binary precedes the name, separated by a tab.
+pub struct ListPrinter {
+    pub with_size: bool,
+}
+
+impl Printer for ListPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        if let Some(binary) = &class.binary {
+            write!(out, "{binary}\t")?;
+        }
+        let name = class.qualified_name();
+        if self.with_size {
+            writeln!(out, "{name}\t{}", class.size_bytes)
+        } else {
+            writeln!(out, "{name}")
+        }
+    }
+}
@@ -0,0 +1,541 @@
+use std::io::Write;
+
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+mod btf;
+mod c;
+mod capnp;
+mod cheat_engine;
+mod doxygen_xml;
+mod flatbuffers;
+mod frida;
+mod list;
+mod offsets;
+mod pahole;
+mod proto;
+mod rust;
+mod rust_tests;
+mod sizes;
+mod static_assert;
+mod text;
+mod zig;
+
+/// The output formats that can be selected on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// C-style class definitions, annotated with sizes and offsets.
+    Text,
+
+    /// A Cheat Engine structure-dissect table (.CT fragment).
+    CheatEngine,
+
+    /// A Frida snippet, with offset constants and typed accessors.
+    Frida,
+
+    /// A C++ header of `static_assert` checks on sizes and offsets.
+    StaticAssert,
+
+    /// A flat C header of `#define` constants for each offset.
+    Offsets,
+
+    /// C struct definitions, with explicit padding fields.
+    C,
+
+    /// `#[repr(C)]` Rust structs, with explicit padding fields.
+    Rust,
+
+    /// A Rust test module that checks the structs generated by the
+    /// `rust` format, using `size_of` and `offset_of!`.
+    RustTests,
+
+    /// A BPF Type Format (BTF) blob, written as raw binary.
+    Btf,
+
+    /// Zig `extern struct` definitions, with explicit padding fields.
+    Zig,
+
+    /// Protocol Buffers (proto3) message definitions.
+    Proto,
+
+    /// FlatBuffers schema, with one fixed-layout struct per class.
+    Flatbuffers,
+
+    /// Cap'n Proto struct schemas.
+    Capnp,
+
+    /// Output in the style of `pahole`, including holes and
+    /// cacheline boundaries.
+    Pahole,
+
+    /// Compound and member records following Doxygen's XML schema.
+    DoxygenXml,
+
+    /// The name of each class, one per line.
+    List,
+
+    /// The name, size, and padding of each class, separated by tabs.
+    Sizes,
+}
+
+/// Options that customize the output of individual formats.
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    /// A prefix added to the name of every macro defined by the
+    /// `offsets` format.
+    pub macro_prefix: String,
+
+    /// If true, formats intended to be read by a person annotate each
+    /// class with the compilation unit and declaration it came from.
+    pub show_cu: bool,
+
+    /// If true, the `text` format gives the file and line at which
+    /// each class was declared.
+    pub show_decl: bool,
+
+    /// If true, the `text` format also gives the line at which each
+    /// member was declared.
+    pub show_member_decl: bool,
+
+    /// If true, the `list` format prints the size of each class
+    /// after its name.
+    pub with_size: bool,
+
+    /// The lines that begin and end the formats that produce a C or
+    /// C++ header.
+    pub header: HeaderScaffold,
+}
+
+/// The lines that begin and end a generated C or C++ header, so that
+/// it can be added to a project without editing.
+#[derive(Clone, Debug, Default)]
+pub struct HeaderScaffold {
+    /// The macro that guards against the header being included more
+    /// than once.  If `None`, `#pragma once` is used instead.
+    pub include_guard: Option<String>,
+
+    /// Text written after the include guard, such as a license or
+    /// the project's own includes.
+    pub prefix: Option<String>,
+}
+
+impl HeaderScaffold {
+    /// Print the start of the header, up to and including the
+    /// `prefix`.
+    fn open(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match &self.include_guard {
+            Some(guard) => {
+                writeln!(out, "#ifndef {guard}")?;
+                writeln!(out, "#define {guard}")?;
+            }
+            None => writeln!(out, "#pragma once")?,
+        }
+        if let Some(prefix) = &self.prefix {
+            writeln!(out)?;
+            write!(out, "{prefix}")?;
+            if !prefix.ends_with('\n') {
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the end of the header, closing the include guard.
+    fn close(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if let Some(guard) = &self.include_guard {
+            writeln!(out, "#endif // {guard}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A backend that prints the classes that matched the search
+/// filter.  The `header` is printed before the first class, and the
+/// `footer` after the last class, even if no classes matched.
+pub trait Printer {
+    fn header(&mut self, _out: &mut dyn Write) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()>;
+
+    /// Print the other instantiations of the template that `class`
+    /// instantiates, after `class` has been printed to represent them
+    /// all.  By default, each is printed in full.
+    fn instantiations(
+        &mut self,
+        out: &mut dyn Write,
+        _class: &ClassLayout,
+        others: &[&ClassLayout],
+    ) -> std::io::Result<()> {
+        others.iter().try_for_each(|other| self.class(out, other))
+    }
+
+    fn footer(&mut self, _out: &mut dyn Write) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputFormat {
+    /// Returns true for the formats that generate type definitions to
+    /// be compiled, in which a class must be defined before it is
+    /// embedded in another.
+    pub fn generates_code(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::C
+                | OutputFormat::Rust
+                | OutputFormat::Zig
+                | OutputFormat::Proto
+                | OutputFormat::Flatbuffers
+                | OutputFormat::Capnp
+        )
+    }
+
+    /// Returns true for the formats that define a name for each class,
+    /// such as a type, a macro, or a test, which can only be defined
+    /// once in the output.
+    pub fn defines_names(self) -> bool {
+        self.generates_code()
+            || matches!(
+                self,
+                OutputFormat::StaticAssert
+                    | OutputFormat::Offsets
+                    | OutputFormat::RustTests
+                    | OutputFormat::Frida
+            )
+    }
+
+    /// Returns true for the formats that note when a member's type is
+    /// only declared.  A type declared in one compilation unit may be
+    /// defined in another, so every class must be read and indexed
+    /// before any is printed.
+    pub fn reports_incomplete_types(self) -> bool {
+        self == OutputFormat::C
+    }
+
+    /// Construct the printer for this output format.
+    pub fn printer(self, options: &FormatOptions) -> Box<dyn Printer> {
+        match self {
+            OutputFormat::Text => Box::new(text::TextPrinter::new(options)),
+            OutputFormat::CheatEngine => Box::new(cheat_engine::CheatEnginePrinter),
+            OutputFormat::Frida => Box::new(frida::FridaPrinter),
+            OutputFormat::StaticAssert => Box::new(static_assert::StaticAssertPrinter {
+                header: options.header.clone(),
+            }),
+            OutputFormat::Offsets => Box::new(offsets::OffsetsPrinter {
+                prefix: options.macro_prefix.clone(),
+                header: options.header.clone(),
+            }),
+            OutputFormat::C => Box::new(c::CPrinter::new(options.header.clone())),
+            OutputFormat::Rust => Box::new(rust::RustPrinter),
+            OutputFormat::RustTests => Box::new(rust_tests::RustTestsPrinter),
+            OutputFormat::Btf => Box::new(btf::BtfPrinter::new()),
+            OutputFormat::Zig => Box::new(zig::ZigPrinter),
+            OutputFormat::Proto => Box::new(proto::ProtoPrinter),
+            OutputFormat::Flatbuffers => Box::new(flatbuffers::FlatbuffersPrinter::default()),
+            OutputFormat::Capnp => Box::new(capnp::CapnpPrinter::new()),
+            OutputFormat::Pahole => Box::new(pahole::PaholePrinter::new(options.show_cu)),
+            OutputFormat::DoxygenXml => Box::new(doxygen_xml::DoxygenXmlPrinter),
+            OutputFormat::List => Box::new(list::ListPrinter {
+                with_size: options.with_size,
+            }),
+            OutputFormat::Sizes => Box::new(sizes::SizesPrinter),
+        }
+    }
+}
+
+/// The layout hash of the class, as printed by every format, so that
+/// tooling can tell whether a class changed between builds without
+/// comparing the layouts member by member.
+fn layout_hash(class: &ClassLayout) -> String {
+    format!("{:016x}", class.layout_hash())
+}
+
+/// Escape a string for use within an XML attribute or text node.
+/// C++ type names frequently contain `<` and `>`, which would
+/// otherwise produce malformed XML.
+fn xml_escape(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+/// Convert a C++ name into a valid identifier for languages that
+/// don't allow `::`, `<`, `>`, or other punctuation in names.
+fn identifier(s: &str) -> String {
+    let ident: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{ident}")
+    } else {
+        ident
+    }
+}
+
+/// Returns `base`, or `base` followed by the first of the suffixes
+/// `_1`, `_2`, and so on that makes it distinct from the `used_names`,
+/// and adds the result to them.
+fn unique_name(base: String, used_names: &mut std::collections::HashSet<String>) -> String {
+    let name = (0..)
+        .map(|i| {
+            if i == 0 {
+                base.clone()
+            } else {
+                format!("{base}_{i}")
+            }
+        })
+        .find(|name| !used_names.contains(name))
+        .unwrap();
+    used_names.insert(name.clone());
+    name
+}
+
+/// Make each of the `names` unique, as for `unique_name`, since
+/// multiple base classes would otherwise all be named `_base_class`,
+/// and unnamed members all `unknown_name`.
+fn unique_names(names: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut used_names = std::collections::HashSet::new();
+    names
+        .into_iter()
+        .map(|name| unique_name(name, &mut used_names))
+        .collect()
+}
+
+/// A fixed-size primitive type, used by code-generation formats to
+/// choose a native type for each member.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Primitive {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Pointer,
+}
+
+impl Primitive {
+    /// The primitive type that represents the member, if any.
+    /// Members without a primitive representation, such as
+    /// aggregates and arrays, should be represented as byte arrays.
+    fn of(member: &MemberLayout) -> Option<Self> {
+        match (member.kind, member.size_bytes) {
+            (TypeKind::Pointer, 8) => Some(Primitive::Pointer),
+            (TypeKind::Float, 4) => Some(Primitive::F32),
+            (TypeKind::Float, 8) => Some(Primitive::F64),
+            (TypeKind::Signed, 1) => Some(Primitive::I8),
+            (TypeKind::Signed, 2) => Some(Primitive::I16),
+            (TypeKind::Signed, 4) => Some(Primitive::I32),
+            (TypeKind::Signed, 8) => Some(Primitive::I64),
+            (
+                TypeKind::Unsigned
+                | TypeKind::Boolean
+                | TypeKind::Character
+                | TypeKind::Enumeration,
+                size,
+            ) => match size {
+                1 => Some(Primitive::U8),
+                2 => Some(Primitive::U16),
+                4 => Some(Primitive::U32),
+                8 => Some(Primitive::U64),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The members of a class that are generated as a primitive type,
+/// which are the only aligned fields of the generated struct, since
+/// every other member is generated as bytes.
+fn primitive_members(class: &ClassLayout) -> impl Iterator<Item = &MemberLayout> {
+    let variant_members = class
+        .variant_part
+        .iter()
+        .flat_map(|variant_part| &variant_part.variants)
+        .flat_map(|variant| &variant.members);
+    class
+        .members
+        .iter()
+        .chain(variant_members)
+        .filter(|member| Primitive::of(member).is_some())
+}
+
+/// The alignment of the struct generated for a class, from that of
+/// its primitive fields, each of which is aligned to its size.
+fn field_alignment(class: &ClassLayout) -> usize {
+    primitive_members(class)
+        .map(|member| member.size_bytes)
+        .max()
+        .unwrap_or(1)
+}
+
+/// The alignment that the struct generated for a class must be given
+/// explicitly, if the class's alignment is greater than that of the
+/// struct's fields.
+fn explicit_alignment(class: &ClassLayout) -> Option<usize> {
+    let alignment = class.alignment?;
+    (alignment > field_alignment(class)).then_some(alignment)
+}
+
+/// Returns true if the struct generated for a class must be packed to
+/// keep its layout, because a primitive field is placed at an offset
+/// that isn't a multiple of its alignment, as in a class declared
+/// with `__attribute__((packed))`, or because the class's size isn't
+/// a multiple of the alignment of its fields.
+fn needs_packing(class: &ClassLayout) -> bool {
+    let is_misaligned =
+        primitive_members(class).any(|member| !member.offset.is_multiple_of(member.size_bytes));
+    is_misaligned || !class.size_bytes.is_multiple_of(field_alignment(class))
+}
+
+/// A single field of a struct generated by a code-generation format.
+enum Field<'a> {
+    /// A member of the class.
+    Member {
+        member: &'a MemberLayout,
+
+        /// A sanitized name for the member, unique within the
+        /// generated struct.
+        name: String,
+    },
+
+    /// Padding bytes inserted so that the next member occurs at the
+    /// same offset as in the binary.
+    Padding { index: usize, size_bytes: usize },
+
+    /// A member that lies within the storage of another member, such
+    /// as a bitfield sharing its storage unit with an earlier
+    /// bitfield, and so cannot be represented as a field of the
+    /// generated struct.
+    Overlapping { member: &'a MemberLayout },
+
+    /// Members that partially overlap each other, with no one member
+    /// covering all of them, as may happen with
+    /// `[[no_unique_address]]`.  They are represented together as a
+    /// single array of bytes.
+    Shared {
+        index: usize,
+        size_bytes: usize,
+        members: Vec<&'a MemberLayout>,
+    },
+}
+
+/// Describe the members represented by a `Field::Shared`, as a list
+/// of their types, names, and offsets.
+fn shared_description(members: &[&MemberLayout]) -> String {
+    members
+        .iter()
+        .map(|member| format!("{} {} at {}", member.type_name, member.name, member.offset))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The fields of a struct that reproduces the layout of the class,
+/// with explicit padding between members and at the end of the
+/// struct.  Member names are sanitized with `identifier`, and made
+/// unique by appending a suffix, since multiple base classes would
+/// otherwise all be named `_base_class`.
+fn padded_fields(class: &ClassLayout) -> Vec<Field<'_>> {
+    padded_members(&class.members, class.size_bytes, identifier)
+}
+
+/// The fields of a struct of `size_bytes` that places each of the
+/// `members` at its offset, as for `padded_fields`, with member names
+/// sanitized by `field_name` before they are made unique.  Members whose
+/// storage overlaps are grouped together.  If one member of a group
+/// covers the storage of all the others, it becomes the field, and
+/// the others are `Field::Overlapping`.  Otherwise, the group becomes
+/// a `Field::Shared` array of bytes, so that the generated struct
+/// keeps the size and offsets of the class.
+fn padded_members(
+    members: &[MemberLayout],
+    size_bytes: usize,
+    field_name: impl Fn(&str) -> String,
+) -> Vec<Field<'_>> {
+    let mut fields = Vec::new();
+    let mut used_names = std::collections::HashSet::new();
+    let mut num_padding = 0;
+    let mut num_shared = 0;
+    let mut position = 0;
+
+    let mut push_padding = |fields: &mut Vec<Field>, from: usize, to: usize| {
+        if to > from {
+            fields.push(Field::Padding {
+                index: num_padding,
+                size_bytes: to - from,
+            });
+            num_padding += 1;
+        }
+    };
+
+    // Members are not always listed in order of their offsets, as
+    // for Rust, whose compiler reorders the fields of a struct.
+    let mut sorted: Vec<&MemberLayout> = members.iter().collect();
+    sorted.sort_by_key(|member| member.offset);
+
+    let mut groups: Vec<Vec<&MemberLayout>> = Vec::new();
+    let mut group_end = 0;
+    for member in sorted {
+        match groups.last_mut() {
+            Some(group) if member.offset < group_end => {
+                group.push(member);
+                group_end = group_end.max(member.end());
+            }
+            _ => {
+                groups.push(vec![member]);
+                group_end = member.end();
+            }
+        }
+    }
+
+    for group in groups {
+        let start = group[0].offset;
+        let end = group.iter().map(|member| member.end()).max().unwrap();
+        push_padding(&mut fields, position, start);
+        position = end;
+
+        let Some(covering) = group
+            .iter()
+            .position(|member| member.offset == start && member.end() == end)
+        else {
+            fields.push(Field::Shared {
+                index: num_shared,
+                size_bytes: end - start,
+                members: group,
+            });
+            num_shared += 1;
+            continue;
+        };
+
+        let member = group[covering];
+        let name = unique_name(field_name(&member.name), &mut used_names);
+
+        fields.push(Field::Member { member, name });
+        fields.extend(
+            group
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != covering)
+                .map(|(_, member)| Field::Overlapping { member }),
+        );
+    }
+    push_padding(&mut fields, position, size_bytes);
+
+    fields
+}
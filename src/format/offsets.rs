@@ -0,0 +1,59 @@
+use std::io::Write;
+
+use super::{identifier, layout_hash, unique_names, HeaderScaffold, Printer};
+use crate::layout::ClassLayout;
+
+/// Prints a flat C header of `#define` constants, one for the size of
+/// each class and one for the offset of each member.
+pub struct OffsetsPrinter {
+    /// Prefix added to the name of each macro.
+    pub prefix: String,
+
+    pub header: HeaderScaffold,
+}
+
+impl Printer for OffsetsPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.header.open(out)
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.header.include_guard.is_some() {
+            writeln!(out)?;
+        }
+        self.header.close(out)
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let prefix = &self.prefix;
+        let class_ident = identifier(&class.name).to_uppercase();
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(out, "// {}", class.name)?;
+        writeln!(out, "#define {prefix}{class_ident}_SIZE {size_bytes:#x}")?;
+        writeln!(
+            out,
+            "#define {prefix}{class_ident}_LAYOUT_HASH 0x{}ULL",
+            layout_hash(class)
+        )?;
+        // Names are made unique after converting to uppercase, since
+        // members whose names differ only in case would otherwise
+        // share a macro.
+        let member_idents = unique_names(
+            class
+                .members
+                .iter()
+                .map(|member| identifier(&member.name).to_uppercase()),
+        );
+        for (member, member_ident) in class.members.iter().zip(member_idents) {
+            let offset = member.offset;
+            writeln!(
+                out,
+                "#define {prefix}{class_ident}_{member_ident}_OFFSET {offset:#x}"
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use super::{layout_hash, Printer};
+use crate::layout::{ClassKind, ClassLayout};
+
+const CACHELINE_BYTES: usize = 64;
+
+/// Prints classes using the textual conventions of `pahole`: an
+/// `/* offset size */` column for each member, markers for holes and
+/// cacheline boundaries, and summary lines at the end of each class.
+pub struct PaholePrinter {
+    num_printed: usize,
+
+    /// If true, each class is preceded by a comment stating where it
+    /// was found.
+    show_cu: bool,
+}
+
+impl PaholePrinter {
+    pub fn new(show_cu: bool) -> Self {
+        Self {
+            num_printed: 0,
+            show_cu,
+        }
+    }
+}
+
+impl Printer for PaholePrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        if self.num_printed > 0 {
+            writeln!(out)?;
+        }
+        self.num_printed += 1;
+
+        if self.show_cu {
+            writeln!(out, "/* from {} */", class.origin())?;
+        } else if let Some(binary) = &class.binary {
+            writeln!(out, "/* in {binary} */")?;
+        }
+
+        let base_classes: Vec<_> = class
+            .members
+            .iter()
+            .filter(|member| member.is_base_class)
+            .map(|member| format!("public {}", member.type_name))
+            .collect();
+        let keyword = if class.kind == ClassKind::Union {
+            "union"
+        } else {
+            "struct"
+        };
+        if base_classes.is_empty() {
+            writeln!(out, "{keyword} {} {{", class.name)?;
+        } else {
+            writeln!(
+                out,
+                "{keyword} {} : {} {{",
+                class.name,
+                base_classes.join(", ")
+            )?;
+        }
+
+        let holes = class.holes();
+        let mut holes_iter = holes.iter().peekable();
+        let mut cacheline = 0;
+
+        for member in &class.members {
+            if let Some(hole) =
+                holes_iter.next_if(|hole| hole.offset + hole.size_bytes == member.offset)
+            {
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "\t/* XXX {} bytes hole, try to pack */",
+                    hole.size_bytes
+                )?;
+                writeln!(out)?;
+            }
+
+            let member_cacheline = member.offset / CACHELINE_BYTES;
+            if member_cacheline > cacheline {
+                cacheline = member_cacheline;
+                let boundary = cacheline * CACHELINE_BYTES;
+                if member.offset == boundary {
+                    writeln!(
+                        out,
+                        "\t/* --- cacheline {cacheline} boundary ({boundary} bytes) --- */"
+                    )?;
+                } else {
+                    let ago = member.offset - boundary;
+                    writeln!(
+                        out,
+                        "\t/* --- cacheline {cacheline} boundary ({boundary} bytes) \
+                         was {ago} bytes ago --- */"
+                    )?;
+                }
+            }
+
+            let offset = member.offset;
+            let size_bytes = member.size_bytes;
+            if member.is_base_class {
+                let declaration = format!("/* {:<23} <ancestor>; */", member.type_name);
+                writeln!(out, "\t{declaration:<48} /* {offset:5} {size_bytes:5} */")?;
+            } else {
+                let name = format!("{}{};", member.name, aligned_attribute(member.alignment));
+                writeln!(
+                    out,
+                    "\t{:<26} {name:<21} /* {offset:5} {size_bytes:5} */",
+                    member.type_name
+                )?;
+            }
+        }
+
+        let size_bytes = class.size_bytes;
+        let num_cachelines = size_bytes.div_ceil(CACHELINE_BYTES);
+        let num_members = class.members.len();
+        let sum_members: usize = class.members.iter().map(|member| member.size_bytes).sum();
+        let sum_holes: usize = holes.iter().map(|hole| hole.size_bytes).sum();
+        let padding = class.tail_padding();
+        let last_cacheline = size_bytes % CACHELINE_BYTES;
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "\t/* size: {size_bytes}, cachelines: {num_cachelines}, members: {num_members} */"
+        )?;
+        if !holes.is_empty() {
+            writeln!(
+                out,
+                "\t/* sum members: {sum_members}, holes: {}, sum holes: {sum_holes} */",
+                holes.len()
+            )?;
+        }
+        if padding > 0 {
+            writeln!(out, "\t/* padding: {padding} */")?;
+        }
+        if last_cacheline > 0 {
+            writeln!(out, "\t/* last cacheline: {last_cacheline} bytes */")?;
+        }
+        writeln!(out, "\t/* layout hash: {} */", layout_hash(class))?;
+        writeln!(out, "}}{};", aligned_attribute(class.alignment))?;
+
+        Ok(())
+    }
+}
+
+/// Returns the attribute that gives an explicit alignment, preceded
+/// by a space, or an empty string if there is none.
+fn aligned_attribute(alignment: Option<usize>) -> String {
+    alignment
+        .map(|alignment| format!(" __attribute__((__aligned__({alignment})))"))
+        .unwrap_or_default()
+}
@@ -0,0 +1,89 @@
+use std::io::Write;
+
+use super::{
+    identifier, layout_hash, padded_fields, shared_description, Field, Primitive, Printer,
+};
+use crate::layout::{ClassLayout, MemberLayout, TypeKind};
+
+/// Prints Protocol Buffers (proto3) message definitions, with one
+/// message per class.  Since protobuf has no notion of memory
+/// layout, the original offset and size of each member are recorded
+/// in a comment.
+pub struct ProtoPrinter;
+
+/// The protobuf scalar type used to represent the member.  Pointers
+/// are stored as their address, and members without a scalar
+/// equivalent are stored as their raw bytes.
+fn proto_type(member: &MemberLayout) -> &'static str {
+    if member.kind == TypeKind::Boolean {
+        return "bool";
+    }
+    match Primitive::of(member) {
+        Some(Primitive::I8 | Primitive::I16 | Primitive::I32) => "int32",
+        Some(Primitive::I64) => "int64",
+        Some(Primitive::U8 | Primitive::U16 | Primitive::U32) => "uint32",
+        Some(Primitive::U64 | Primitive::Pointer) => "uint64",
+        Some(Primitive::F32) => "float",
+        Some(Primitive::F64) => "double",
+        None => "bytes",
+    }
+}
+
+impl Printer for ProtoPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, "syntax = \"proto3\";")?;
+        Ok(())
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = identifier(&class.name);
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "// {}, {size_bytes} bytes, layout hash {}",
+            class.name,
+            layout_hash(class)
+        )?;
+        writeln!(out, "message {name} {{")?;
+
+        let mut field_number = 0;
+        for field in padded_fields(class) {
+            match field {
+                Field::Member { member, name } => {
+                    field_number += 1;
+                    let proto_type = proto_type(member);
+                    let offset = member.offset;
+                    let size_bytes = member.size_bytes;
+                    writeln!(
+                        out,
+                        "  {proto_type} {name} = {field_number}; \
+                         // {}, offset {offset}, {size_bytes} bytes",
+                        member.type_name,
+                    )?;
+                }
+                Field::Padding { .. } => {}
+                Field::Overlapping { member } => {
+                    writeln!(
+                        out,
+                        "  // Overlaps previous member: {} {} at {}",
+                        member.type_name, member.name, member.offset
+                    )?;
+                }
+                Field::Shared { index, members, .. } => {
+                    field_number += 1;
+                    writeln!(
+                        out,
+                        "  bytes _overlap{index} = {field_number}; // Overlapping members: {}",
+                        shared_description(&members)
+                    )?;
+                }
+            }
+        }
+
+        writeln!(out, "}}")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::io::Write;
+
+use super::{
+    explicit_alignment, identifier, layout_hash, needs_packing, padded_members, shared_description,
+    unique_names, Field, Primitive, Printer,
+};
+use crate::layout::{ClassKind, ClassLayout, MemberLayout};
+
+/// Prints `#[repr(C)]` Rust structs.  Members are laid out at the
+/// same offsets as in the binary by inserting explicit padding
+/// fields, and any member without a primitive Rust equivalent is
+/// represented as a byte array of the same size.  A class aligned
+/// more strictly than its fields is given `#[repr(align(N))]`, and one
+/// whose fields are misaligned is given `#[repr(packed)]`.  Rust
+/// doesn't allow both, so a class that needs both is only packed, and
+/// a warning is printed.  A union is printed as a `#[repr(C)]` union
+/// with a field for each of its members.
+pub struct RustPrinter;
+
+/// Identifiers that cannot be used as a field name without the `r#`
+/// prefix.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+/// Identifiers that cannot be used as a field name, even as a raw
+/// identifier.
+const RUST_RESERVED: &[&str] = &["_", "crate", "self", "Self", "super"];
+
+/// The name of the Rust struct generated for a class.
+pub(super) fn struct_name(class: &ClassLayout) -> String {
+    identifier(&class.name)
+}
+
+/// Convert a member name into a valid Rust field name.
+fn field_name(name: &str) -> String {
+    let name = identifier(name);
+    if RUST_RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
+
+/// The fields of the Rust struct generated for a class, as for
+/// `padded_fields`, with names that are valid Rust field names.
+pub(super) fn struct_fields(class: &ClassLayout) -> Vec<Field<'_>> {
+    padded_members(&class.members, class.size_bytes, field_name)
+}
+
+/// The fields of the Rust union generated for a union, each paired
+/// with the member it represents.
+pub(super) fn union_fields(class: &ClassLayout) -> Vec<(&MemberLayout, String)> {
+    let names = unique_names(class.members.iter().map(|member| field_name(&member.name)));
+    class.members.iter().zip(names).collect()
+}
+
+/// The Rust type used to represent the member.
+fn rust_type(member: &MemberLayout) -> String {
+    Primitive::of(member)
+        .map(|primitive| {
+            match primitive {
+                Primitive::I8 => "i8",
+                Primitive::I16 => "i16",
+                Primitive::I32 => "i32",
+                Primitive::I64 => "i64",
+                Primitive::U8 => "u8",
+                Primitive::U16 => "u16",
+                Primitive::U32 => "u32",
+                Primitive::U64 => "u64",
+                Primitive::F32 => "f32",
+                Primitive::F64 => "f64",
+                Primitive::Pointer => "*mut core::ffi::c_void",
+            }
+            .to_string()
+        })
+        .unwrap_or_else(|| format!("[u8; {}]", member.size_bytes))
+}
+
+impl Printer for RustPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = struct_name(class);
+        let size_bytes = class.size_bytes;
+
+        writeln!(out, "/// `{}`, {size_bytes} bytes", class.name)?;
+        // Rust doesn't allow a struct to be both packed and aligned,
+        // so a packed struct has an alignment of 1, and loses any
+        // alignment the class was declared with.
+        if needs_packing(class) {
+            if let Some(alignment) = explicit_alignment(class) {
+                eprintln!(
+                    "warning: {} is packed, so its alignment of {alignment} is not kept",
+                    class.name
+                );
+            }
+            writeln!(out, "#[repr(C, packed)]")?;
+        } else if let Some(alignment) = explicit_alignment(class) {
+            writeln!(out, "#[repr(C, align({alignment}))]")?;
+        } else {
+            writeln!(out, "#[repr(C)]")?;
+        }
+        if class.kind == ClassKind::Union {
+            write_union(out, class)?;
+        } else {
+            write_struct(out, class)?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "impl {name} {{")?;
+        writeln!(
+            out,
+            "    pub const LAYOUT_HASH: u64 = 0x{};",
+            layout_hash(class)
+        )?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
+
+/// Print a union with a field for each member.  A union may be larger
+/// than its largest member, as when it is given a greater alignment,
+/// so a byte array of the union's full size is added where needed to
+/// keep its size.
+fn write_union(out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+    let size_bytes = class.size_bytes;
+    writeln!(out, "pub union {} {{", struct_name(class))?;
+    for (member, name) in union_fields(class) {
+        writeln!(out, "    /// `{}`", member.type_name)?;
+        writeln!(out, "    pub {name}: {},", rust_type(member))?;
+    }
+    let largest = class.members.iter().map(|member| member.end()).max();
+    if largest.unwrap_or(0) < size_bytes {
+        writeln!(out, "    _size: [u8; {size_bytes}],")?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Print a struct that places each member at its offset.
+fn write_struct(out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+    writeln!(out, "pub struct {} {{", struct_name(class))?;
+    for field in struct_fields(class) {
+        match field {
+            Field::Member { member, name } => {
+                let rust_type = rust_type(member);
+                writeln!(out, "    /// `{}`", member.type_name)?;
+                writeln!(out, "    pub {name}: {rust_type},")?;
+            }
+            Field::Padding { index, size_bytes } => {
+                writeln!(out, "    _padding{index}: [u8; {size_bytes}],")?;
+            }
+            Field::Overlapping { member } => {
+                writeln!(
+                    out,
+                    "    // Overlaps previous member: {} {} at {}",
+                    member.type_name, member.name, member.offset
+                )?;
+            }
+            Field::Shared {
+                index,
+                size_bytes,
+                members,
+            } => {
+                writeln!(
+                    out,
+                    "    /// Overlapping members: {}",
+                    shared_description(&members)
+                )?;
+                writeln!(out, "    pub _overlap{index}: [u8; {size_bytes}],")?;
+            }
+        }
+    }
+
+    writeln!(out, "}}")
+}
@@ -0,0 +1,66 @@
+use std::io::Write;
+
+use super::rust::{struct_fields, struct_name, union_fields};
+use super::{layout_hash, Field, Printer};
+use crate::layout::{ClassKind, ClassLayout};
+
+/// Prints a Rust test module that checks the size of each struct
+/// generated by the `rust` format, and the offset of each of its
+/// fields.  Intended to be placed alongside the generated structs,
+/// so that regenerating the tests against an updated binary catches
+/// any drift in the layouts.
+pub struct RustTestsPrinter;
+
+impl Printer for RustTestsPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, "#[cfg(test)]")?;
+        writeln!(out, "mod layout_tests {{")?;
+        writeln!(out, "    use super::*;")?;
+        Ok(())
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = struct_name(class);
+        let test_name = name.to_lowercase();
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(out, "    // layout hash {}", layout_hash(class))?;
+        writeln!(out, "    #[test]")?;
+        writeln!(out, "    fn {test_name}_layout() {{")?;
+        writeln!(
+            out,
+            "        assert_eq!(core::mem::size_of::<{name}>(), {size_bytes});"
+        )?;
+        // Padding isn't checked, and overlapping members are omitted
+        // from the generated struct.
+        let fields: Vec<_> = if class.kind == ClassKind::Union {
+            union_fields(class)
+                .into_iter()
+                .map(|(member, field)| (field, member.offset))
+                .collect()
+        } else {
+            struct_fields(class)
+                .into_iter()
+                .filter_map(|field| match field {
+                    Field::Member { member, name } => Some((name, member.offset)),
+                    _ => None,
+                })
+                .collect()
+        };
+        for (field, offset) in fields {
+            writeln!(
+                out,
+                "        assert_eq!(core::mem::offset_of!({name}, {field}), {offset});"
+            )?;
+        }
+        writeln!(out, "    }}")?;
+
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use std::io::Write;
+
+use super::Printer;
+use crate::layout::ClassLayout;
+
+/// Prints one tab-separated line per class, holding the qualified
+/// name, the size in bytes, and the number of padding bytes, for use
+/// with `sort`, `awk`, and similar tools.  If the class records the
+/// binary it was found in, the binary is the first column.
+pub struct SizesPrinter;
+
+impl Printer for SizesPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        if let Some(binary) = &class.binary {
+            write!(out, "{binary}\t")?;
+        }
+        writeln!(
+            out,
+            "{}\t{}\t{}",
+            class.qualified_name(),
+            class.size_bytes,
+            class.padding_bytes()
+        )
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::Write;
+
+use super::{layout_hash, HeaderScaffold, Printer};
+use crate::layout::ClassLayout;
+
+/// Prints a C++ header of `static_assert` checks, verifying the size
+/// of each class and the offset of each data member.  Including the
+/// header after a hand-written definition of the same classes checks
+/// that definition against the binary at compile time.
+pub struct StaticAssertPrinter {
+    pub header: HeaderScaffold,
+}
+
+impl Printer for StaticAssertPrinter {
+    fn header(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.header.open(out)?;
+        writeln!(out)?;
+        writeln!(out, "#include <cstddef>")?;
+        Ok(())
+    }
+
+    fn footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.header.include_guard.is_some() {
+            writeln!(out)?;
+        }
+        self.header.close(out)
+    }
+
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = &class.name;
+        let size_bytes = class.size_bytes;
+
+        writeln!(out)?;
+        writeln!(out, "// {name}, layout hash {}", layout_hash(class))?;
+        writeln!(
+            out,
+            "static_assert(sizeof({name}) == {size_bytes}, \"sizeof({name})\");"
+        )?;
+
+        // `offsetof` cannot refer to base classes, so only the data
+        // members are checked.
+        class
+            .members
+            .iter()
+            .filter(|member| !member.is_base_class)
+            .try_for_each(|member| {
+                let member_name = &member.name;
+                let offset = member.offset;
+                writeln!(
+                    out,
+                    "static_assert(offsetof({name}, {member_name}) == {offset}, \
+                     \"offsetof({name}, {member_name})\");"
+                )
+            })
+    }
+}
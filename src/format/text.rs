@@ -0,0 +1,361 @@
+use std::io::Write;
+
+use super::{layout_hash, FormatOptions, Printer};
+use crate::layout::{ClassKind, ClassLayout, Language, MemberLayout, SizeKind};
+use crate::stdlib;
+
+/// Prints class definitions in the syntax of their source language,
+/// with the size and location of each member in a trailing comment.
+pub struct TextPrinter {
+    num_printed: usize,
+
+    /// If true, each class is preceded by a comment stating where it
+    /// was found.
+    show_cu: bool,
+
+    /// If true, each class is preceded by a comment giving the file
+    /// and line at which it was declared.
+    show_decl: bool,
+
+    /// If true, the comment after each member also gives the line at
+    /// which it was declared.
+    show_member_decl: bool,
+}
+
+impl TextPrinter {
+    pub fn new(options: &FormatOptions) -> Self {
+        Self {
+            num_printed: 0,
+            show_cu: options.show_cu,
+            show_decl: options.show_decl,
+            show_member_decl: options.show_member_decl,
+        }
+    }
+}
+
+impl Printer for TextPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        if self.num_printed > 0 {
+            writeln!(out)?;
+        }
+        self.num_printed += 1;
+
+        let name = class.qualified_name();
+        let size_bytes = class.size_bytes;
+
+        // TODO: Align the comments for readability.
+
+        if self.show_cu {
+            writeln!(out, "// from {}", class.origin())?;
+        } else if let Some(binary) = &class.binary {
+            writeln!(out, "// in {binary}")?;
+        }
+        let syntax = Syntax::new(class);
+        let comment = syntax.comment;
+        if let Some(declaration) = class.declaration().filter(|_| self.show_decl) {
+            writeln!(out, "{comment} declared at {declaration}")?;
+        }
+        for alias in class
+            .aliases
+            .iter()
+            .filter(|alias| **alias != class.name && **alias != name)
+        {
+            writeln!(out, "{comment} {}", syntax.alias(&name, alias))?;
+        }
+        let hash = layout_hash(class);
+        let size = match class.size_kind {
+            SizeKind::Exact => format!("{size_bytes} bytes"),
+            SizeKind::Dynamic => format!("size: dynamic, at least {size_bytes} bytes"),
+            SizeKind::Inferred => format!("at least {size_bytes} bytes, inferred from members"),
+        };
+        let size_note = match class.members.iter().find(|member| member.is_flexible_array) {
+            Some(member) => format!(
+                ", excluding the elements of {} beyond its declared length",
+                member.name
+            ),
+            None => String::new(),
+        };
+        writeln!(
+            out,
+            "{} {comment} {size}{size_note}, layout hash {hash}",
+            syntax.opening(&name)
+        )?;
+
+        // TODO: Highlight the part of the structure that matched
+        // the SearchFilter.
+        let siblings: &[MemberLayout] = if syntax.is_union { &[] } else { &class.members };
+        let class_file = self
+            .show_member_decl
+            .then(|| class.decl_file.as_deref().unwrap_or_default());
+        for member in &class.members {
+            write_member(out, &syntax, "    ", member, siblings, class_file)?;
+        }
+
+        // The variants of a Rust enum or Ada record overlap, and so
+        // are listed after the discriminant, each with its own
+        // members.  The discriminant of an Ada record is one of its
+        // members, and so has already been printed.
+        if let Some(variant_part) = &class.variant_part {
+            if let Some(discriminant) = &variant_part.discriminant {
+                let is_member = class.members.iter().any(|member| {
+                    member.name == discriminant.name && member.offset == discriminant.offset
+                });
+                if !is_member {
+                    write_member(out, &syntax, "    ", discriminant, &[], class_file)?;
+                }
+            }
+            for variant in &variant_part.variants {
+                let label = if variant.name.is_empty() {
+                    "variant".to_string()
+                } else {
+                    format!("variant {}", variant.name)
+                };
+                let values = variant
+                    .discriminant_values
+                    .iter()
+                    .map(|(first, last)| {
+                        if first == last {
+                            format!("{first}")
+                        } else {
+                            format!("{first}..{last}")
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if values.is_empty() {
+                    writeln!(out, "    {comment} {label}, any other discriminant")?;
+                } else {
+                    writeln!(
+                        out,
+                        "    {comment} {label}, discriminant {}",
+                        values.join(" | ")
+                    )?;
+                }
+                for member in &variant.members {
+                    write_member(
+                        out,
+                        &syntax,
+                        "        ",
+                        member,
+                        &variant.members,
+                        class_file,
+                    )?;
+                }
+            }
+        }
+
+        writeln!(out, "{}", syntax.closing(&name))?;
+
+        Ok(())
+    }
+
+    /// List the other instantiations by name and size, noting those
+    /// whose members differ from the class printed in full, as for a
+    /// specialization.
+    fn instantiations(
+        &mut self,
+        out: &mut dyn Write,
+        class: &ClassLayout,
+        others: &[&ClassLayout],
+    ) -> std::io::Result<()> {
+        let comment = Syntax::new(class).comment;
+        writeln!(out, "{comment} also instantiated as:")?;
+        for other in others {
+            let note = if other.same_structure(class) {
+                ""
+            } else {
+                ", different members"
+            };
+            writeln!(
+                out,
+                "{comment}     {} ({} bytes{note})",
+                other.qualified_name(),
+                other.size_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints a single member, with its size and location in a trailing
+/// comment.  The comment also names any of the `siblings` whose
+/// storage partially overlaps the member's, as may happen with
+/// `[[no_unique_address]]`.  Bitfields, which are shown as their
+/// whole storage unit, are not considered to overlap.  The members of
+/// a union are all alternatives for the same storage, and so are
+/// given by their size and offset rather than as a range.  If the
+/// `class_file` is given, the comment also gives the line at which the
+/// member was declared, along with its file if that differs from the
+/// class's, as for members expanded from another class.
+fn write_member(
+    out: &mut dyn Write,
+    syntax: &Syntax,
+    indent: &str,
+    member: &MemberLayout,
+    siblings: &[MemberLayout],
+    class_file: Option<&str>,
+) -> std::io::Result<()> {
+    let field_size = member.size_bytes;
+    let field_start = member.offset;
+    let field_end = member.end();
+    let type_name = stdlib::display_name(&member.type_name);
+    let declaration = if member.is_flexible_array {
+        syntax.flexible_array_member(&member.name, &type_name)
+    } else if let Some(bitfield) = member.bitfield {
+        syntax.bitfield_member(&member.name, &type_name, bitfield.bit_size)
+    } else {
+        syntax.member(&member.name, &type_name)
+    };
+    // Members that occupy no storage are given by their offset alone,
+    // since a range such as `8-8` looks like a mistake.
+    let location = match (field_size, member.is_base_class) {
+        (0, true) => format!("empty base class, at {field_start}"),
+        (0, false) => format!("0 bytes, at {field_start}"),
+        _ if syntax.is_union => format!("{field_size} bytes, at {field_start}"),
+        _ => format!("{field_size} bytes, {field_start}-{field_end}"),
+    };
+    let mut note = if member.is_flexible_array {
+        ", flexible array member".to_string()
+    } else if let Some(bitfield) = member.bitfield {
+        // The bits are given within the storage unit.
+        let first_bit = bitfield.bit_offset - 8 * field_start;
+        format!(", bits {first_bit}-{}", first_bit + bitfield.bit_size)
+    } else {
+        String::new()
+    };
+    let overlaps = siblings
+        .iter()
+        .filter(|_| member.bitfield.is_none())
+        .filter(|other| other.bitfield.is_none())
+        .filter(|other| (other.offset, other.size_bytes) != (member.offset, member.size_bytes))
+        .filter(|other| other.offset < member.end() && member.offset < other.end())
+        .map(|other| other.name.as_str())
+        .collect::<Vec<_>>();
+    if !overlaps.is_empty() {
+        note += &format!(", overlaps {}", overlaps.join(", "));
+    }
+    if let (Some(class_file), Some(decl_line)) = (class_file, member.decl_line) {
+        match member.decl_file.as_deref() {
+            Some(decl_file) if decl_file != class_file => {
+                note += &format!(", declared at {decl_file}:{decl_line}");
+            }
+            _ => note += &format!(", declared at line {decl_line}"),
+        }
+    }
+    writeln!(
+        out,
+        "{indent}{declaration} {} {location}{note}",
+        syntax.comment
+    )
+}
+
+/// The syntax in which a class is written, following its source
+/// language.  The names of types are written as given by the
+/// compiler, which already uses the language's own spelling, such as
+/// `u32` in Rust or `integer(kind=4)` in Fortran.
+struct Syntax {
+    language: Language,
+
+    /// The keyword that introduces the class in C-like languages.
+    keyword: &'static str,
+
+    /// The prefix of a comment that runs to the end of the line.
+    comment: &'static str,
+
+    /// Whether the class is a union, whose members are alternatives
+    /// rather than being laid out one after another.
+    is_union: bool,
+}
+
+impl Syntax {
+    fn new(class: &ClassLayout) -> Self {
+        let language = class.language;
+        let keyword = match (language, class.kind) {
+            (Language::Rust, _) if class.variant_part.is_some() => "enum",
+            (Language::Cpp, ClassKind::Class) | (_, ClassKind::Union) => class.kind.keyword(),
+            _ => "struct",
+        };
+        let comment = match language {
+            Language::Ada => "--",
+            Language::Fortran => "!",
+            _ => "//",
+        };
+        Self {
+            language,
+            keyword,
+            comment,
+            is_union: class.kind == ClassKind::Union,
+        }
+    }
+
+    /// Returns the line that opens the definition of a class.
+    fn opening(&self, name: &str) -> String {
+        match self.language {
+            Language::Go => format!("type {name} struct {{"),
+            Language::Ada => format!("type {name} is record"),
+            Language::Fortran => format!("type :: {name}"),
+            Language::ObjC => format!("@interface {name} {{"),
+            _ => format!("{} {name} {{", self.keyword),
+        }
+    }
+
+    /// Returns the line that closes the definition of a class.
+    fn closing(&self, name: &str) -> String {
+        match self.language {
+            Language::Rust | Language::Go => "}".into(),
+            Language::Ada => "end record;".into(),
+            Language::Fortran => format!("end type {name}"),
+            Language::ObjC => "}\n@end".into(),
+            _ => "};".into(),
+        }
+    }
+
+    /// Returns the declaration of a member, without its comment.
+    fn member(&self, name: &str, type_name: &str) -> String {
+        match self.language {
+            Language::Rust => format!("{name}: {type_name},"),
+            Language::Go => format!("{name} {type_name}"),
+            Language::Ada => format!("{name} : {type_name};"),
+            Language::Fortran => format!("{type_name} :: {name}"),
+            _ => format!("{type_name} {name};"),
+        }
+    }
+
+    /// Returns the declaration of a bitfield member.  Languages other
+    /// than C and C++ have no syntax for bitfields, and are written as
+    /// an ordinary member.
+    fn bitfield_member(&self, name: &str, type_name: &str, bit_size: usize) -> String {
+        match self.language {
+            Language::C | Language::Cpp | Language::ObjC => {
+                format!("{type_name} {name} : {bit_size};")
+            }
+            _ => self.member(name, type_name),
+        }
+    }
+
+    /// Returns the declaration of a flexible array member.  In C and
+    /// C++, the dimensions of the array are written after its name,
+    /// as in `char data[]`, since that is how the idiom is usually
+    /// recognized.
+    fn flexible_array_member(&self, name: &str, type_name: &str) -> String {
+        let is_c_like = matches!(self.language, Language::C | Language::Cpp | Language::ObjC);
+        match type_name.find('[') {
+            Some(dims) if is_c_like && type_name.ends_with(']') => {
+                let (element_type, dims) = type_name.split_at(dims);
+                format!("{element_type} {name}{dims};")
+            }
+            _ => self.member(name, type_name),
+        }
+    }
+
+    /// Returns the declaration of another name for a class, to be
+    /// written within a comment.
+    fn alias(&self, name: &str, alias: &str) -> String {
+        match self.language {
+            Language::Rust => format!("type {alias} = {name};"),
+            Language::Go => format!("type {alias} = {name}"),
+            Language::Ada => format!("subtype {alias} is {name};"),
+            Language::Fortran => format!("also known as {alias}"),
+            _ => format!("typedef {name} {alias};"),
+        }
+    }
+}
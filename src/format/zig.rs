@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use super::{
+    identifier, layout_hash, padded_fields, shared_description, Field, Primitive, Printer,
+};
+use crate::layout::{ClassLayout, MemberLayout};
+
+/// Prints Zig `extern struct` definitions.  As with the `rust`
+/// format, members are placed at the same offsets as in the binary
+/// by inserting explicit padding fields.
+pub struct ZigPrinter;
+
+/// Identifiers that must be written as `@"name"` to be used as a
+/// field or type name.
+const ZIG_KEYWORDS: &[&str] = &[
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "type",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "var",
+    "volatile",
+    "while",
+    "_",
+];
+
+fn zig_identifier(name: &str) -> String {
+    if ZIG_KEYWORDS.contains(&name) {
+        format!("@\"{name}\"")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The Zig type used to represent the member.
+fn zig_type(member: &MemberLayout) -> String {
+    Primitive::of(member)
+        .map(|primitive| {
+            match primitive {
+                Primitive::I8 => "i8",
+                Primitive::I16 => "i16",
+                Primitive::I32 => "i32",
+                Primitive::I64 => "i64",
+                Primitive::U8 => "u8",
+                Primitive::U16 => "u16",
+                Primitive::U32 => "u32",
+                Primitive::U64 => "u64",
+                Primitive::F32 => "f32",
+                Primitive::F64 => "f64",
+                Primitive::Pointer => "?*anyopaque",
+            }
+            .to_string()
+        })
+        .unwrap_or_else(|| format!("[{}]u8", member.size_bytes))
+}
+
+impl Printer for ZigPrinter {
+    fn class(&mut self, out: &mut dyn Write, class: &ClassLayout) -> std::io::Result<()> {
+        let name = zig_identifier(&identifier(&class.name));
+        let size_bytes = class.size_bytes;
+
+        writeln!(out, "/// `{}`, {size_bytes} bytes", class.name)?;
+        writeln!(out, "pub const {name} = extern struct {{")?;
+
+        for field in padded_fields(class) {
+            match field {
+                Field::Member { member, name } => {
+                    let name = zig_identifier(&name);
+                    let zig_type = zig_type(member);
+                    writeln!(out, "    /// `{}`", member.type_name)?;
+                    writeln!(out, "    {name}: {zig_type},")?;
+                }
+                Field::Padding { index, size_bytes } => {
+                    writeln!(out, "    _padding{index}: [{size_bytes}]u8,")?;
+                }
+                Field::Overlapping { member } => {
+                    writeln!(
+                        out,
+                        "    // Overlaps previous member: {} {} at {}",
+                        member.type_name, member.name, member.offset
+                    )?;
+                }
+                Field::Shared {
+                    index,
+                    size_bytes,
+                    members,
+                } => {
+                    writeln!(
+                        out,
+                        "    /// Overlapping members: {}",
+                        shared_description(&members)
+                    )?;
+                    writeln!(out, "    _overlap{index}: [{size_bytes}]u8,")?;
+                }
+            }
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    pub const layout_hash: u64 = 0x{};",
+            layout_hash(class)
+        )?;
+        writeln!(out, "}};")?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+/// The attribute by which the Go linker records the `reflect.Kind`
+/// of each type, which tells apart the built-in composite types that
+/// are all described as structures.
+pub const DW_AT_GO_KIND: gimli::DwAt = gimli::DwAt(0x2900);
+
+/// The `reflect.Kind` of a slice, described as a structure of
+/// `array`, `len`, and `cap`.
+const KIND_SLICE: u64 = 23;
+
+/// The `reflect.Kind` of a string, described as a structure of
+/// `str` and `len`.
+const KIND_STRING: u64 = 24;
+
+/// Returns true if a structure with the given `reflect.Kind` is the
+/// header of a built-in type, such as a string or slice, rather than
+/// a struct declared in Go.  There is one such structure for every
+/// element type, which would otherwise crowd out the structs of
+/// interest.
+pub fn is_builtin_header(go_kind: u64) -> bool {
+    go_kind == KIND_SLICE || go_kind == KIND_STRING
+}
+
+/// Split the name of a Go type into its package path and the name
+/// within the package, such as `net/http` and `Request` for
+/// `net/http.Request`.  The package path ends at the first `.` after
+/// its last `/`, ignoring the type arguments of generic types, which
+/// may themselves hold qualified names.  Returns `None` for types
+/// that are not in a package, such as `int`, `[]string`, or an
+/// anonymous `struct { ... }`.
+pub fn split_package(name: &str) -> Option<(&str, &str)> {
+    let base = &name[..name.find('[').unwrap_or(name.len())];
+    if base.contains(' ') {
+        return None;
+    }
+    let package_start = base.rfind('/').map_or(0, |slash| slash + 1);
+    let dot = package_start + base[package_start..].find('.')?;
+    Some((&name[..dot], &name[dot + 1..]))
+}
@@ -0,0 +1,479 @@
+use std::io::Write;
+
+use itertools::Itertools as _;
+
+use crate::errors::Error;
+use crate::layout::{
+    Bitfield, ClassIndex, ClassKind, ClassLayout, Enumerator, MemberLayout, TypeKind,
+};
+use crate::search_filter::SearchFilter;
+use crate::stdlib;
+
+/// The deepest nesting of member classes whose values are printed.
+/// Deeper members are printed as raw bytes.
+const MAX_DEPTH: usize = 8;
+
+/// The most bytes printed for a member whose type isn't decoded, such
+/// as an array.
+const MAX_RAW_BYTES: usize = 32;
+
+/// The most characters printed from the string that a `char` pointer
+/// points to.
+pub const MAX_STRING_LEN: usize = 64;
+
+/// Reads of strings are split at multiples of this size, so that a
+/// short string just before an unmapped page can still be read.
+const PAGE_SIZE: u64 = 4096;
+
+/// The address space of a process, which the values of an object are
+/// read from.
+pub trait Memory {
+    /// Fill the buffer with the bytes starting at the address.  Fails
+    /// if any of the bytes are unavailable.
+    fn read(&self, address: u64, buf: &mut [u8]) -> Result<(), Error>;
+
+    /// Whether multi-byte values are stored least significant byte
+    /// first.
+    fn is_little_endian(&self) -> bool;
+}
+
+/// Print each class that matches the search filter as if an instance
+/// of it were at the address, with the value of each member read from
+/// memory.  Members whose type is a known class are expanded to show
+/// the values of their own members.
+pub fn print_objects(
+    classes: impl Iterator<Item = ClassLayout>,
+    search_filter: &SearchFilter,
+    memory: &dyn Memory,
+    address: u64,
+) -> Result<(), Error> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    let index = ClassIndex::new(classes);
+    let matching: Vec<_> = index
+        .iter()
+        .filter(|class| search_filter.matches(class, &index))
+        .unique_by(|class| (class.qualified_name(), class.layout_hash()))
+        .collect();
+    if matching.is_empty() {
+        eprintln!("warning: no class matches the search options");
+    }
+
+    for (i, class) in matching.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let mut bytes = vec![0; class.size_bytes];
+        memory.read(address, &mut bytes)?;
+        writeln!(
+            out,
+            "struct {} @ {address:#x} {{ // {} bytes",
+            class.qualified_name(),
+            class.size_bytes
+        )?;
+        print_members(&mut out, &index, class, &bytes, memory, 1)?;
+        writeln!(out, "}};")?;
+    }
+
+    Ok(())
+}
+
+/// Print the members of a class, given the bytes of an instance.
+/// Pointers to `char` are followed to show the string they point to,
+/// the contents of `std::string` and `std::vector` members are shown
+/// in place of their members, and smart pointers are shown by the
+/// address they hold and the counts of their control block.
+fn print_members(
+    out: &mut dyn Write,
+    index: &ClassIndex,
+    class: &ClassLayout,
+    bytes: &[u8],
+    memory: &dyn Memory,
+    depth: usize,
+) -> std::io::Result<()> {
+    let indent = "    ".repeat(depth);
+    for member in &class.members {
+        let type_name = &*stdlib::display_name(&member.type_name);
+        let name = &member.name;
+        let field_size = member.size_bytes;
+        let field_start = member.offset;
+        let field_end = member.end();
+        let Some(member_bytes) = bytes.get(field_start..field_end) else {
+            continue;
+        };
+
+        let nested = index
+            .get(&member.type_name)
+            .filter(|nested| nested.size_bytes == member.size_bytes)
+            .filter(|_| member.kind == TypeKind::Aggregate && depth < MAX_DEPTH);
+        if let Some(contents) = stdlib::decode(member, member_bytes, index, memory) {
+            writeln!(
+                out,
+                "{indent}{type_name} {name} = {contents}; \
+                 // {field_size} bytes, {field_start}-{field_end}"
+            )?;
+        } else if let Some(nested) = nested {
+            writeln!(
+                out,
+                "{indent}{type_name} {name} = {{ // {field_size} bytes, {field_start}-{field_end}"
+            )?;
+            print_members(out, index, nested, member_bytes, memory, depth + 1)?;
+            writeln!(out, "{indent}}};")?;
+        } else {
+            let mut value = member_value(member, member_bytes, memory.is_little_endian());
+            if is_char_pointer(type_name) {
+                let address = unsigned_value(member_bytes, memory.is_little_endian());
+                if let Some(string) = address.and_then(|address| read_string(memory, address)) {
+                    value = format!("{value} {string}");
+                }
+            }
+            writeln!(
+                out,
+                "{indent}{type_name} {name} = {value}; \
+                 // {field_size} bytes, {field_start}-{field_end}"
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Decode the value of a member from its bytes.  Integers, floats,
+/// pointers, and enumerations are decoded according to the member's
+/// type.  Any other value is shown as hexadecimal bytes.  The bytes of
+/// a bitfield are those of its storage unit, from which its own bits
+/// are extracted.
+pub fn member_value(member: &MemberLayout, bytes: &[u8], little_endian: bool) -> String {
+    match member
+        .bitfield
+        .and_then(|bitfield| bitfield_value(member, bitfield, bytes, little_endian))
+    {
+        Some(value) => typed_value(
+            member.kind,
+            &member.enumerators,
+            &value.to_le_bytes()[..bytes.len()],
+            true,
+        ),
+        None => typed_value(member.kind, &member.enumerators, bytes, little_endian),
+    }
+}
+
+/// Extract the bits of a bitfield from the bytes of its storage unit,
+/// sign-extended to the width of the unit if the bitfield is signed.
+/// Returns `None` if the bitfield doesn't lie within the bytes.
+fn bitfield_value(
+    member: &MemberLayout,
+    bitfield: Bitfield,
+    bytes: &[u8],
+    little_endian: bool,
+) -> Option<u64> {
+    let unit = unsigned_value(bytes, little_endian)?;
+    let unit_bits = 8 * bytes.len();
+    let first_bit = bitfield.bit_offset.checked_sub(8 * member.offset)?;
+    let bit_size = bitfield.bit_size;
+    if bit_size == 0 || first_bit + bit_size > unit_bits {
+        return None;
+    }
+    // Big-endian targets number the bits from the most significant
+    // bit of the unit.
+    let shift = if little_endian {
+        first_bit
+    } else {
+        unit_bits - first_bit - bit_size
+    };
+    let value = (unit >> shift) & (u64::MAX >> (64 - bit_size));
+    if member.kind == TypeKind::Signed {
+        let extend = 64 - bit_size as u32;
+        Some((((value << extend) as i64) >> extend) as u64)
+    } else {
+        Some(value)
+    }
+}
+
+/// Decode a value of the given kind from its bytes, as for
+/// `member_value`.
+pub fn typed_value(
+    kind: TypeKind,
+    enumerators: &[Enumerator],
+    bytes: &[u8],
+    little_endian: bool,
+) -> String {
+    let unsigned = || unsigned_value(bytes, little_endian);
+    let signed = || signed_value(bytes, little_endian);
+
+    let value = match kind {
+        TypeKind::Signed => signed().map(|value| value.to_string()),
+        TypeKind::Unsigned => unsigned().map(|value| value.to_string()),
+        TypeKind::Boolean => unsigned().map(|value| (value != 0).to_string()),
+        TypeKind::Character => unsigned().map(|value| match char::from_u32(value as u32) {
+            Some(c) if bytes.len() == 1 && (c.is_ascii_graphic() || c == ' ') => {
+                format!("{value} '{c}'")
+            }
+            _ => value.to_string(),
+        }),
+        TypeKind::Float => match bytes.len() {
+            4 => unsigned().map(|bits| f32::from_bits(bits as u32).to_string()),
+            8 => unsigned().map(|bits| f64::from_bits(bits).to_string()),
+            _ => None,
+        },
+        TypeKind::Pointer => unsigned().map(|value| format!("{value:#x}")),
+        TypeKind::Enumeration => signed().map(|value| {
+            match enumerators
+                .iter()
+                .find(|enumerator| enumerator.value == value)
+            {
+                Some(enumerator) => format!("{} ({value})", enumerator.name),
+                None => value.to_string(),
+            }
+        }),
+        TypeKind::Aggregate | TypeKind::Unknown => None,
+    };
+    value.unwrap_or_else(|| raw_bytes(bytes))
+}
+
+/// Decode an unsigned integer of up to eight bytes.
+pub fn unsigned_value(bytes: &[u8], little_endian: bool) -> Option<u64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+    let mut buf = [0; 8];
+    if little_endian {
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Some(u64::from_le_bytes(buf))
+    } else {
+        buf[8 - bytes.len()..].copy_from_slice(bytes);
+        Some(u64::from_be_bytes(buf))
+    }
+}
+
+/// Decode a two's complement integer of up to eight bytes.
+pub fn signed_value(bytes: &[u8], little_endian: bool) -> Option<i64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+    let shift = 64 - 8 * bytes.len() as u32;
+    Some(((unsigned_value(bytes, little_endian)? << shift) as i64) >> shift)
+}
+
+/// Returns true if the type is a pointer to a single-byte character,
+/// such as `const char*`, which usually points to a C string.
+fn is_char_pointer(type_name: &str) -> bool {
+    let Some(pointee) = type_name.strip_suffix('*') else {
+        return false;
+    };
+    let pointee = pointee
+        .split_whitespace()
+        .filter(|word| *word != "const" && *word != "volatile")
+        .join(" ");
+    matches!(
+        pointee.as_str(),
+        "char" | "signed char" | "unsigned char" | "char8_t"
+    )
+}
+
+/// Read the null-terminated string at the address, formatted as a
+/// quoted string literal, with bytes other than printable ASCII
+/// escaped.  Strings longer than
+/// `MAX_STRING_LEN` are truncated.  Returns `None` if the address is
+/// null or can't be read.
+fn read_string(memory: &dyn Memory, address: u64) -> Option<String> {
+    if address == 0 {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    let mut next = address;
+    let is_terminated = loop {
+        let chunk_len = (PAGE_SIZE - next % PAGE_SIZE).min((MAX_STRING_LEN - bytes.len()) as u64);
+        let mut chunk = vec![0; chunk_len as usize];
+        if memory.read(next, &mut chunk).is_err() {
+            if bytes.is_empty() {
+                return None;
+            }
+            break false;
+        }
+        if let Some(len) = chunk.iter().position(|&byte| byte == 0) {
+            bytes.extend_from_slice(&chunk[..len]);
+            break true;
+        }
+        bytes.extend_from_slice(&chunk);
+        next += chunk_len;
+        if bytes.len() >= MAX_STRING_LEN {
+            break false;
+        }
+    };
+    let suffix = if is_terminated { "" } else { "..." };
+    Some(format!("{}{suffix}", quote(&bytes)))
+}
+
+/// Format bytes as a quoted string literal, with bytes other than
+/// printable ASCII escaped.
+pub fn quote(bytes: &[u8]) -> String {
+    let string: String = bytes
+        .iter()
+        .flat_map(|&byte| std::ascii::escape_default(byte))
+        .map(char::from)
+        .collect();
+    format!("\"{string}\"")
+}
+
+/// Format bytes as space-separated hexadecimal, truncated to
+/// `MAX_RAW_BYTES`.
+fn raw_bytes(bytes: &[u8]) -> String {
+    let shown = bytes
+        .iter()
+        .take(MAX_RAW_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .join(" ");
+    if bytes.len() > MAX_RAW_BYTES {
+        format!("[{shown} ...]")
+    } else {
+        format!("[{shown}]")
+    }
+}
+
+/// A range of bytes within an overlay, along with its label.
+struct OverlayRow {
+    offset: usize,
+    size_bytes: usize,
+    label: String,
+}
+
+/// The number of bytes shown on each line of an overlay.
+const OVERLAY_WIDTH: usize = 16;
+
+/// Print a hexdump of the bytes of an instance of the class, with the
+/// bytes of each member labeled by the member's name, type, and value.
+/// Members whose type is a known class are replaced by their own
+/// members, and bytes that don't belong to any member are labeled as
+/// padding.  If there are fewer bytes than the size of the class, only
+/// the members that they cover are shown.
+pub fn print_overlay(
+    classes: impl Iterator<Item = ClassLayout>,
+    class_name: &str,
+    bytes: &[u8],
+    little_endian: bool,
+) -> Result<(), Error> {
+    let index = ClassIndex::new(classes);
+    let class = index
+        .get(class_name)
+        .ok_or_else(|| Error::UnknownClass(class_name.to_string()))?;
+    if bytes.len() < class.size_bytes {
+        eprintln!(
+            "warning: {} is {} bytes, but only {} bytes were given",
+            class.qualified_name(),
+            class.size_bytes,
+            bytes.len()
+        );
+    }
+    let bytes = &bytes[..bytes.len().min(class.size_bytes)];
+
+    let mut rows = Vec::new();
+    overlay_rows(&index, class, bytes, little_endian, 0, "", 1, &mut rows);
+
+    // Label the gaps between members, and after the last member.
+    let mut padded_rows = Vec::new();
+    let mut end = 0;
+    for row in rows.into_iter().chain(std::iter::once(OverlayRow {
+        offset: class.size_bytes,
+        size_bytes: 0,
+        label: String::new(),
+    })) {
+        if row.offset > end {
+            padded_rows.push(OverlayRow {
+                offset: end,
+                size_bytes: row.offset - end,
+                label: "(padding)".into(),
+            });
+        }
+        end = end.max(row.offset + row.size_bytes);
+        if row.size_bytes > 0 {
+            padded_rows.push(row);
+        }
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    writeln!(
+        out,
+        "{} {} {{ // {} bytes",
+        class.kind.keyword(),
+        class.qualified_name(),
+        class.size_bytes
+    )?;
+    for row in &padded_rows {
+        let Some(row_bytes) = bytes.get(row.offset..row.offset + row.size_bytes) else {
+            continue;
+        };
+        for (i, line) in row_bytes.chunks(OVERLAY_WIDTH).enumerate() {
+            let hex = line.iter().map(|byte| format!("{byte:02x}")).join(" ");
+            let label = if i == 0 { row.label.as_str() } else { "" };
+            let line_offset = row.offset + i * OVERLAY_WIDTH;
+            let text = format!(
+                "{line_offset:08x}  {hex:<width$}  {label}",
+                width = 3 * OVERLAY_WIDTH - 1
+            );
+            writeln!(out, "{}", text.trim_end())?;
+        }
+    }
+    writeln!(out, "}};")?;
+
+    Ok(())
+}
+
+/// Collect the labeled byte ranges of the members of a class, in
+/// order, expanding members whose type is a known class.  The `prefix`
+/// is the path of enclosing members, such as `position.`.  The members
+/// of a union are each labeled as an alternative, and their bytes are
+/// shown once for each alternative, so that every interpretation of
+/// the shared storage can be read.
+#[allow(clippy::too_many_arguments)]
+fn overlay_rows(
+    index: &ClassIndex,
+    class: &ClassLayout,
+    bytes: &[u8],
+    little_endian: bool,
+    base_offset: usize,
+    prefix: &str,
+    depth: usize,
+    rows: &mut Vec<OverlayRow>,
+) {
+    for member in &class.members {
+        let path = format!("{prefix}{}", member.name);
+        let offset = base_offset + member.offset;
+        let nested = index
+            .get(&member.type_name)
+            .filter(|nested| nested.size_bytes == member.size_bytes)
+            .filter(|_| member.kind == TypeKind::Aggregate && depth < MAX_DEPTH);
+        if let Some(nested) = nested {
+            let prefix = format!("{path}.");
+            overlay_rows(
+                index,
+                nested,
+                bytes,
+                little_endian,
+                offset,
+                &prefix,
+                depth + 1,
+                rows,
+            );
+            continue;
+        }
+
+        let mut label = match bytes.get(offset..offset + member.size_bytes) {
+            Some(member_bytes) => format!(
+                "{path}: {} = {}",
+                member.type_name,
+                member_value(member, member_bytes, little_endian)
+            ),
+            None => format!("{path}: {}", member.type_name),
+        };
+        if class.kind == ClassKind::Union {
+            label += " (alternative)";
+        }
+        rows.push(OverlayRow {
+            offset,
+            size_bytes: member.size_bytes,
+            label,
+        });
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use crate::errors::Error;
+use crate::LoadOptions;
+
+/// The directory in which the running kernel exposes the BTF of
+/// itself, as `vmlinux`, and of each loaded module.
+const KERNEL_BTF_DIR: &str = "/sys/kernel/btf";
+
+/// Find the image of the running kernel.  An uncompressed `vmlinux`
+/// with DWARF information is preferred, as installed by the kernel's
+/// debug package or left in the build tree, since it records source
+/// locations and explicit alignments.  Otherwise, the BTF exposed by
+/// the kernel itself is used.
+pub fn find_vmlinux(load_options: &LoadOptions) -> Result<PathBuf, Error> {
+    let release = std::fs::read_to_string("/proc/sys/kernel/osrelease")?;
+    let release = release.trim();
+    let candidates = [
+        load_options
+            .debug_root
+            .join("boot")
+            .join(format!("vmlinux-{release}")),
+        load_options
+            .debug_root
+            .join("lib/modules")
+            .join(release)
+            .join("vmlinux"),
+        PathBuf::from(format!("/boot/vmlinux-{release}")),
+        Path::new("/lib/modules")
+            .join(release)
+            .join("build/vmlinux"),
+        Path::new(KERNEL_BTF_DIR).join("vmlinux"),
+    ];
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| Error::NoKernelImage(release.to_string()))
+}
+
+/// Returns the BTF that the BTF of the file extends: the file given
+/// by `--btf-base`, if any, and otherwise the running kernel's BTF if
+/// the file is a kernel module, either as a `.ko` file or as exposed
+/// in `/sys/kernel/btf`.
+pub fn btf_base(path: &Path, load_options: &LoadOptions) -> Option<PathBuf> {
+    if let Some(btf_base) = &load_options.btf_base {
+        return Some(btf_base.clone());
+    }
+    let vmlinux = Path::new(KERNEL_BTF_DIR).join("vmlinux");
+    let is_module = path.extension().is_some_and(|extension| extension == "ko")
+        || (path.parent() == Some(Path::new(KERNEL_BTF_DIR)) && path != vmlinux);
+    (is_module && vmlinux.is_file()).then_some(vmlinux)
+}
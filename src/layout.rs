@@ -0,0 +1,798 @@
+use std::collections::HashMap;
+
+/// The layout of a single class, as extracted from the DWARF
+/// information.  This is the representation used by all output
+/// formats, so that each format only needs to know how to print a
+/// class, and not how to unpack it from the debug information.
+#[derive(Debug, Clone)]
+pub struct ClassLayout {
+    /// The name of the class, without any enclosing namespace.
+    pub name: String,
+
+    /// The namespace that contains the class, or an empty string for
+    /// classes at global scope.  Nested namespaces are separated as
+    /// for the class's `language`.
+    pub namespace: String,
+
+    /// The source language of the class, which determines how it is
+    /// written.
+    pub language: Language,
+
+    /// The keyword with which the class was declared.
+    pub kind: ClassKind,
+
+    /// The input binary in which the class was found, if classes
+    /// were read from more than one binary.
+    pub binary: Option<String>,
+
+    /// The name of the compilation unit in which the class was
+    /// found, if known.
+    pub unit_name: Option<String>,
+
+    /// The path of the file in which the class was declared, if
+    /// known.
+    pub decl_file: Option<String>,
+
+    /// The line within `decl_file` at which the class was declared,
+    /// if known.
+    pub decl_line: Option<u64>,
+
+    /// The offset within .debug_info of the compilation unit that
+    /// contains the class, if read from DWARF.
+    pub unit_offset: Option<u64>,
+
+    /// The offset within .debug_info of the class's entry, if read
+    /// from DWARF.
+    pub die_offset: Option<u64>,
+
+    /// Whether the class itself declares a vtable pointer or virtual
+    /// methods.  Classes that only inherit a vtable pointer from a
+    /// base class may have this flag unset.
+    pub has_vtable: bool,
+
+    /// The typedef names that refer to the class, including their
+    /// enclosing namespace.
+    pub aliases: Vec<String>,
+
+    /// The mangled names of the methods and static data members
+    /// declared within the class.
+    pub linkage_names: Vec<String>,
+
+    /// The size of the class, in bytes.  For a class of dynamic or
+    /// inferred size, this is inferred from its members, and is a
+    /// lower bound on its size.
+    pub size_bytes: usize,
+
+    /// How the size of the class is known.
+    pub size_kind: SizeKind,
+
+    /// The alignment of the class, in bytes, if given explicitly, as
+    /// by `__attribute__((__aligned__(N)))` or `alignas(N)`.
+    pub alignment: Option<usize>,
+
+    /// The data members and base classes, in the order in which
+    /// they occur in the debug information.
+    pub members: Vec<MemberLayout>,
+
+    /// For a Rust enum or an Ada variant record, the variants that
+    /// share the storage of the class.  `None` for all other classes.
+    pub variant_part: Option<VariantPart>,
+}
+
+/// The variants of a Rust enum or an Ada variant record, as
+/// described by a `DW_TAG_variant_part`.
+#[derive(Debug, Clone)]
+pub struct VariantPart {
+    /// The member whose value selects the active variant, if any.
+    /// For enums whose discriminant is stored in a niche of another
+    /// variant's members, such as `Option<&T>`, this overlaps the
+    /// members of that variant.  The discriminant of an Ada record is
+    /// also one of the record's own members.
+    pub discriminant: Option<MemberLayout>,
+
+    /// The variants, in the order in which they occur in the debug
+    /// information.
+    pub variants: Vec<VariantLayout>,
+}
+
+/// A single variant within a `VariantPart`.
+#[derive(Debug, Clone)]
+pub struct VariantLayout {
+    /// The name of the variant, or an empty string for the unnamed
+    /// variants of Ada records.
+    pub name: String,
+
+    /// The inclusive ranges of discriminant values that select this
+    /// variant.  Empty if the variant is selected by every value not
+    /// listed for another variant, as for the variant that holds the
+    /// niche of a Rust enum, or the `when others` of an Ada record.
+    pub discriminant_values: Vec<(i64, i64)>,
+
+    /// The members of the variant, with offsets from the start of
+    /// the class.
+    pub members: Vec<MemberLayout>,
+}
+
+/// A single data member or base class within a `ClassLayout`.
+#[derive(Debug, Clone)]
+pub struct MemberLayout {
+    /// The name of the member.  Base classes are named
+    /// `_base_class`.
+    pub name: String,
+
+    /// The name of the member's type, after expanding typedefs.
+    pub type_name: std::sync::Arc<str>,
+
+    /// The kind of the member's type, after expanding typedefs.
+    pub kind: TypeKind,
+
+    /// Whether this entry is a base class, rather than a data
+    /// member.
+    pub is_base_class: bool,
+
+    /// The offset of the member from the start of the class, in
+    /// bytes.
+    pub offset: usize,
+
+    /// The size of the member, in bytes.
+    pub size_bytes: usize,
+
+    /// The alignment of the member, in bytes, if given explicitly.
+    pub alignment: Option<usize>,
+
+    /// If the member's type is an enumeration, the enumerators of
+    /// that type.  Otherwise, empty.
+    pub enumerators: Vec<Enumerator>,
+
+    /// If the member is a pointer or reference, the name of the type
+    /// ultimately pointed to.  Otherwise, `None`.
+    pub pointee: Option<String>,
+
+    /// If the member is a pointer or reference to a class, the kind of
+    /// that class, so that the class can be declared where only the
+    /// pointer is needed.  Otherwise, `None`.
+    pub pointee_class: Option<ClassKind>,
+
+    /// Whether the member is a flexible array member, an array at
+    /// the end of the class whose length, if declared at all, doesn't
+    /// bound the elements stored after the class.  Its `size_bytes`
+    /// covers only the declared length.
+    pub is_flexible_array: bool,
+
+    /// Whether the member's type or, for a pointer, the type it
+    /// points to, is a class that is only declared, with no
+    /// definition among the classes read.
+    pub is_incomplete: bool,
+
+    /// If the member is a bitfield, the bits that it occupies.  Its
+    /// offset and size are those of the storage unit that holds it,
+    /// which it may share with other members.
+    pub bitfield: Option<Bitfield>,
+
+    /// The path of the file in which the member was declared, if
+    /// known.  This is usually the `decl_file` of its class.
+    pub decl_file: Option<std::sync::Arc<str>>,
+
+    /// The line within `decl_file` at which the member was declared,
+    /// if known.
+    pub decl_line: Option<u64>,
+}
+
+/// The bits of a class occupied by a bitfield member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bitfield {
+    /// The offset of the first bit from the start of the class.  Bits
+    /// are numbered from the least significant bit of each byte on
+    /// little-endian targets, and from the most significant bit on
+    /// big-endian targets.
+    pub bit_offset: usize,
+
+    /// The width of the bitfield, in bits.
+    pub bit_size: usize,
+}
+
+impl Bitfield {
+    /// The offset of the storage unit of `size_bytes` that holds the
+    /// bitfield.  The unit is aligned to its size, as the C ABIs
+    /// allocate bitfields, unless the bitfield crosses the boundary of
+    /// such a unit, as in a packed class.  It then starts at the byte
+    /// that holds the first bit.
+    pub fn storage_offset(&self, size_bytes: usize) -> usize {
+        let unit_bits = 8 * size_bytes.max(1);
+        let aligned = self.bit_offset / unit_bits * unit_bits;
+        if self.bit_offset + self.bit_size <= aligned + unit_bits {
+            aligned / 8
+        } else {
+            self.bit_offset / 8
+        }
+    }
+}
+
+/// A single named value of an enumeration.
+#[derive(Debug, Clone)]
+pub struct Enumerator {
+    pub name: String,
+    pub value: i64,
+}
+
+/// How the size of a class is known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeKind {
+    /// The size is fixed, and given by the debug information.
+    #[default]
+    Exact,
+
+    /// The size depends on values only known at runtime, as for a
+    /// struct with a variable-length array, or an Ada record whose
+    /// size depends on its discriminants.
+    Dynamic,
+
+    /// The size was not given, and is inferred from the end of the
+    /// last member, rounded up to the alignment of the members.  The
+    /// true size may be larger.
+    Inferred,
+}
+
+/// The source language of a class.  Languages without their own
+/// conventions are written as C.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    C,
+    Cpp,
+    Rust,
+    Go,
+    Fortran,
+    Ada,
+
+    /// An Objective-C class, as opposed to a C struct declared in
+    /// Objective-C.
+    ObjC,
+}
+
+impl Language {
+    /// The separator between a namespace and the names within it,
+    /// such as `::` in C++ or `.` for Go packages.
+    pub fn namespace_separator(self) -> &'static str {
+        match self {
+            Language::Go | Language::Ada => ".",
+            _ => "::",
+        }
+    }
+}
+
+/// The keyword with which a class was declared.  Interfaces are
+/// declared as classes, and only C++ distinguishes classes from
+/// structs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClassKind {
+    #[default]
+    Struct,
+    Class,
+
+    /// A union, whose members all start at offset 0 and are
+    /// alternatives for the same storage.
+    Union,
+}
+
+impl ClassKind {
+    /// The keyword that introduces a class of this kind in C and C++.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            ClassKind::Struct => "struct",
+            ClassKind::Class => "class",
+            ClassKind::Union => "union",
+        }
+    }
+}
+
+/// The broad category of a member's type.  Used by output formats
+/// that need to map the member onto a primitive type of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeKind {
+    /// A signed integer (`DW_ATE_signed`)
+    Signed,
+
+    /// An unsigned integer (`DW_ATE_unsigned`)
+    Unsigned,
+
+    /// A floating-point value (`DW_ATE_float`)
+    Float,
+
+    /// A boolean (`DW_ATE_boolean`)
+    Boolean,
+
+    /// A signed or unsigned character (`DW_ATE_signed_char` or
+    /// `DW_ATE_unsigned_char`)
+    Character,
+
+    /// A pointer or reference to another type.
+    Pointer,
+
+    /// An enumeration.
+    Enumeration,
+
+    /// A class, struct, or union.
+    Aggregate,
+
+    /// Any other type, such as an array.
+    Unknown,
+}
+
+/// The order in which classes are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// By qualified name.
+    Name,
+
+    /// By size in bytes.
+    Size,
+
+    /// By the total number of padding bytes.
+    Padding,
+
+    /// By the name of the compilation unit that defines the class.
+    Cu,
+}
+
+impl SortKey {
+    /// Compare two classes by this key.  Ties are broken by the
+    /// qualified name, so that the order doesn't depend on the order
+    /// of the debug information.
+    pub fn compare(&self, a: &ClassLayout, b: &ClassLayout) -> std::cmp::Ordering {
+        let by_key = match self {
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::Size => a.size_bytes.cmp(&b.size_bytes),
+            SortKey::Padding => a.padding_bytes().cmp(&b.padding_bytes()),
+            SortKey::Cu => a.unit_name.cmp(&b.unit_name),
+        };
+        by_key.then_with(|| a.qualified_name().cmp(&b.qualified_name()))
+    }
+}
+
+/// A range of bytes within a class that isn't occupied by any
+/// member, and isn't at the end of the class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hole {
+    /// The offset of the first unused byte.
+    pub offset: usize,
+
+    /// The number of unused bytes.
+    pub size_bytes: usize,
+}
+
+impl ClassLayout {
+    /// The name of the class, including its enclosing namespace.
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            let separator = self.language.namespace_separator();
+            format!("{}{separator}{}", self.namespace, self.name)
+        }
+    }
+
+    /// The name of the template that the class instantiates, such as
+    /// `Pair` for `Pair<int, double>`, or `None` for a class that
+    /// isn't an instantiation.
+    pub fn template_name(&self) -> Option<&str> {
+        let (template, _) = self.name.strip_suffix('>')?.split_once('<')?;
+        (!template.is_empty()).then_some(template)
+    }
+
+    /// Returns true if the two classes have members of the same names,
+    /// in the same order, and variants of the same names, as do the
+    /// instantiations of a template that isn't specialized.  The
+    /// types, offsets, and sizes of the members may differ.
+    pub fn same_structure(&self, other: &ClassLayout) -> bool {
+        let members = |class: &ClassLayout| {
+            class
+                .members
+                .iter()
+                .map(|member| (member.name.clone(), member.is_base_class))
+                .collect::<Vec<_>>()
+        };
+        let variants = |class: &ClassLayout| {
+            class.variant_part.as_ref().map(|variant_part| {
+                variant_part
+                    .variants
+                    .iter()
+                    .map(|variant| variant.name.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        members(self) == members(other) && variants(self) == variants(other)
+    }
+
+    /// The unused bytes between members.  A hole is reported
+    /// immediately before the member that follows it.
+    pub fn holes(&self) -> Vec<Hole> {
+        let mut holes = Vec::new();
+        let mut position = 0;
+        for member in &self.members {
+            if member.offset > position {
+                holes.push(Hole {
+                    offset: position,
+                    size_bytes: member.offset - position,
+                });
+            }
+            position = position.max(member.end());
+        }
+        holes
+    }
+
+    /// The unused bytes after the last member.
+    pub fn tail_padding(&self) -> usize {
+        let end = self
+            .members
+            .iter()
+            .map(|member| member.end())
+            .max()
+            .unwrap_or(0);
+        self.size_bytes.saturating_sub(end)
+    }
+
+    /// Describes where the class was found, as the compilation unit
+    /// followed by the file and line of the declaration, for example
+    /// `src/npc.cpp (include/npc.h:12)`.  If the class records its
+    /// binary, the binary is given first, as in
+    /// `libgame.so: src/npc.cpp (include/npc.h:12)`.
+    pub fn origin(&self) -> String {
+        let unit_name = self
+            .unit_name
+            .as_deref()
+            .unwrap_or("unknown compilation unit");
+        let origin = match self.declaration() {
+            Some(declaration) => format!("{unit_name} ({declaration})"),
+            None => unit_name.to_string(),
+        };
+        match &self.binary {
+            Some(binary) => format!("{binary}: {origin}"),
+            None => origin,
+        }
+    }
+
+    /// Where the class was declared, as the file and line, for example
+    /// `include/npc.h:12`, or the file alone if the line is unknown.
+    pub fn declaration(&self) -> Option<String> {
+        let decl_file = self.decl_file.as_ref()?;
+        match self.decl_line {
+            Some(decl_line) => Some(format!("{decl_file}:{decl_line}")),
+            None => Some(decl_file.clone()),
+        }
+    }
+
+    /// A hash of the layout of the class, including its name, size,
+    /// the name, type, offset, and size of each member, and the
+    /// variants of Rust enums and Ada records.  Where
+    /// the class was found is not included, so identical definitions
+    /// from different compilation units have the same hash.  The hash
+    /// is FNV-1a, and so is stable across runs and platforms.
+    pub fn layout_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut write = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+        };
+
+        write(self.qualified_name().as_bytes());
+        write(&(self.size_bytes as u64).to_le_bytes());
+        let variants = self
+            .variant_part
+            .iter()
+            .flat_map(|variant_part| &variant_part.variants);
+        let variant_members = variants.clone().flat_map(|variant| &variant.members);
+        let discriminant = self
+            .variant_part
+            .as_ref()
+            .and_then(|variant_part| variant_part.discriminant.as_ref());
+        for member in self
+            .members
+            .iter()
+            .chain(discriminant)
+            .chain(variant_members)
+        {
+            write(member.name.as_bytes());
+            write(member.type_name.as_bytes());
+            write(&[member.is_base_class as u8]);
+            write(&(member.offset as u64).to_le_bytes());
+            write(&(member.size_bytes as u64).to_le_bytes());
+        }
+        for variant in variants {
+            write(variant.name.as_bytes());
+            for (first, last) in &variant.discriminant_values {
+                write(&first.to_le_bytes());
+                write(&last.to_le_bytes());
+            }
+        }
+        hash
+    }
+
+    /// The total number of unused bytes, including both holes
+    /// between members and padding at the end of the class.
+    pub fn padding_bytes(&self) -> usize {
+        self.holes()
+            .iter()
+            .map(|hole| hole.size_bytes)
+            .sum::<usize>()
+            + self.tail_padding()
+    }
+}
+
+impl MemberLayout {
+    /// The offset one past the last byte of the member.
+    pub fn end(&self) -> usize {
+        self.offset + self.size_bytes
+    }
+}
+
+/// All classes found in the input, along with a lookup by name.
+/// Used to follow base classes and members into the layouts of their
+/// own types.
+pub struct ClassIndex {
+    /// All classes, in the order in which they were found, including
+    /// duplicate definitions from different compilation units.
+    classes: Vec<ClassLayout>,
+
+    /// The position in `classes` of the first class with each name.
+    /// Classes can be looked up either by their qualified name, or by
+    /// their unqualified name as used for the types of members.
+    by_name: HashMap<String, usize>,
+}
+
+impl ClassIndex {
+    /// Index the classes by name.  A member whose type is only
+    /// declared in the compilation unit it was read from is no longer
+    /// incomplete if another unit defines the type.
+    pub fn new(classes: impl Iterator<Item = ClassLayout>) -> Self {
+        let mut classes: Vec<_> = classes.collect();
+        let mut by_name = HashMap::new();
+        for (i, class) in classes.iter().enumerate() {
+            by_name.entry(class.qualified_name()).or_insert(i);
+            by_name.entry(class.name.clone()).or_insert(i);
+            for alias in &class.aliases {
+                by_name.entry(alias.clone()).or_insert(i);
+            }
+        }
+        for member in classes
+            .iter_mut()
+            .flat_map(|class| class.members.iter_mut())
+            .filter(|member| member.is_incomplete)
+        {
+            let type_name = member.pointee.as_deref().unwrap_or(&member.type_name);
+            member.is_incomplete = !by_name.contains_key(type_name);
+        }
+        Self { classes, by_name }
+    }
+
+    /// Look up a class by name.
+    pub fn get(&self, name: &str) -> Option<&ClassLayout> {
+        self.by_name.get(name).map(|&i| &self.classes[i])
+    }
+
+    /// Iterate over all classes, in the order in which they were
+    /// found.
+    pub fn iter(&self) -> impl Iterator<Item = &ClassLayout> + '_ {
+        self.classes.iter()
+    }
+
+    /// Iterate over the names of all direct and indirect base classes
+    /// of the class.  Each base class is visited once, even if it is
+    /// inherited along multiple paths.
+    pub fn ancestors<'a>(&'a self, class: &'a ClassLayout) -> impl Iterator<Item = &'a str> + 'a {
+        let mut visited = std::collections::HashSet::new();
+        let mut stack: Vec<&str> = class
+            .members
+            .iter()
+            .filter(|member| member.is_base_class)
+            .map(|member| &*member.type_name)
+            .collect();
+        std::iter::from_fn(move || loop {
+            let name = stack.pop()?;
+            if !visited.insert(name) {
+                continue;
+            }
+            if let Some(base) = self.get(name) {
+                stack.extend(
+                    base.members
+                        .iter()
+                        .filter(|member| member.is_base_class)
+                        .map(|member| &*member.type_name),
+                );
+            }
+            return Some(name);
+        })
+    }
+
+    /// Returns true if the class, or any of its base classes, has a
+    /// vtable pointer.
+    pub fn is_polymorphic(&self, class: &ClassLayout) -> bool {
+        class.has_vtable
+            || self
+                .ancestors(class)
+                .filter_map(|name| self.get(name))
+                .any(|base| base.has_vtable)
+    }
+
+    /// Returns a copy of the class in which each member whose type is
+    /// a known class is replaced by that class's members, up to
+    /// `depth` levels deep, with offsets from the start of the
+    /// outermost class.  Nested members are named by their path, as
+    /// `position.x`, while the members of a base class keep their own
+    /// names, as they are accessed directly.  Members whose class
+    /// has a variant part, or whose size differs from that of their
+    /// class, are kept whole.
+    pub fn expand_members(&self, class: &ClassLayout, depth: usize) -> ClassLayout {
+        let mut members = Vec::new();
+        self.expand_into(&class.members, 0, "", depth, &mut members);
+        ClassLayout {
+            members,
+            ..class.clone()
+        }
+    }
+
+    /// Append the `members` to `expanded`, with offsets from
+    /// `base_offset`, expanding those whose type is a known class.
+    fn expand_into(
+        &self,
+        members: &[MemberLayout],
+        base_offset: usize,
+        prefix: &str,
+        depth: usize,
+        expanded: &mut Vec<MemberLayout>,
+    ) {
+        for member in members {
+            let nested = self
+                .get(&member.type_name)
+                .filter(|_| depth > 0 && member.kind == TypeKind::Aggregate)
+                .filter(|nested| nested.variant_part.is_none() && !nested.members.is_empty())
+                .filter(|nested| nested.size_bytes == member.size_bytes);
+            match nested {
+                Some(nested) => {
+                    let prefix = if member.is_base_class {
+                        prefix.to_string()
+                    } else {
+                        format!("{prefix}{}.", member.name)
+                    };
+                    self.expand_into(
+                        &nested.members,
+                        base_offset + member.offset,
+                        &prefix,
+                        depth - 1,
+                        expanded,
+                    );
+                }
+                None => expanded.push(MemberLayout {
+                    name: format!("{prefix}{}", member.name),
+                    offset: base_offset + member.offset,
+                    ..member.clone()
+                }),
+            }
+        }
+    }
+
+    /// Returns the `classes`, followed by every known class that they
+    /// depend on, directly or indirectly, as the type of a member or
+    /// as a base class.  With `follow_pointers`, the classes that
+    /// members point to are included as well.  Each dependency is
+    /// listed once, in the order in which it was first reached, and
+    /// classes that are already among the `classes` are not repeated.
+    pub fn with_dependencies<'a>(
+        &'a self,
+        classes: Vec<&'a ClassLayout>,
+        follow_pointers: bool,
+    ) -> Vec<&'a ClassLayout> {
+        let mut visited: std::collections::HashSet<String> =
+            classes.iter().map(|class| class.qualified_name()).collect();
+        let mut result = classes;
+        let mut next = 0;
+        while let Some(class) = result.get(next).copied() {
+            next += 1;
+            let variant_members = class
+                .variant_part
+                .iter()
+                .flat_map(|variant_part| &variant_part.variants)
+                .flat_map(|variant| &variant.members);
+            for member in class.members.iter().chain(variant_members) {
+                let embedded = (member.kind == TypeKind::Aggregate).then_some(&*member.type_name);
+                let pointee = member.pointee.as_deref().filter(|_| follow_pointers);
+                for dependency in embedded.into_iter().chain(pointee) {
+                    let Some(dependency) = self.get(dependency) else {
+                        continue;
+                    };
+                    if visited.insert(dependency.qualified_name()) {
+                        result.push(dependency);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Reorder the `classes` so that each class comes after the
+    /// classes among them that it embeds, either as a member or as a
+    /// base class, as a compiler requires.  Classes are otherwise
+    /// kept in their given order.  Classes that are only reached
+    /// through a pointer may come later, since a pointer only needs
+    /// its class to be declared, which allows cycles of pointers.
+    pub fn dependency_order<'a>(&'a self, classes: Vec<&'a ClassLayout>) -> Vec<&'a ClassLayout> {
+        let positions: HashMap<String, usize> = classes
+            .iter()
+            .enumerate()
+            .map(|(i, class)| (class.qualified_name(), i))
+            .collect();
+        let embedded = |class: &ClassLayout| -> Vec<usize> {
+            let variant_members = class
+                .variant_part
+                .iter()
+                .flat_map(|variant_part| &variant_part.variants)
+                .flat_map(|variant| &variant.members);
+            class
+                .members
+                .iter()
+                .chain(variant_members)
+                .filter(|member| member.kind == TypeKind::Aggregate)
+                .filter_map(|member| self.get(&member.type_name))
+                .filter_map(|dependency| positions.get(&dependency.qualified_name()).copied())
+                .collect()
+        };
+
+        // A depth-first search, in which each class is placed once
+        // all of its dependencies have been placed.
+        let mut placed = vec![false; classes.len()];
+        let mut ordered = Vec::with_capacity(classes.len());
+        for start in 0..classes.len() {
+            let mut stack = vec![(start, false)];
+            while let Some((i, expanded)) = stack.pop() {
+                if placed[i] {
+                    continue;
+                }
+                if expanded {
+                    placed[i] = true;
+                    ordered.push(classes[i]);
+                    continue;
+                }
+                stack.push((i, true));
+                stack.extend(
+                    embedded(classes[i])
+                        .into_iter()
+                        .rev()
+                        .filter(|&dependency| !placed[dependency])
+                        .map(|dependency| (dependency, false)),
+                );
+            }
+        }
+        ordered
+    }
+
+    /// Iterate over the type names of all data members embedded
+    /// within the class, either directly or within other embedded
+    /// members.  Members inherited from base classes are included.
+    /// Members accessed through a pointer are not embedded, and so
+    /// are not followed.
+    pub fn embedded_types<'a>(
+        &'a self,
+        class: &'a ClassLayout,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        let mut visited = std::collections::HashSet::new();
+        let mut stack: Vec<&ClassLayout> = vec![class];
+        let mut pending: Vec<&str> = Vec::new();
+        std::iter::from_fn(move || loop {
+            if let Some(name) = pending.pop() {
+                return Some(name);
+            }
+            let class = stack.pop()?;
+            for member in &class.members {
+                let nested = (member.kind == TypeKind::Aggregate)
+                    .then(|| self.get(&member.type_name))
+                    .flatten()
+                    .filter(|nested| visited.insert(nested.qualified_name()));
+                stack.extend(nested);
+                if !member.is_base_class {
+                    pending.push(&member.type_name);
+                }
+            }
+        })
+    }
+}
@@ -1,56 +1,701 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use clap::Parser as _;
 use itertools::Itertools as _;
 
 use fallible_iterator::FallibleIterator;
-use gimli::{Dwarf, Reader, Unit};
+use gimli::{Dwarf, Reader, ReaderOffset as _, Unit};
 use object::{Object, ObjectSection};
 
+mod abi_check;
+
+mod ada;
+
+mod btf;
+use btf::Btf;
+
+mod core_dump;
+use core_dump::CoreDump;
+
+mod demangle;
+
+mod dependencies;
+
+mod debug_file;
+
+mod debuginfod;
+
+mod diff;
+
+mod download;
+
+mod dwarf_compat;
+
+mod dwarf_expr;
+
+mod dyld_cache;
+
 mod errors;
 use errors::Error;
 
+mod file_contents;
+use file_contents::FileContents;
+
+mod format;
+use format::{FormatOptions, HeaderScaffold, OutputFormat};
+
+mod go;
+
+mod inspect;
+
+mod kernel;
+
+mod layout;
+use layout::{
+    Bitfield, ClassIndex, ClassKind, ClassLayout, Enumerator, Language, MemberLayout, SizeKind,
+    SortKey, TypeKind, VariantLayout, VariantPart,
+};
+
+mod name_index;
+use name_index::NameLookup;
+
+mod pdb;
+use pdb::Pdb;
+
+mod process;
+use process::ProcessMemory;
+
+mod objc;
+
+mod odr_check;
+
 mod relocation_map;
 use relocation_map::*;
 
+mod resolve;
+
+mod scan;
+
+mod search_filter;
+use search_filter::SearchFilter;
+
+mod stdlib;
+
+mod type_index;
+use type_index::TypeIndex;
+
 #[derive(clap::Parser)]
 struct CommandLineInterface {
-    #[arg(long = "shared-object", value_name = "PATH")]
-    shared_object_path: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long = "shared-object", value_name = "PATH", global = true)]
+    shared_object_paths: Vec<PathBuf>,
+
+    #[arg(long = "scan", value_name = "DIR", global = true)]
+    scan_dirs: Vec<PathBuf>,
+
+    #[arg(long = "with-deps", global = true)]
+    with_deps: bool,
+
+    #[arg(long = "kernel", global = true)]
+    kernel: bool,
+
+    #[arg(
+        long = "pid",
+        value_name = "PID",
+        global = true,
+        group = "memory_source"
+    )]
+    pid: Option<u32>,
 
     #[arg(long = "name")]
-    class_name: Option<String>,
+    class_names: Vec<String>,
 
     #[arg(long = "base-class")]
-    base_class_name: Option<String>,
+    base_class_names: Vec<String>,
+
+    #[arg(long = "base-class-recursive", requires = "base_class_names")]
+    base_class_recursive: bool,
 
     #[arg(long = "contains")]
-    contained_class_name: Option<String>,
+    contained_class_names: Vec<String>,
+
+    #[arg(long = "contains-recursive", requires = "contained_class_names")]
+    contains_recursive: bool,
+
+    #[arg(long = "contains-pointers", requires = "contained_class_names")]
+    contains_pointers: bool,
+
+    #[arg(long = "member-name")]
+    member_names: Vec<String>,
+
+    #[arg(long = "member-at-offset", value_name = "OFFSET", value_parser = parse_integer)]
+    member_offset: Option<usize>,
+
+    #[arg(
+        long = "member-size",
+        value_name = "BYTES",
+        value_parser = parse_integer,
+        requires = "member_offset"
+    )]
+    member_size: Option<usize>,
+
+    #[arg(long = "namespace", value_name = "NS")]
+    namespaces: Vec<String>,
+
+    #[arg(long = "cu", value_name = "PATTERN")]
+    unit_patterns: Vec<String>,
+
+    #[arg(long = "decl-file", value_name = "PATTERN")]
+    decl_file_patterns: Vec<String>,
+
+    #[arg(long = "declared-in", value_name = "GLOB")]
+    declared_in: Vec<String>,
+
+    #[arg(long = "size", value_name = "BYTES", value_parser = parse_integer)]
+    size: Option<usize>,
+
+    #[arg(long = "min-size", value_name = "BYTES", value_parser = parse_integer)]
+    min_size: Option<usize>,
+
+    #[arg(long = "max-size", value_name = "BYTES", value_parser = parse_integer)]
+    max_size: Option<usize>,
+
+    #[arg(long = "min-padding", value_name = "BYTES", value_parser = parse_integer)]
+    min_padding: Option<usize>,
+
+    #[arg(long = "has-vtable")]
+    has_vtable: bool,
+
+    #[arg(long = "linkage-name", value_name = "SYMBOL")]
+    linkage_names: Vec<String>,
+
+    #[arg(long = "exclude", value_name = "PATTERN")]
+    exclude_patterns: Vec<String>,
+
+    #[arg(long = "ignore-case")]
+    ignore_case: bool,
+
+    #[arg(long = "sort", value_enum)]
+    sort: Option<SortKey>,
+
+    #[arg(long = "descending", requires = "sort")]
+    descending: bool,
+
+    #[arg(long = "jobs", short = 'j', value_name = "N", global = true)]
+    jobs: Option<usize>,
+
+    #[arg(long = "no-mmap", global = true)]
+    no_mmap: bool,
+
+    #[arg(long = "debug-root", value_name = "DIR", global = true)]
+    debug_root: Option<PathBuf>,
+
+    #[arg(long = "debug-file", value_name = "PATH", global = true)]
+    debug_file: Option<PathBuf>,
+
+    #[arg(long = "debug-dir", value_name = "DIR", global = true)]
+    debug_dirs: Vec<PathBuf>,
+
+    #[arg(long = "ignore-debuglink-crc", global = true)]
+    ignore_debuglink_crc: bool,
+
+    #[arg(long = "sysroot", value_name = "DIR", global = true)]
+    sysroot: Option<PathBuf>,
+
+    #[arg(long = "dsym", value_name = "PATH", global = true)]
+    dsym: Option<PathBuf>,
+
+    #[arg(long = "btf-base", value_name = "PATH", global = true)]
+    btf_base: Option<PathBuf>,
+
+    #[arg(long = "dyld-image", value_name = "IMAGE", global = true)]
+    dyld_image: Option<String>,
+
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    #[arg(long = "list", conflicts_with = "format")]
+    list: bool,
+
+    #[arg(long = "sizes-only", conflicts_with_all = ["format", "list"])]
+    sizes_only: bool,
+
+    #[arg(long = "with-size")]
+    with_size: bool,
+
+    #[arg(long = "macro-prefix", default_value = "")]
+    macro_prefix: String,
+
+    #[arg(long = "show-cu")]
+    show_cu: bool,
+
+    #[arg(long = "show-decl")]
+    show_decl: bool,
+
+    #[arg(long = "show-member-decl", requires = "show_decl")]
+    show_member_decl: bool,
+
+    #[arg(long = "include-guard", value_name = "MACRO")]
+    include_guard: Option<String>,
+
+    #[arg(long = "header-prefix", value_name = "FILE")]
+    header_prefix: Option<PathBuf>,
+
+    #[arg(long = "expand-depth", value_name = "N", default_value_t = 0)]
+    expand_depth: usize,
+
+    #[arg(long = "recursive")]
+    recursive: bool,
+
+    #[arg(long = "recursive-pointers", requires = "recursive")]
+    recursive_pointers: bool,
+
+    #[arg(long = "collapse-templates")]
+    collapse_templates: bool,
+
+    #[arg(
+        long = "core",
+        value_name = "PATH",
+        requires = "address",
+        group = "memory_source"
+    )]
+    core_path: Option<PathBuf>,
+
+    #[arg(
+        long = "at",
+        value_name = "ADDRESS",
+        value_parser = parse_integer,
+        requires = "memory_source"
+    )]
+    address: Option<usize>,
+}
+
+// Actions other than printing the matching classes.  Not a doc
+// comment, since clap would use it as the description of the program
+// in `--help`.
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Find the member at each offset listed in a file.  Each line
+    /// of the file should contain a class name and an offset, such
+    /// as `Npc 0x48`.
+    Resolve {
+        /// The file of offsets to resolve.  If absent, offsets are
+        /// read from stdin.
+        path: Option<PathBuf>,
+    },
+
+    /// Report classes whose definition differs between compilation
+    /// units, violating the One Definition Rule.
+    OdrCheck,
+
+    /// Write an index of the classes in the binary to a cache
+    /// directory, keyed by the binary's build-id.  Later queries
+    /// against the same binary use the index to find the requested
+    /// classes without reading every compilation unit.
+    Index,
+
+    /// Print the names of the classes that match the search options,
+    /// as for `--list`, using the index written by `index` instead of
+    /// reading the debug information.  If the binary has not been
+    /// indexed, it is read in full and its index is written for later
+    /// queries.
+    Query,
+
+    /// Print a hexdump of a file of raw bytes, such as an object
+    /// carved from a memory dump, with each range of bytes labeled by
+    /// the member of the class that holds it and the member's value.
+    Overlay {
+        /// The class whose layout is overlaid on the bytes.
+        class_name: String,
+
+        /// The file of raw bytes, which starts with the first byte of
+        /// the object.
+        path: PathBuf,
+    },
+
+    /// Compare two binaries, printing the classes whose size or
+    /// member layout changed, along with classes that were added or
+    /// removed.  Search options select which classes are compared.
+    Diff {
+        /// The binary before the change.
+        old: PathBuf,
+
+        /// The binary after the change.
+        new: PathBuf,
+    },
+
+    /// Compare two binaries as for `diff`, classifying each change as
+    /// breaking or compatible.  Exits with a non-zero status if any
+    /// change is breaking.
+    AbiCheck {
+        /// The binary that existing code was compiled against.
+        old: PathBuf,
+
+        /// The binary that existing code will be run against.
+        new: PathBuf,
+    },
+}
+
+/// Everything collected from the command line that determines how
+/// the classes found in the input are processed.
+struct Options {
+    command: Option<Command>,
+    search_filter: SearchFilter,
+    sort: Option<SortKey>,
+    descending: bool,
+    format: OutputFormat,
+    format_options: FormatOptions,
+    load_options: LoadOptions,
+
+    /// If set, the core dump whose memory is read by `--at`.
+    core_path: Option<PathBuf>,
+
+    /// If set, the running process whose memory is read by `--at`.
+    pid: Option<u32>,
+
+    /// If set, the address of an object whose member values are
+    /// printed, instead of printing the class definitions.
+    address: Option<u64>,
+
+    /// Whether the inputs include the libraries they depend on, whose
+    /// definitions complete the classes that are only declared in the
+    /// binaries that use them.
+    with_deps: bool,
+
+    /// The number of levels of embedded members that are replaced by
+    /// the members of their own class, as described in
+    /// `ClassIndex::expand_members`.
+    expand_depth: usize,
+
+    /// Whether the classes that the matching classes depend on are
+    /// printed along with them, so that generated code is
+    /// self-contained.
+    recursive: bool,
+
+    /// Whether the dependencies printed by `recursive` include the
+    /// classes that are only reached through a pointer.
+    recursive_pointers: bool,
+
+    /// Whether the instantiations of each template are printed as a
+    /// single class, followed by a list of the others.
+    collapse_templates: bool,
+
+    /// The inputs that were found by `--scan`.  A scanned binary that
+    /// can't be read is skipped with a warning, rather than ending
+    /// the search, and classes always record which binary they were
+    /// found in, even if only one binary was found.
+    scanned_inputs: Vec<PathBuf>,
+}
+
+/// Options that control how the input files are read.
+struct LoadOptions {
+    /// The number of threads used to read compilation units.
+    jobs: usize,
+
+    /// Whether input files are memory-mapped, rather than read into
+    /// memory.
+    use_mmap: bool,
+
+    /// The directory under which separate debug files are installed
+    /// by build-id, as `.build-id/xx/yyyy.debug`.  Defaults to
+    /// `/usr/lib/debug` within the sysroot.
+    debug_root: PathBuf,
+
+    /// If set, the root directory of the target's filesystem, such as
+    /// an embedded board's image, within which the absolute paths
+    /// recorded in the input files are resolved.
+    sysroot: Option<PathBuf>,
+
+    /// If set, the separate debug file to use, instead of searching
+    /// for one.
+    debug_file: Option<PathBuf>,
+
+    /// Directories searched for the file named by `.gnu_debuglink`,
+    /// in addition to the directory of the object file.
+    debug_dirs: Vec<PathBuf>,
+
+    /// Whether the file named by `.gnu_debuglink` is used even if its
+    /// CRC doesn't match the one recorded in the object file.
+    ignore_debuglink_crc: bool,
+
+    /// If set, the `.dSYM` bundle, or the DWARF file within it, to use
+    /// for Mach-O objects instead of searching for one.
+    dsym: Option<PathBuf>,
+
+    /// If set, the BTF that the BTF of each input extends, instead of
+    /// the running kernel's BTF for kernel modules.
+    btf_base: Option<PathBuf>,
+
+    /// If set, the image to read from inputs that are dyld shared
+    /// caches, given by either its install path or its file name.
+    dyld_image: Option<String>,
+
+    /// If non-empty, only classes with one of these names are needed,
+    /// and may be found through the binary's accelerator tables
+    /// instead of reading every compilation unit.
+    lookup_names: Vec<String>,
+}
+
+impl LoadOptions {
+    /// Returns the host path of a path on the target's filesystem.
+    /// Absolute paths are moved within the sysroot, if any, while
+    /// relative paths are unchanged.  Symbolic links within the
+    /// sysroot are followed as on the target, so that a link to an
+    /// absolute path also stays within the sysroot.
+    fn target_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        match (&self.sysroot, path.strip_prefix("/")) {
+            (Some(sysroot), Ok(relative)) => sysroot.join(resolve_within(sysroot, relative)),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+/// Follow the symbolic links in `path`, which is relative to `root`,
+/// as if `root` were the root directory, so that neither absolute
+/// links nor `..` leave it.  Returns the path with the links
+/// replaced, relative to `root`.  Components that don't exist are kept
+/// as they are, and links are no longer followed after 40 of them, as
+/// for the kernel's limit on nested links.
+fn resolve_within(root: &Path, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    let mut pending: Vec<std::ffi::OsString> = path
+        .components()
+        .rev()
+        .map(|component| component.as_os_str().to_owned())
+        .collect();
+    let mut num_links = 0;
+    while let Some(component) = pending.pop() {
+        match Path::new(&component).components().next() {
+            Some(std::path::Component::RootDir) => resolved.clear(),
+            Some(std::path::Component::ParentDir) => {
+                resolved.pop();
+            }
+            Some(std::path::Component::Normal(name)) => {
+                let candidate = resolved.join(name);
+                let link = std::fs::read_link(root.join(&candidate))
+                    .ok()
+                    .filter(|_| num_links < 40);
+                match link {
+                    Some(target) => {
+                        num_links += 1;
+                        pending.extend(
+                            target
+                                .components()
+                                .rev()
+                                .map(|component| component.as_os_str().to_owned()),
+                        );
+                    }
+                    None => resolved = candidate,
+                }
+            }
+            _ => {}
+        }
+    }
+    resolved
 }
 
-/// Represents the user's search options, as specified on the command
-/// line.
-struct SearchFilter {
-    /// If present, only print classes whose name matches the
-    /// `class_name`.
-    class_name: Option<String>,
+/// Returns the directory in which generated and downloaded files are
+/// cached, `$XDG_CACHE_HOME`, defaulting to `~/.cache`.
+fn cache_home() -> Result<PathBuf, Error> {
+    match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => {
+            let home_dir = std::env::var_os("HOME").ok_or(Error::NoHomeDirectoryFound)?;
+            Ok(PathBuf::from(home_dir).join(".cache"))
+        }
+    }
+}
 
-    /// If present, only print classes that inherit from a class whose
-    /// name matches the `base_class_name`.
-    base_class_name: Option<String>,
+/// Format bytes, such as a build-id, as lowercase hexadecimal.
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
 
-    /// If present, only print classes that contain at least one
-    /// member whose name matched the `contained_class_name`.
-    contained_class_name: Option<String>,
+/// Parse an integer given on the command line, either as decimal or
+/// as hexadecimal with a `0x` prefix.  Offsets are usually copied from
+/// a disassembler, and so are typically in hexadecimal.
+fn parse_integer(arg: &str) -> Result<usize, std::num::ParseIntError> {
+    if let Some(hex) = arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+        usize::from_str_radix(hex, 16)
+    } else {
+        arg.parse()
+    }
 }
 
 /// The compilation units found.  Since a DIE may refer to symbols at
 /// an arbitrary location in the .debug_info section, storing all
 /// headers allows them to be inspected without re-parsing through
 /// `gimli::Dwarf::units()`
+///
+/// If the debug information has been processed by `dwz`, the entries
+/// shared between binaries are held in a supplementary file, whose
+/// units are listed separately in `sup_units`.  These units are
+/// processed after those of the main file, as if the supplementary
+/// .debug_info section followed the main one, and offsets within it
+/// are reported in the same way.
 struct DwarfUnits<'a, R: Reader> {
     dwarf: &'a Dwarf<R>,
-    units: Vec<Unit<R>>,
+    units: Vec<LazyUnit<R>>,
+    sup_units: Vec<LazyUnit<R>>,
+    memo_lru: std::sync::Arc<MemoLru<R>>,
+}
+
+/// A compilation unit whose header has been read, but whose
+/// abbreviations, line program, and other per-unit state are only
+/// parsed when one of its entries is needed.
+///
+/// Each unit is parsed while it is being processed, and dropped
+/// afterwards, so that only a few units are held in memory at once.
+/// Units that entries of the processed unit refer to are held in the
+/// worker's `KeptUnits`, and are dropped along with it.
+struct LazyUnit<R: Reader> {
+    header: gimli::UnitHeader<R>,
+
+    /// Attributes already decoded from the entries of the unit, when
+    /// it is referenced from another unit.  These are kept while the
+    /// unit is among the most recently referenced, as tracked by
+    /// `MemoLru`, since the same types are referenced from many units.
+    memo: std::sync::Arc<Memo<R>>,
+}
+
+/// The most units whose memoized attributes are kept by `MemoLru`.
+const MAX_MEMOIZED_UNITS: usize = 256;
+
+/// Bounds the memory held by the memoized attributes of referenced
+/// units.  Once more than `MAX_MEMOIZED_UNITS` units have been
+/// referenced, the memo of the least recently referenced unit is
+/// cleared.
+struct MemoLru<R: Reader> {
+    /// The memos of referenced units, keyed by the unit's position in
+    /// `DwarfUnits::all_units`, from least to most recently referenced.
+    memos: std::sync::Mutex<std::collections::VecDeque<(usize, std::sync::Arc<Memo<R>>)>>,
+}
+
+/// The units referenced from the unit that a worker of
+/// `DwarfUnits::par_for_each` is processing.  The `ContextEntry` for a
+/// referenced entry borrows its unit for as long as the referring
+/// entry is in use, so the units are kept until the referring unit
+/// has been processed, and are then dropped by `clear`.
+struct KeptUnits<R: Reader> {
+    /// The parsed units, numbered as in `DwarfUnits::all_units`, or
+    /// `None` for units that could not be parsed.
+    units: Vec<std::sync::OnceLock<Option<Unit<R>>>>,
+
+    /// The indices within `units` of the units that have been parsed.
+    loaded: std::cell::RefCell<Vec<usize>>,
+
+    /// Records each parsed unit as referenced, shared by all workers.
+    memo_lru: std::sync::Arc<MemoLru<R>>,
+}
+
+/// The results of attribute lookups on the entries of a compilation
+/// unit, keyed by the offset of the entry within the unit.  Type
+/// entries are shared by every member and class that uses the type,
+/// so the same lookup is often repeated many times in a single run.
+struct Memo<R: Reader> {
+    names: MemoTable<R, Option<String>>,
+    sizes: MemoTable<R, Option<usize>>,
+
+    /// The location of the entry's `DW_AT_type`.
+    classes: MemoTable<R, Option<EntryLocation<R::Offset>>>,
+
+    /// The source language of the unit, from its root entry.
+    language: std::sync::OnceLock<Option<gimli::DwLang>>,
+
+    /// The path of each file in the unit's line program, keyed by the
+    /// index used by `DW_AT_decl_file`.  Shared by every entry
+    /// declared in the same file.
+    decl_files: std::sync::Mutex<HashMap<u64, Option<std::sync::Arc<str>>>>,
+}
+
+/// Memoized values of a single attribute lookup, keyed by the offset
+/// of the entry within its compilation unit.
+type MemoTable<R, T> = std::sync::Mutex<HashMap<<R as Reader>::Offset, T>>;
+
+/// The location of an entry, relative to the entry that refers to it.
+#[derive(Clone, Copy)]
+enum EntryLocation<Offset: gimli::ReaderOffset> {
+    /// An offset within the same unit as the referring entry.
+    Local(gimli::UnitOffset<Offset>),
+
+    /// An offset within another unit of the same file, given by its
+    /// index in `ContextEntry::units`.
+    Other(usize, gimli::UnitOffset<Offset>),
+
+    /// An offset within a unit of the supplementary file, given by
+    /// its index in `ContextEntry::sup_units`.
+    Sup(usize, gimli::UnitOffset<Offset>),
+}
+
+/// Deduplicates the names of member types, which are repeated for
+/// every member of the same type.  Each distinct name is allocated
+/// once, and shared by all members of that type.  The names are split
+/// by hash into shards with a lock of their own, so that the threads
+/// reading compilation units rarely wait for each other.
+#[derive(Default)]
+struct Interner {
+    hasher: std::hash::RandomState,
+    shards: [std::sync::Mutex<HashSet<std::sync::Arc<str>>>; 16],
+}
+
+impl Interner {
+    /// Returns the shared copy of `string`, adding it if this is the
+    /// first use.
+    fn intern(&self, string: &str) -> std::sync::Arc<str> {
+        let hash = std::hash::BuildHasher::hash_one(&self.hasher, string);
+        let shard = &self.shards[hash as usize % self.shards.len()];
+        let mut strings = shard.lock().unwrap();
+        if let Some(interned) = strings.get(string) {
+            return interned.clone();
+        }
+        let interned: std::sync::Arc<str> = string.into();
+        strings.insert(interned.clone());
+        interned
+    }
+}
+
+impl<R: Reader> Default for Memo<R> {
+    fn default() -> Self {
+        Self {
+            names: Default::default(),
+            sizes: Default::default(),
+            classes: Default::default(),
+            language: Default::default(),
+            decl_files: Default::default(),
+        }
+    }
+}
+
+impl<R: Reader> Memo<R> {
+    /// Forget the memoized values.  The source language is kept,
+    /// since it is a single value.
+    fn clear(&self) {
+        self.names.lock().unwrap().clear();
+        self.sizes.lock().unwrap().clear();
+        self.classes.lock().unwrap().clear();
+        self.decl_files.lock().unwrap().clear();
+    }
+
+    /// Returns the memoized value for the entry at `offset`,
+    /// computing it if this is the first lookup.  The lock is not
+    /// held while computing the value, since computing a name may
+    /// look up the names of other entries in the same unit.
+    fn get_or_insert<T: Clone>(
+        map: &MemoTable<R, T>,
+        offset: gimli::UnitOffset<R::Offset>,
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        if let Some(value) = map.lock().unwrap().get(&offset.0) {
+            return value.clone();
+        }
+        let value = compute();
+        map.lock().unwrap().insert(offset.0, value.clone());
+        value
+    }
 }
 
 /// Handles into a specific compilation unit.  Similar to the
@@ -59,8 +704,13 @@ struct DwarfUnits<'a, R: Reader> {
 #[derive(Clone, Copy)]
 struct DwarfUnit<'a, R: Reader> {
     dwarf: &'a Dwarf<R>,
-    units: &'a [Unit<R>],
+    units: &'a [LazyUnit<R>],
+    sup_units: &'a [LazyUnit<R>],
+    offset_base: u64,
+    unit_base: usize,
+    kept: &'a KeptUnits<R>,
     unit: &'a Unit<R>,
+    memo: &'a Memo<R>,
 }
 
 /// Represents a single DWARF Debugging Information Entry (DIE), along
@@ -73,13 +723,34 @@ struct ContextEntry<'a, R: Reader> {
 
     /// The compilation units contained in the Dwarf unpacker.  Used
     /// to expand references that point relative to .debug_info.
-    units: &'a [Unit<R>],
+    units: &'a [LazyUnit<R>],
+
+    /// The compilation units of the supplementary file, if the entry
+    /// is in the main file.  Used to expand references into the
+    /// supplementary .debug_info.
+    sup_units: &'a [LazyUnit<R>],
+
+    /// Added to offsets within the entry's .debug_info section, so
+    /// that entries in the supplementary file have offsets after
+    /// those of the main file.  Zero for entries in the main file.
+    offset_base: u64,
+
+    /// The position within `DwarfUnits::all_units` of the first of
+    /// `units`.  Zero for entries in the main file.
+    unit_base: usize,
+
+    /// The units that have been parsed for references from the unit
+    /// being processed.
+    kept: &'a KeptUnits<R>,
 
     /// The compilation unit that contains the entry.  Used to expand
     /// references that point relative to the current compilation
     /// unit.
     unit: &'a Unit<R>,
 
+    /// The memoized attributes of entries in the compilation unit.
+    memo: &'a Memo<R>,
+
     /// The entry itself.
     entry: gimli::DebuggingInformationEntry<'a, 'a, R>,
 }
@@ -88,17 +759,285 @@ impl<'a, R: Reader> DwarfUnits<'a, R> {
     /// Construct a new instance.  Propagates any errors that result
     /// from unpacking the DWARF headers.
     fn new(dwarf: &'a Dwarf<R>) -> Result<Self, gimli::Error> {
-        let units = dwarf.units().map(|header| dwarf.unit(header)).collect()?;
-        Ok(Self { dwarf, units })
+        let read_units = |dwarf: &Dwarf<R>| -> Result<Vec<_>, gimli::Error> {
+            let mut units = dwarf
+                .units()
+                .map(|header| {
+                    Ok(LazyUnit {
+                        header,
+                        memo: Default::default(),
+                    })
+                })
+                .collect::<Vec<_>>()?;
+            units.sort_by_key(|lazy_unit| lazy_unit.start());
+            Ok(units)
+        };
+        Ok(Self {
+            dwarf,
+            units: read_units(dwarf)?,
+            sup_units: dwarf.sup().map(read_units).transpose()?.unwrap_or_default(),
+            memo_lru: Default::default(),
+        })
     }
 
-    /// Iterate over all compilation units.
-    fn iter<'b>(&'b self) -> impl Iterator<Item = DwarfUnit<'b, R>> + 'b {
-        self.units.iter().map(move |unit| DwarfUnit {
-            dwarf: &self.dwarf,
-            units: &self.units,
-            unit,
-        })
+    /// Iterate over the units of the main file, followed by those of
+    /// the supplementary file.  Each unit is returned with the offset
+    /// added to positions within its file, as for
+    /// `ContextEntry::offset_base`.  Units are identified by their
+    /// position in this sequence.
+    fn all_units(&self) -> impl Iterator<Item = (&LazyUnit<R>, u64)> + '_ {
+        let sup_base = sup_offset_base(self.dwarf);
+        self.units.iter().map(|lazy_unit| (lazy_unit, 0)).chain(
+            self.sup_units
+                .iter()
+                .map(move |lazy_unit| (lazy_unit, sup_base)),
+        )
+    }
+
+    /// Find the position within `all_units` of the unit containing an
+    /// offset, where offsets within the supplementary file follow
+    /// those of the main file.
+    fn find_index(&self, offset: u64) -> Option<usize> {
+        let sup_base = sup_offset_base(self.dwarf);
+        if offset < sup_base || self.sup_units.is_empty() {
+            let offset = R::Offset::from_u64(offset).ok()?;
+            LazyUnit::find_index(&self.units, gimli::DebugInfoOffset(offset))
+        } else {
+            let offset = R::Offset::from_u64(offset - sup_base).ok()?;
+            LazyUnit::find_index(&self.sup_units, gimli::DebugInfoOffset(offset))
+                .map(|i| self.units.len() + i)
+        }
+    }
+
+    /// Apply `func` to every compilation unit, using up to `jobs`
+    /// threads, and concatenate the results.  The results are in the
+    /// order of the compilation units, as for `par_for_each`.
+    fn par_flat_map<T, F>(&self, jobs: usize, selected: Option<&HashSet<usize>>, func: F) -> Vec<T>
+    where
+        R: Send + Sync,
+        R::Offset: Send + Sync,
+        T: Send,
+        F: Fn(DwarfUnit<'_, R>) -> Vec<T> + Sync,
+    {
+        let mut results = Vec::new();
+        self.par_for_each(jobs, selected, func, |unit_results| {
+            results.extend(unit_results)
+        });
+        results
+    }
+
+    /// Apply `func` to every compilation unit, using up to `jobs`
+    /// threads, and pass the results of each unit to `emit`.  Each
+    /// thread takes the next unprocessed unit when it finishes the
+    /// previous one, so that a few large units don't leave the other
+    /// threads idle.  The results are emitted in the order of the
+    /// compilation units, regardless of which thread processed each
+    /// unit, as soon as all earlier units have been processed.  If
+    /// `selected` is provided, only the units at those indices are
+    /// processed, and the other units are never parsed.  Units are
+    /// numbered as in `all_units`.  Each unit, and the units that its
+    /// entries refer to, are dropped once `func` returns.
+    fn par_for_each<T, F, E>(
+        &self,
+        jobs: usize,
+        selected: Option<&HashSet<usize>>,
+        func: F,
+        mut emit: E,
+    ) where
+        R: Send + Sync,
+        R::Offset: Send + Sync,
+        T: Send,
+        F: Fn(DwarfUnit<'_, R>) -> Vec<T> + Sync,
+        E: FnMut(Vec<T>),
+    {
+        let num_units = self.units.len() + self.sup_units.len();
+        let next_unit = std::sync::atomic::AtomicUsize::new(0);
+        let worker = |sender: std::sync::mpsc::Sender<(usize, Vec<T>)>| {
+            let mut kept = KeptUnits::new(num_units, self.memo_lru.clone());
+            loop {
+                let i = next_unit.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let results = self.process_unit(i, selected, &kept, &func);
+                kept.clear();
+                let Some(results) = results else {
+                    return;
+                };
+                if sender.send((i, results)).is_err() {
+                    return;
+                }
+            }
+        };
+
+        let jobs = jobs.clamp(1, num_units.max(1));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::scope(|scope| {
+            let worker = &worker;
+            for _ in 0..jobs {
+                let sender = sender.clone();
+                scope.spawn(move || worker(sender));
+            }
+            drop(sender);
+
+            let mut finished = std::collections::BTreeMap::new();
+            let mut next_to_emit = 0;
+            for (i, results) in receiver {
+                finished.insert(i, results);
+                while let Some(results) = finished.remove(&next_to_emit) {
+                    emit(results);
+                    next_to_emit += 1;
+                }
+            }
+        });
+    }
+    /// Apply `func` to the unit at position `i` within `all_units`,
+    /// as for `par_for_each`, with the units that it refers to parsed
+    /// into `kept`.  Returns `None` if there is no such unit.
+    fn process_unit<T>(
+        &self,
+        i: usize,
+        selected: Option<&HashSet<usize>>,
+        kept: &KeptUnits<R>,
+        func: &impl Fn(DwarfUnit<'_, R>) -> Vec<T>,
+    ) -> Option<Vec<T>> {
+        let (dwarf, units, sup_units, offset_base, unit_base) = if i < self.units.len() {
+            (self.dwarf, &self.units[..], &self.sup_units[..], 0, 0)
+        } else if let Some(sup) = self.dwarf.sup() {
+            (
+                sup,
+                &self.sup_units[..],
+                &[][..],
+                sup_offset_base(self.dwarf),
+                self.units.len(),
+            )
+        } else {
+            return None;
+        };
+        let lazy_unit = self
+            .units
+            .get(i)
+            .or_else(|| self.sup_units.get(i - self.units.len()))?;
+        // Units that are skipped still produce an empty result, so
+        // that the results of later units aren't held back.
+        if selected.is_some_and(|selected| !selected.contains(&i)) {
+            return Some(Vec::new());
+        }
+        let results = lazy_unit
+            .parse(dwarf)
+            .map(|unit| {
+                func(DwarfUnit {
+                    dwarf,
+                    units,
+                    sup_units,
+                    offset_base,
+                    unit_base,
+                    kept,
+                    unit: &unit,
+                    memo: &Memo::default(),
+                })
+            })
+            .unwrap_or_default();
+        Some(results)
+    }
+}
+
+/// Returns the offset added to positions within the supplementary
+/// file, which is the size of the main .debug_info section.
+fn sup_offset_base<R: Reader>(dwarf: &Dwarf<R>) -> u64 {
+    gimli::Section::reader(&dwarf.debug_info).len().into_u64()
+}
+
+impl<R: Reader> LazyUnit<R> {
+    /// The offset of the unit within .debug_info, used to order the
+    /// units.
+    fn start(&self) -> Option<R::Offset> {
+        self.header
+            .offset()
+            .as_debug_info_offset()
+            .map(|offset| offset.0)
+    }
+
+    /// Find the index of the unit containing an offset into
+    /// .debug_info, which is the last unit starting at or before the
+    /// offset.  The `units` must be sorted by their start, as done by
+    /// `DwarfUnits::new`, so that the unit can be found by binary
+    /// search.
+    fn find_index(units: &[Self], offset: gimli::DebugInfoOffset<R::Offset>) -> Option<usize> {
+        let num_before = units
+            .partition_point(|lazy_unit| lazy_unit.start().is_some_and(|start| start <= offset.0));
+        num_before.checked_sub(1)
+    }
+
+    /// Parse the unit, without keeping it.  Units that cannot be
+    /// parsed are reported, and then skipped.
+    fn parse(&self, dwarf: &Dwarf<R>) -> Option<Unit<R>> {
+        match dwarf.unit(self.header.clone()) {
+            Ok(unit) => Some(unit),
+            Err(err) => {
+                eprintln!(
+                    "warning: skipping compilation unit at {:?}: {err}",
+                    self.header.offset()
+                );
+                None
+            }
+        }
+    }
+}
+
+impl<R: Reader> Default for MemoLru<R> {
+    fn default() -> Self {
+        Self {
+            memos: Default::default(),
+        }
+    }
+}
+
+impl<R: Reader> MemoLru<R> {
+    /// Record that the unit at position `i` within `all_units` has
+    /// been referenced, clearing the memo of the least recently
+    /// referenced unit if there are too many.
+    fn touch(&self, i: usize, memo: &std::sync::Arc<Memo<R>>) {
+        let mut memos = self.memos.lock().unwrap();
+        match memos.iter().position(|(unit, _)| *unit == i) {
+            Some(position) => {
+                let entry = memos.remove(position).unwrap();
+                memos.push_back(entry);
+            }
+            None => memos.push_back((i, memo.clone())),
+        }
+        if memos.len() > MAX_MEMOIZED_UNITS {
+            if let Some((_, evicted)) = memos.pop_front() {
+                evicted.clear();
+            }
+        }
+    }
+}
+
+impl<R: Reader> KeptUnits<R> {
+    fn new(num_units: usize, memo_lru: std::sync::Arc<MemoLru<R>>) -> Self {
+        Self {
+            units: (0..num_units).map(|_| std::sync::OnceLock::new()).collect(),
+            loaded: Default::default(),
+            memo_lru,
+        }
+    }
+
+    /// Returns the unit at position `i` within `all_units`, parsing it
+    /// if this is the first use since the last `clear`.  Units that
+    /// cannot be parsed are reported, and then skipped.
+    fn load(&self, i: usize, lazy_unit: &LazyUnit<R>, dwarf: &Dwarf<R>) -> Option<&Unit<R>> {
+        self.units[i]
+            .get_or_init(|| {
+                self.loaded.borrow_mut().push(i);
+                self.memo_lru.touch(i, &lazy_unit.memo);
+                lazy_unit.parse(dwarf)
+            })
+            .as_ref()
+    }
+
+    /// Drop the units that have been parsed.
+    fn clear(&mut self) {
+        for i in self.loaded.get_mut().drain(..) {
+            self.units[i].take();
+        }
     }
 }
 
@@ -114,10 +1053,24 @@ impl<'a, R: Reader> DwarfUnit<'a, R> {
         iter_raw_entry.map(move |entry| ContextEntry {
             dwarf: self.dwarf,
             units: self.units,
+            sup_units: self.sup_units,
+            offset_base: self.offset_base,
+            unit_base: self.unit_base,
+            kept: self.kept,
             unit: self.unit,
+            memo: self.memo,
             entry,
         })
     }
+
+    /// Iterate over the entries of the compilation unit, descending
+    /// into namespaces.  Each entry is returned along with the
+    /// `::`-separated name of its enclosing namespace, which is empty
+    /// for entries at global scope.
+    fn iter_namespaced(self) -> impl Iterator<Item = (String, ContextEntry<'a, R>)> + 'a {
+        self.iter()
+            .flat_map(|entry| entry.flatten_namespaces(String::new()))
+    }
 }
 
 impl<'a, R: Reader> ContextEntry<'a, R> {
@@ -133,64 +1086,240 @@ impl<'a, R: Reader> ContextEntry<'a, R> {
         iter_raw_entry.map(|entry| Self { entry, ..*self })
     }
 
+    /// If the entry is a namespace, returns the entries within it,
+    /// recursively descending into nested namespaces.  Otherwise,
+    /// returns the entry itself.  Fortran modules are treated as
+    /// namespaces, and since Fortran declares derived types and
+    /// COMMON blocks within the procedures that use them, the entries
+    /// within Fortran procedures are returned in their place.
+    fn flatten_namespaces(self, namespace: String) -> Vec<(String, Self)> {
+        let is_fortran_scope =
+            matches!(self.tag(), gimli::DW_TAG_module | gimli::DW_TAG_subprogram)
+                && self.is_fortran();
+        if self.tag() == gimli::DW_TAG_subprogram && is_fortran_scope {
+            return self
+                .iter_children()
+                .flat_map(|child| child.flatten_namespaces(namespace.clone()))
+                .collect();
+        }
+        if self.tag() != gimli::DW_TAG_namespace && !is_fortran_scope {
+            return vec![(namespace, self)];
+        }
+
+        let name = self
+            .name()
+            .unwrap_or_else(|| "(anonymous namespace)".into());
+        let inner = if namespace.is_empty() {
+            name
+        } else {
+            format!("{namespace}::{name}")
+        };
+        self.iter_children()
+            .flat_map(|child| child.flatten_namespaces(inner.clone()))
+            .collect()
+    }
+
     /// Returns the DWARF tag of the entry.
     fn tag(&self) -> gimli::DwTag {
         self.entry.tag()
     }
 
-    fn iter_base_classes(&self) -> impl Iterator<Item = Self> + '_ {
-        debug_assert!(
-            self.tag() == gimli::DW_TAG_class_type,
-            "Iterating over base classes \
-             should only occur for type definitions (DW_TAG_class_type), \
-             but was used for an entry with tag {}.",
-            self.tag(),
-        );
-        self.iter_children()
-            .filter(|entry| entry.tag() == gimli::DW_TAG_inheritance)
-            .filter_map(|entry| entry.class())
-    }
-
-    fn iter_class_members(&self) -> impl Iterator<Item = Self> + '_ {
-        debug_assert!(
-            self.tag() == gimli::DW_TAG_class_type,
-            "Iterating over class members \
-             should only occur for type definitions (DW_TAG_class_type), \
-             but was used for an entry with tag {}.",
-            self.tag(),
-        );
-        self.iter_children()
-            .filter(|entry| entry.tag() == gimli::DW_TAG_member)
-            .filter(|entry| entry.member_location().is_some())
-    }
-
-    /// Returns the size of the class described.
-    fn size_bytes(&self) -> Option<usize> {
+    /// Returns the size of the class described.
+    fn size_bytes(&self) -> Option<usize> {
         debug_assert!(
             self.tag() == gimli::DW_TAG_class_type
                 || self.tag() == gimli::DW_TAG_structure_type
+                || self.tag() == gimli::DW_TAG_interface_type
                 || self.tag() == gimli::DW_TAG_union_type
                 || self.tag() == gimli::DW_TAG_enumeration_type
                 || self.tag() == gimli::DW_TAG_base_type
                 || self.tag() == gimli::DW_TAG_inheritance
-                || self.tag() == gimli::DW_TAG_pointer_type,
+                || self.tag() == gimli::DW_TAG_pointer_type
+                || self.tag() == gimli::DW_TAG_reference_type
+                || self.tag() == gimli::DW_TAG_rvalue_reference_type
+                || self.tag() == gimli::DW_TAG_array_type
+                || self.tag() == gimli::DW_TAG_string_type
+                || self.tag() == gimli::DW_TAG_common_block,
             "The size of a class can only be determined \
              should only occur for type definitions \
              (DW_TAG_class_type or DW_TAG_pointer_type), \
              but `entry.size_bytes()` was used for an entry with tag {}.",
             self.tag(),
         );
-        self.entry
-            .attr_value(gimli::DW_AT_byte_size)
+        Memo::<R>::get_or_insert(&self.memo.sizes, self.entry.offset(), || {
+            self.entry
+                .attr_value(gimli::DW_AT_byte_size)
+                .unwrap()
+                .and_then(|attr_value| dwarf_compat::byte_size(attr_value, self.unit.encoding()))
+                .or_else(|| {
+                    matches!(
+                        self.entry.tag(),
+                        gimli::DW_TAG_pointer_type
+                            | gimli::DW_TAG_reference_type
+                            | gimli::DW_TAG_rvalue_reference_type
+                    )
+                    .then(std::mem::size_of::<usize>)
+                })
+                .or_else(|| self.array_size_bytes())
+                .or_else(|| self.size_from_members())
+        })
+    }
+
+    /// Returns how the size of a class is known.  A `DW_AT_byte_size`
+    /// that refers to a variable, or is an expression that reads one,
+    /// gives a size only known at runtime.  A class without a
+    /// `DW_AT_byte_size` has its size inferred from its members.
+    fn size_kind(&self) -> SizeKind {
+        match self.entry.attr_value(gimli::DW_AT_byte_size).unwrap() {
+            Some(attr_value) => match dwarf_compat::byte_size(attr_value, self.unit.encoding()) {
+                Some(_) => SizeKind::Exact,
+                None => SizeKind::Dynamic,
+            },
+            None => SizeKind::Inferred,
+        }
+    }
+
+    /// Returns the size of an array type without a `DW_AT_byte_size`,
+    /// from the size of its elements and the length of each
+    /// dimension.  Fortran arrays whose bounds are only known at
+    /// runtime, such as allocatable and assumed-shape arrays, are
+    /// stored as a descriptor.  Its size is that of the descriptor
+    /// used by gfortran: the base address, offset, two words of type
+    /// information, and span, followed by the stride and bounds of
+    /// each dimension.
+    fn array_size_bytes(&self) -> Option<usize> {
+        if self.tag() != gimli::DW_TAG_array_type {
+            return None;
+        }
+        let dimensions = self.array_dimensions();
+        if self
+            .entry
+            .attr_value(gimli::DW_AT_data_location)
             .unwrap()
-            .map(|attr_value| match attr_value {
-                gimli::AttributeValue::Udata(data) => data as usize,
-                _ => panic!("Invalid AttributeValue for byte size"),
+            .is_some()
+        {
+            let word_size = self.unit.encoding().address_size as usize;
+            return Some((5 + 3 * dimensions.len()) * word_size);
+        }
+        let element_size = self.class()?.expand_type_defs().size_bytes()?;
+        dimensions
+            .iter()
+            .try_fold(element_size, |size, (_, len)| Some(size * (*len)? as usize))
+    }
+
+    /// Returns the lower bound and, if known, the length of each
+    /// dimension of an array type, as given by its subranges.
+    fn array_dimensions(&self) -> Vec<(i64, Option<u64>)> {
+        let default_lower = self.default_lower_bound();
+        self.iter_children()
+            .filter(|child| child.tag() == gimli::DW_TAG_subrange_type)
+            .map(|subrange| {
+                let bound = |attr| {
+                    subrange
+                        .entry
+                        .attr_value(attr)
+                        .unwrap()
+                        .and_then(|value| dwarf_compat::constant(value, self.unit.encoding()))
+                };
+                let lower = bound(gimli::DW_AT_lower_bound).unwrap_or(default_lower);
+                let len = bound(gimli::DW_AT_count)
+                    .or_else(|| Some(bound(gimli::DW_AT_upper_bound)? - lower + 1))
+                    .map(|len| len.max(0) as u64);
+                (lower, len)
+            })
+            .collect()
+    }
+
+    /// Returns the size of a class definition that has no
+    /// `DW_AT_byte_size`, as written by some versions of GCC for
+    /// DWARF 2 and 3, from the end of its last member, rounded up to
+    /// the alignment of its members.  This is a lower bound, since
+    /// the class may have been given a larger alignment.  Returns
+    /// `None` for declarations and for other types.
+    fn size_from_members(&self) -> Option<usize> {
+        if self.tag() == gimli::DW_TAG_common_block {
+            let size = self
+                .common_block_variables()
+                .into_iter()
+                .map(|(offset, variable)| offset + variable.member_size_bytes().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            return Some(size);
+        }
+        let is_aggregate = matches!(
+            self.tag(),
+            gimli::DW_TAG_class_type
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_interface_type
+                | gimli::DW_TAG_union_type
+        );
+        if !is_aggregate || self.is_declaration() {
+            return None;
+        }
+        let end = self
+            .iter_children()
+            .filter(|child| {
+                child.tag() == gimli::DW_TAG_member || child.tag() == gimli::DW_TAG_inheritance
             })
-            .or_else(|| {
-                (self.entry.tag() == gimli::DW_TAG_pointer_type)
-                    .then(|| std::mem::size_of::<usize>())
+            .filter_map(|child| {
+                let offset = child.member_location().unwrap_or(0);
+                let size = child.member_size_bytes().unwrap_or(0);
+                (!child.is_declaration()).then_some(offset + size)
             })
+            .max()
+            .unwrap_or(0);
+        Some(end.next_multiple_of(self.natural_alignment()))
+    }
+
+    /// Returns the alignment of a type, in bytes, as given explicitly
+    /// or, failing that, as the largest alignment of its parts.  A
+    /// primitive type is aligned to its size, or to the power of two
+    /// below it for sizes such as the 12-byte `long double` of i386.
+    /// Packed classes, whose members may be misaligned, are not
+    /// recognized.
+    fn natural_alignment(&self) -> usize {
+        if let Some(alignment) = self.alignment() {
+            return alignment;
+        }
+        match self.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_atomic_type
+            | gimli::DW_TAG_array_type => self.class().map_or(1, |inner| inner.natural_alignment()),
+            gimli::DW_TAG_class_type
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_interface_type
+            | gimli::DW_TAG_union_type => self
+                .iter_children()
+                .filter(|child| {
+                    child.tag() == gimli::DW_TAG_member || child.tag() == gimli::DW_TAG_inheritance
+                })
+                .filter(|child| !child.is_declaration())
+                .filter_map(|child| child.class())
+                .map(|member_type| member_type.natural_alignment())
+                .max()
+                .unwrap_or(1),
+            gimli::DW_TAG_base_type
+            | gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_enumeration_type => {
+                let size = self.size_bytes().unwrap_or(1).clamp(1, 16);
+                1 << size.ilog2()
+            }
+            gimli::DW_TAG_reference_type | gimli::DW_TAG_rvalue_reference_type => {
+                self.unit.encoding().address_size as usize
+            }
+            _ => 1,
+        }
+    }
+
+    /// Returns true if the entry is only a declaration, such as a
+    /// forward-declared class or a static data member.
+    fn is_declaration(&self) -> bool {
+        matches!(
+            self.entry.attr_value(gimli::DW_AT_declaration).unwrap(),
+            Some(gimli::AttributeValue::Flag(true))
+        )
     }
 
     /// Returns the name of the entry, considering only the DW_AT_name
@@ -211,17 +1340,155 @@ impl<'a, R: Reader> ContextEntry<'a, R> {
 
     /// Returns the name of the pointed-to type.
     fn name_as_pointer(&self) -> Option<String> {
-        (self.tag() == gimli::DW_TAG_pointer_type)
-            .then(|| self.class())
-            .flatten()
-            .and_then(|pointee_type| pointee_type.name())
-            .map(|pointee_name| format!("{pointee_name}*"))
+        let suffix = match self.tag() {
+            gimli::DW_TAG_pointer_type => "*",
+            gimli::DW_TAG_reference_type => "&",
+            gimli::DW_TAG_rvalue_reference_type => "&&",
+            _ => return None,
+        };
+        // A pointer without a DW_AT_type is a `void*`.
+        let pointee_name = match self.class() {
+            Some(pointee_type) => pointee_type.name()?,
+            None => "void".into(),
+        };
+        Some(format!("{pointee_name}{suffix}"))
+    }
+
+    /// Returns the name of a cv-qualified type.
+    fn name_as_modifier(&self) -> Option<String> {
+        let qualifier = match self.tag() {
+            gimli::DW_TAG_const_type => "const",
+            gimli::DW_TAG_volatile_type => "volatile",
+            _ => return None,
+        };
+        let inner_name = match self.class() {
+            Some(inner_type) => inner_type.name()?,
+            None => "void".into(),
+        };
+        Some(format!("{qualifier} {inner_name}"))
+    }
+
+    /// Returns the name of an array type, written as in C, such as
+    /// `int[4][2]`.  Fortran arrays are written as the element type
+    /// followed by the bounds of each dimension, such as
+    /// `real(kind=8)(1:3,0:4)`, with `:` for bounds known only at
+    /// runtime, and Ada arrays as `array (1 .. 4) of integer`, with
+    /// `<>` for bounds known only at runtime.
+    fn name_as_array(&self) -> Option<String> {
+        if self.tag() != gimli::DW_TAG_array_type {
+            return None;
+        }
+        let element_name = self.class()?.name()?;
+        let dimensions = self.array_dimensions();
+        if self.is_ada() {
+            let bounds = dimensions
+                .iter()
+                .map(|(lower, len)| match len {
+                    Some(len) => format!("{lower} .. {}", lower + *len as i64 - 1),
+                    None => "<>".into(),
+                })
+                .join(", ");
+            Some(format!(
+                "array ({bounds}) of {}",
+                ada::decode_name(&element_name)
+            ))
+        } else if self.is_fortran() {
+            let bounds = dimensions
+                .iter()
+                .map(|(lower, len)| match len {
+                    Some(len) => format!("{lower}:{}", lower + *len as i64 - 1),
+                    None => ":".into(),
+                })
+                .join(",");
+            Some(format!("{element_name}({bounds})"))
+        } else {
+            let lengths: String = dimensions
+                .iter()
+                .map(|(_, len)| match len {
+                    Some(len) => format!("[{len}]"),
+                    None => "[]".into(),
+                })
+                .collect();
+            Some(format!("{element_name}{lengths}"))
+        }
+    }
+
+    /// Returns the name of a Fortran character type, such as
+    /// `character(len=8)`, or `character(len=:)` if the length is only
+    /// known at runtime.
+    fn name_as_string(&self) -> Option<String> {
+        if self.tag() != gimli::DW_TAG_string_type {
+            return None;
+        }
+        let len = match self.size_bytes() {
+            Some(len) => len.to_string(),
+            None => ":".into(),
+        };
+        Some(format!("character(len={len})"))
     }
 
     /// Returns the name of the entity being described.
     fn name(&self) -> Option<String> {
-        None.or_else(|| self.name_from_tag())
-            .or_else(|| self.name_as_pointer())
+        let compute = || {
+            None.or_else(|| self.name_from_tag())
+                .or_else(|| self.name_as_pointer())
+                .or_else(|| self.name_as_modifier())
+                .or_else(|| self.name_as_array())
+                .or_else(|| self.name_as_string())
+        };
+        // Each data member is named only once, so only the names of
+        // types, which are shared, are worth remembering.
+        if self.tag() == gimli::DW_TAG_member || self.tag() == gimli::DW_TAG_inheritance {
+            compute()
+        } else {
+            Memo::<R>::get_or_insert(&self.memo.names, self.entry.offset(), compute)
+        }
+    }
+
+    /// Returns the keyword with which a class was declared, or `None`
+    /// for entries that aren't classes.
+    fn class_kind(&self) -> Option<ClassKind> {
+        match self.tag() {
+            gimli::DW_TAG_class_type | gimli::DW_TAG_interface_type => Some(ClassKind::Class),
+            gimli::DW_TAG_structure_type => Some(ClassKind::Struct),
+            gimli::DW_TAG_union_type => Some(ClassKind::Union),
+            _ => None,
+        }
+    }
+
+    /// If the entity is a pointer or reference, returns the name of
+    /// the type ultimately pointed to, as for `pointee`.
+    fn pointee_name(&self) -> Option<String> {
+        self.pointee()?.name()
+    }
+
+    /// If the entity is a pointer or reference, returns the type
+    /// ultimately pointed to, after following any further pointers,
+    /// references, cv-qualifiers, and typedefs.
+    fn pointee(&self) -> Option<Self> {
+        let is_indirection = |entry: &Self| {
+            matches!(
+                entry.tag(),
+                gimli::DW_TAG_pointer_type
+                    | gimli::DW_TAG_reference_type
+                    | gimli::DW_TAG_rvalue_reference_type
+            )
+        };
+        let is_transparent = |entry: &Self| {
+            is_indirection(entry)
+                || matches!(
+                    entry.tag(),
+                    gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type | gimli::DW_TAG_typedef
+                )
+        };
+
+        if !is_indirection(self) {
+            return None;
+        }
+        std::iter::successors(self.class(), |entry| {
+            is_transparent(entry).then(|| entry.class()).flatten()
+        })
+        .find(|entry| !is_transparent(entry))
     }
 
     /// Returns the class of the entity being described.
@@ -232,46 +1499,106 @@ impl<'a, R: Reader> ContextEntry<'a, R> {
              but the `entry.class()` method was used \
              for an entry with tag DW_TAG_class_type."
         );
-        self.entry
-            .attr_value(gimli::DW_AT_type)
-            .unwrap()
-            .map(|attr_value| match attr_value {
-                gimli::AttributeValue::UnitRef(offset) => {
-                    // This is the same as
-                    // `unit.entry(offset).unwrap()`, but isn't
-                    // restricted to the the lifetime of the temporary
-                    // view produced by Deref.  This allows the
-                    // returned `ContextEntry<'a, R>` to use the
-                    // lifetime 'a, rather than the lifetime of this
-                    // method's `&self` parameter.
-                    let entry = self.unit.entry(offset).unwrap();
-                    Self { entry, ..*self }
-                }
+        let location = Memo::<R>::get_or_insert(&self.memo.classes, self.entry.offset(), || {
+            self.entry
+                .attr_value(gimli::DW_AT_type)
+                .unwrap()
+                .map(|attr_value| match attr_value {
+                    gimli::AttributeValue::UnitRef(offset) => EntryLocation::Local(offset),
 
-                gimli::AttributeValue::DebugInfoRef(offset) => {
-                    let (unit, offset) = self
-                        .units
-                        .iter()
-                        .find_map(|unit| {
-                            offset
-                                .to_unit_offset(&unit.header)
-                                .map(|offset| (unit, offset))
-                        })
-                        .unwrap_or_else(|| panic!("Could not find {offset:?} in any CU"));
-                    let entry = unit.entry(offset).unwrap();
-                    Self {
-                        entry,
-                        unit,
-                        ..*self
+                    gimli::AttributeValue::DebugInfoRef(offset) => {
+                        let (unit_index, unit_offset) = Self::find_entry(self.units, offset);
+                        if unit_index == self.unit_index() {
+                            EntryLocation::Local(unit_offset)
+                        } else {
+                            EntryLocation::Other(unit_index, unit_offset)
+                        }
                     }
-                }
 
-                other => panic!(
-                    "Invalid AttributeValue for type, \
-                     must be reference into debug info section, \
-                     but instead was {other:?}."
-                ),
-            })
+                    gimli::AttributeValue::DebugInfoRefSup(offset) => {
+                        let (unit_index, unit_offset) = Self::find_entry(self.sup_units, offset);
+                        EntryLocation::Sup(unit_index, unit_offset)
+                    }
+
+                    other => panic!(
+                        "Invalid AttributeValue for type, \
+                         must be reference into debug info section, \
+                         but instead was {other:?}."
+                    ),
+                })
+        })?;
+
+        let (dwarf, units, sup_units, offset_base, unit_base, unit_index, offset) = match location {
+            EntryLocation::Local(offset) => {
+                let entry = self.unit.entry(offset).unwrap();
+                return Some(Self { entry, ..*self });
+            }
+            EntryLocation::Other(unit_index, offset) => (
+                self.dwarf,
+                self.units,
+                self.sup_units,
+                self.offset_base,
+                self.unit_base,
+                unit_index,
+                offset,
+            ),
+            // Only entries of the main file refer to the supplementary
+            // file, whose units follow those of the main file.
+            EntryLocation::Sup(unit_index, offset) => (
+                self.dwarf.sup().unwrap_or_else(|| {
+                    panic!("Found reference to {offset:?} without a supplementary file")
+                }),
+                self.sup_units,
+                &[][..],
+                sup_offset_base(self.dwarf),
+                self.units.len(),
+                unit_index,
+                offset,
+            ),
+        };
+        let lazy_unit = &units[unit_index];
+        let unit = self
+            .kept
+            .load(unit_base + unit_index, lazy_unit, dwarf)
+            .unwrap_or_else(|| panic!("Could not parse the CU containing {offset:?}"));
+        // This is the same as `unit.entry(offset).unwrap()`, but isn't
+        // restricted to the the lifetime of the temporary view
+        // produced by Deref.  This allows the returned
+        // `ContextEntry<'a, R>` to use the lifetime 'a, rather than the
+        // lifetime of this method's `&self` parameter.
+        let entry = unit.entry(offset).unwrap();
+        Some(Self {
+            dwarf,
+            units,
+            sup_units,
+            offset_base,
+            unit_base,
+            kept: self.kept,
+            unit,
+            memo: &lazy_unit.memo,
+            entry,
+        })
+    }
+
+    /// Find the unit containing an offset into .debug_info, returning
+    /// its index within `units` and the offset within the unit.
+    fn find_entry(
+        units: &[LazyUnit<R>],
+        offset: gimli::DebugInfoOffset<R::Offset>,
+    ) -> (usize, gimli::UnitOffset<R::Offset>) {
+        let unit_index = LazyUnit::find_index(units, offset)
+            .unwrap_or_else(|| panic!("Could not find {offset:?} in any CU"));
+        let unit_offset = offset
+            .to_unit_offset(&units[unit_index].header)
+            .unwrap_or_else(|| panic!("Could not find {offset:?} in any CU"));
+        (unit_index, unit_offset)
+    }
+
+    /// Returns the index of the entry's compilation unit within
+    /// `units`.
+    fn unit_index(&self) -> usize {
+        let start = self.unit.header.offset().as_debug_info_offset().unwrap();
+        LazyUnit::find_index(self.units, start).unwrap()
     }
 
     /// Expand `DW_TAG_typedef` tag into the pointed-to type.
@@ -293,14 +1620,746 @@ impl<'a, R: Reader> ContextEntry<'a, R> {
              for an entry with tag {}.",
             self.tag(),
         );
+        let encoding = self.unit.encoding();
+        if let Some(attr) = self.entry.attr(gimli::DW_AT_data_member_location).unwrap() {
+            return dwarf_compat::member_offset(&attr, encoding);
+        }
+        // Bitfields in DWARF 4 and later give the offset of their
+        // first bit instead, and are placed at their storage unit.
+        let size_bytes = self.member_size_bytes()?;
+        Some(self.bitfield()?.storage_offset(size_bytes))
+    }
+
+    /// Returns the bits occupied by a bitfield member, or `None` for
+    /// other members.  DWARF 4 and later give the offset of the first
+    /// bit as `DW_AT_data_bit_offset`.  DWARF 2 and 3 give the offset
+    /// of the storage unit, along with a `DW_AT_bit_offset` counting
+    /// from the most significant bit of the unit, which on
+    /// little-endian targets is the far end of the unit.
+    fn bitfield(&self) -> Option<Bitfield> {
+        let attr = |name| {
+            self.entry
+                .attr_value(name)
+                .unwrap()
+                .and_then(|attr_value| attr_value.udata_value())
+                .map(|value| value as usize)
+        };
+        let bit_size = attr(gimli::DW_AT_bit_size)?;
+        let bit_offset = match attr(gimli::DW_AT_data_bit_offset) {
+            Some(bit_offset) => bit_offset,
+            None => {
+                let encoding = self.unit.encoding();
+                let storage_offset = self
+                    .entry
+                    .attr(gimli::DW_AT_data_member_location)
+                    .unwrap()
+                    .and_then(|attr| dwarf_compat::member_offset(&attr, encoding))
+                    .unwrap_or(0);
+                let msb_offset = attr(gimli::DW_AT_bit_offset).unwrap_or(0);
+                let endian = gimli::Section::reader(&self.dwarf.debug_info).endian();
+                let is_big_endian = gimli::Endianity::is_big_endian(endian);
+                let bit_offset = if is_big_endian {
+                    msb_offset
+                } else {
+                    (8 * self.member_size_bytes()?).checked_sub(msb_offset + bit_size)?
+                };
+                8 * storage_offset + bit_offset
+            }
+        };
+        Some(Bitfield {
+            bit_offset,
+            bit_size,
+        })
+    }
+
+    /// Returns the size of a data member, in bytes.  Bitfields in
+    /// DWARF 2 and 3 give the size of the storage unit that holds
+    /// them as their own `DW_AT_byte_size`, alongside the
+    /// `DW_AT_bit_offset` of their bits within it, and other members
+    /// have the size of their type.
+    fn member_size_bytes(&self) -> Option<usize> {
+        let storage_size = self
+            .entry
+            .attr_value(gimli::DW_AT_byte_size)
+            .unwrap()
+            .and_then(|attr_value| dwarf_compat::byte_size(attr_value, self.unit.encoding()));
+        storage_size.or_else(|| self.class()?.expand_type_defs().size_bytes())
+    }
+
+    /// Returns the position of the entry's compilation unit within
+    /// the .debug_info section.
+    fn unit_offset(&self) -> Option<u64> {
+        self.unit
+            .header
+            .offset()
+            .as_debug_info_offset()
+            .map(|offset| self.offset_base + offset.0.into_u64())
+    }
+
+    /// Returns the position of the entry within the .debug_info
+    /// section, which identifies the entry across all compilation
+    /// units.  Entries in the supplementary file are placed after
+    /// the main file, as described for `offset_base`.
+    fn debug_info_offset(&self) -> Option<u64> {
         self.entry
-            .attr_value(gimli::DW_AT_data_member_location)
+            .offset()
+            .to_debug_info_offset(&self.unit.header)
+            .map(|offset| self.offset_base + offset.0.into_u64())
+    }
+
+    /// Returns the broad category of the type being described.
+    fn type_kind(&self) -> TypeKind {
+        match self.tag() {
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type => TypeKind::Pointer,
+            gimli::DW_TAG_enumeration_type => TypeKind::Enumeration,
+            gimli::DW_TAG_class_type
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_interface_type
+            | gimli::DW_TAG_union_type => TypeKind::Aggregate,
+            gimli::DW_TAG_base_type => {
+                match self.entry.attr_value(gimli::DW_AT_encoding).unwrap() {
+                    Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_signed)) => TypeKind::Signed,
+                    Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_unsigned)) => {
+                        TypeKind::Unsigned
+                    }
+                    Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_float)) => TypeKind::Float,
+                    Some(gimli::AttributeValue::Encoding(gimli::DW_ATE_boolean)) => {
+                        TypeKind::Boolean
+                    }
+                    Some(gimli::AttributeValue::Encoding(
+                        gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char,
+                    )) => TypeKind::Character,
+                    _ => TypeKind::Unknown,
+                }
+            }
+            _ => TypeKind::Unknown,
+        }
+    }
+
+    /// Returns the name of the compilation unit containing the entry.
+    fn unit_name(&self) -> Option<String> {
+        self.unit
+            .name
+            .as_ref()
+            .map(|name| name.to_string_lossy().unwrap().into())
+    }
+
+    /// Returns the path of the file in which the entity was declared,
+    /// as given by the `DW_AT_decl_file` attribute.  Relative paths
+    /// are resolved against the directory of the compilation unit.
+    fn decl_file(&self) -> Option<std::sync::Arc<str>> {
+        let file_index = match self.entry.attr_value(gimli::DW_AT_decl_file).unwrap()? {
+            gimli::AttributeValue::FileIndex(index) => index,
+            gimli::AttributeValue::Udata(index) => index,
+            _ => return None,
+        };
+        // The path is computed without looking up any other entries,
+        // so the lock is held throughout.
+        let mut decl_files = self.memo.decl_files.lock().unwrap();
+        decl_files
+            .entry(file_index)
+            .or_insert_with(|| self.file_path(file_index).map(Into::into))
+            .clone()
+    }
+
+    /// Returns the path of a file in the line program of the entry's
+    /// unit, given its index.
+    fn file_path(&self, file_index: u64) -> Option<String> {
+        let header = self.unit.line_program.as_ref()?.header();
+        let file = header.file(file_index)?;
+
+        let attr_string = |attr_value| -> String {
+            self.dwarf
+                .attr_string(self.unit, attr_value)
+                .unwrap()
+                .to_string_lossy()
+                .unwrap()
+                .into()
+        };
+
+        let mut path = PathBuf::new();
+        if let Some(comp_dir) = self.unit.comp_dir.as_ref() {
+            path.push(&*comp_dir.to_string_lossy().unwrap());
+        }
+        if let Some(directory) = file.directory(header) {
+            path.push(attr_string(directory));
+        }
+        path.push(attr_string(file.path_name()));
+        Some(path.to_string_lossy().into())
+    }
+
+    /// Returns the line at which the entry was declared.
+    fn decl_line(&self) -> Option<u64> {
+        self.entry
+            .attr_value(gimli::DW_AT_decl_line)
+            .unwrap()?
+            .udata_value()
+    }
+
+    /// Returns the alignment given explicitly for the entry, as by
+    /// `__attribute__((__aligned__(N)))`.  Since `rustc` records the
+    /// alignment of every type and member, whether or not it was
+    /// requested, alignments from Rust are ignored.
+    fn alignment(&self) -> Option<usize> {
+        let alignment = self
+            .entry
+            .attr_value(gimli::DW_AT_alignment)
+            .unwrap()?
+            .udata_value()?;
+        (self.language() != Some(gimli::DW_LANG_Rust)).then_some(alignment as usize)
+    }
+
+    /// Returns true if the entry is the structure that Go uses to
+    /// describe a built-in type, such as a string or slice, as given
+    /// by its `DW_AT_go_kind`.
+    fn is_go_builtin_header(&self) -> bool {
+        self.entry
+            .attr_value(go::DW_AT_GO_KIND)
+            .unwrap()
+            .and_then(|attr_value| attr_value.udata_value())
+            .is_some_and(go::is_builtin_header)
+    }
+
+    /// Returns true if the entry is within an Ada compilation unit.
+    fn is_ada(&self) -> bool {
+        matches!(
+            self.language(),
+            Some(
+                gimli::DW_LANG_Ada83
+                    | gimli::DW_LANG_Ada95
+                    | gimli::DW_LANG_Ada2005
+                    | gimli::DW_LANG_Ada2012
+            )
+        )
+    }
+
+    /// Returns the lower bound of array dimensions that don't give one
+    /// explicitly, which is 1 for languages such as Fortran and Ada,
+    /// and 0 for C and most others.
+    fn default_lower_bound(&self) -> i64 {
+        if self.is_fortran() || self.is_ada() {
+            return 1;
+        }
+        self.language()
+            .and_then(|language| language.default_lower_bound())
+            .unwrap_or(0) as i64
+    }
+
+    /// Returns true if the entry is within a Fortran compilation unit.
+    fn is_fortran(&self) -> bool {
+        matches!(
+            self.language(),
+            Some(
+                gimli::DW_LANG_Fortran77
+                    | gimli::DW_LANG_Fortran90
+                    | gimli::DW_LANG_Fortran95
+                    | gimli::DW_LANG_Fortran03
+                    | gimli::DW_LANG_Fortran08
+                    | gimli::DW_LANG_Fortran18
+            )
+        )
+    }
+
+    /// Returns the language in which a class is written out.  A struct
+    /// declared in Objective-C is written as one from C or C++, unless
+    /// it is an Objective-C class, which clang marks with its runtime.
+    fn source_language(&self) -> Language {
+        match self.language() {
+            Some(
+                gimli::DW_LANG_C_plus_plus
+                | gimli::DW_LANG_C_plus_plus_03
+                | gimli::DW_LANG_C_plus_plus_11
+                | gimli::DW_LANG_C_plus_plus_14
+                | gimli::DW_LANG_C_plus_plus_17
+                | gimli::DW_LANG_C_plus_plus_20,
+            ) => Language::Cpp,
+            Some(gimli::DW_LANG_Rust) => Language::Rust,
+            Some(gimli::DW_LANG_Go) => Language::Go,
+            Some(gimli::DW_LANG_ObjC | gimli::DW_LANG_ObjC_plus_plus)
+                if self
+                    .entry
+                    .attr_value(gimli::DW_AT_APPLE_runtime_class)
+                    .unwrap()
+                    .is_some() =>
+            {
+                Language::ObjC
+            }
+            Some(gimli::DW_LANG_ObjC_plus_plus) => Language::Cpp,
+            _ if self.is_fortran() => Language::Fortran,
+            _ if self.is_ada() => Language::Ada,
+            _ => Language::C,
+        }
+    }
+
+    /// Returns the source language of the compilation unit containing
+    /// the entry.
+    fn language(&self) -> Option<gimli::DwLang> {
+        *self.memo.language.get_or_init(|| {
+            let mut entries = self.unit.entries();
+            let (_, root) = entries.next_dfs().unwrap()?;
+            match root.attr_value(gimli::DW_AT_language).unwrap()? {
+                gimli::AttributeValue::Language(language) => Some(language),
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns the mangled name of the entry, from either the
+    /// DW_AT_linkage_name attribute or its pre-DWARF-4 equivalent.
+    fn linkage_name(&self) -> Option<String> {
+        [gimli::DW_AT_linkage_name, gimli::DW_AT_MIPS_linkage_name]
+            .into_iter()
+            .find_map(|attr| self.entry.attr_value(attr).unwrap())
+            .map(|attr_value| {
+                self.dwarf
+                    .attr_string(self.unit, attr_value)
+                    .unwrap()
+                    .to_string_lossy()
+                    .unwrap()
+                    .into()
+            })
+    }
+
+    /// Returns the mangled names of the methods and static data
+    /// members declared within the class.
+    fn member_linkage_names(&self) -> Vec<String> {
+        self.iter_children()
+            .filter(|child| {
+                matches!(
+                    child.tag(),
+                    gimli::DW_TAG_subprogram | gimli::DW_TAG_variable | gimli::DW_TAG_member
+                )
+            })
+            .filter_map(|child| child.linkage_name())
+            .collect()
+    }
+
+    /// Returns true if the class holds no data, having no vtable
+    /// pointer, no non-static data members, and only empty base
+    /// classes.  Such a class has a size of 1, so that distinct
+    /// objects have distinct addresses.
+    fn is_empty_class(&self) -> bool {
+        let is_aggregate = matches!(
+            self.tag(),
+            gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type
+        );
+        is_aggregate
+            && !self.is_declaration()
+            && !self.has_vtable()
+            && self.iter_children().all(|child| match child.tag() {
+                gimli::DW_TAG_member => child.is_declaration(),
+                gimli::DW_TAG_inheritance => child
+                    .class()
+                    .is_some_and(|base| base.expand_type_defs().is_empty_class()),
+                _ => true,
+            })
+    }
+
+    /// Returns true if the class has a vtable pointer, either as an
+    /// artificial `_vptr` member or implied by a virtual method or a
+    /// `DW_AT_containing_type` attribute.  Vtable pointers inherited
+    /// from a base class are not considered.
+    fn has_vtable(&self) -> bool {
+        let has_containing_type = self
+            .entry
+            .attr_value(gimli::DW_AT_containing_type)
             .unwrap()
-            .map(|attr_value| match attr_value {
-                gimli::AttributeValue::Udata(data) => data as usize,
-                _ => panic!("Invalid AttributeValue for member location"),
+            .is_some();
+
+        has_containing_type
+            || self.iter_children().any(|child| match child.tag() {
+                gimli::DW_TAG_member => {
+                    let is_artificial = matches!(
+                        child.entry.attr_value(gimli::DW_AT_artificial).unwrap(),
+                        Some(gimli::AttributeValue::Flag(true))
+                    );
+                    is_artificial && child.name().is_some_and(|name| name.starts_with("_vptr"))
+                }
+                gimli::DW_TAG_subprogram => matches!(
+                    child.entry.attr_value(gimli::DW_AT_virtuality).unwrap(),
+                    Some(gimli::AttributeValue::Virtuality(virtuality))
+                        if virtuality != gimli::DW_VIRTUALITY_none
+                ),
+                _ => false,
             })
     }
+
+    /// Returns the enumerators of an enumeration type.  Returns an
+    /// empty list for any other type.
+    fn enumerators(&self) -> Vec<Enumerator> {
+        if self.tag() != gimli::DW_TAG_enumeration_type {
+            return Vec::new();
+        }
+        self.iter_children()
+            .filter(|child| child.tag() == gimli::DW_TAG_enumerator)
+            .filter_map(|child| {
+                let name = child.name()?;
+                let value = match child.entry.attr_value(gimli::DW_AT_const_value).unwrap()? {
+                    gimli::AttributeValue::Sdata(value) => value,
+                    gimli::AttributeValue::Udata(value) => value as i64,
+                    gimli::AttributeValue::Data1(value) => value as i64,
+                    gimli::AttributeValue::Data2(value) => value as i64,
+                    gimli::AttributeValue::Data4(value) => value as i64,
+                    gimli::AttributeValue::Data8(value) => value as i64,
+                    _ => return None,
+                };
+                Some(Enumerator { name, value })
+            })
+            .collect()
+    }
+
+    /// Returns the definition of an anonymous enum, listing its
+    /// enumerators, as in `enum { A = 0, B = 1 }`.  Returns `None` for
+    /// other types.
+    fn anonymous_enum_name(&self) -> Option<String> {
+        if self.tag() != gimli::DW_TAG_enumeration_type || self.name().is_some() {
+            return None;
+        }
+        let enumerators = self
+            .enumerators()
+            .into_iter()
+            .map(|enumerator| format!("{} = {}", enumerator.name, enumerator.value))
+            .collect::<Vec<_>>();
+        if enumerators.is_empty() {
+            Some("enum {}".into())
+        } else {
+            Some(format!("enum {{ {} }}", enumerators.join(", ")))
+        }
+    }
+
+    /// Collect the layout of the class described by this entry, for
+    /// use by the output formats.  The `namespace` is the enclosing
+    /// namespace of the class.  The names of member types are shared
+    /// through `type_names`.
+    fn layout(
+        &self,
+        namespace: String,
+        aliases: Vec<String>,
+        type_names: &Interner,
+    ) -> ClassLayout {
+        // An anonymous struct that is given a name by a typedef, as
+        // is common in C, is known by that name instead.
+        let name = self.name().or_else(|| aliases.first().cloned()).unwrap();
+        let size_bytes = self.size_bytes().unwrap();
+
+        // Go has no namespaces in its debug information, but names
+        // each type with its package path, as in `runtime.g`, and GNAT
+        // encodes the enclosing packages of Ada types in the same way,
+        // as in `pkg__rec` for `pkg.rec`.  The package is used as the
+        // namespace, and the full name is kept as an alias, as used by
+        // the types of members.
+        let is_ada = self.is_ada();
+        let name = if is_ada {
+            ada::decode_name(&name)
+        } else {
+            name
+        };
+        let package = match self.language() {
+            Some(gimli::DW_LANG_Go) => go::split_package(&name),
+            _ if is_ada => ada::split_package(&name),
+            _ => None,
+        };
+        let (name, namespace, aliases) = match package {
+            Some((package, type_name)) => {
+                let mut aliases = aliases;
+                aliases.insert(0, name.clone());
+                (type_name.to_string(), package.to_string(), aliases)
+            }
+            None => (name, namespace, aliases),
+        };
+
+        let members = if self.tag() == gimli::DW_TAG_common_block {
+            self.common_block_variables()
+                .into_iter()
+                .map(|(offset, variable)| variable.member_layout(offset, type_names))
+                .collect()
+        } else {
+            self.members(0, type_names)
+        };
+
+        ClassLayout {
+            name,
+            namespace,
+            binary: None,
+            unit_name: self.unit_name(),
+            decl_file: self.decl_file().map(|decl_file| decl_file.to_string()),
+            decl_line: self.decl_line(),
+            unit_offset: self.unit_offset(),
+            die_offset: self.debug_info_offset(),
+            has_vtable: self.has_vtable(),
+            aliases,
+            linkage_names: self.member_linkage_names(),
+            size_bytes,
+            size_kind: self.size_kind(),
+            alignment: self.alignment(),
+            members,
+            variant_part: self.variant_part(type_names),
+            language: self.source_language(),
+            kind: self.class_kind().unwrap_or_default(),
+        }
+    }
+
+    /// Collect the data members and base classes of the class
+    /// described by this entry, with offsets from `base_offset`.
+    fn members(&self, base_offset: usize, type_names: &Interner) -> Vec<MemberLayout> {
+        // The members of a union all start at its beginning, and so
+        // are usually given without a location.
+        let is_union = self.tag() == gimli::DW_TAG_union_type;
+        let mut is_open_array = false;
+        let mut members: Vec<MemberLayout> = self
+            .iter_children()
+            .filter(|child| {
+                child.tag() == gimli::DW_TAG_member || child.tag() == gimli::DW_TAG_inheritance
+            })
+            .filter_map(|child| {
+                let offset = child.member_location().or(is_union.then_some(0))?;
+                is_open_array = child.has_open_array_type();
+                Some(child.member_layout(base_offset + offset, type_names))
+            })
+            .collect();
+        if let Some(last) = members.last_mut() {
+            last.is_flexible_array = is_open_array;
+        }
+        members
+    }
+
+    /// Returns true if the entry is a data member whose type is an
+    /// array with no length, as for a C99 flexible array member
+    /// `T data[]`, or with a length of 0 or 1, as for the older
+    /// `T data[0]` and `T data[1]` idioms.  Only the outermost
+    /// dimension is considered.
+    fn has_open_array_type(&self) -> bool {
+        if self.tag() != gimli::DW_TAG_member {
+            return false;
+        }
+        let Some(class) = self.class().map(|class| class.expand_type_defs()) else {
+            return false;
+        };
+        if class.tag() != gimli::DW_TAG_array_type {
+            return false;
+        }
+        matches!(
+            class.array_dimensions().first(),
+            Some((_, None | Some(0) | Some(1)))
+        )
+    }
+
+    /// Returns the variables of a Fortran COMMON block, along with
+    /// their offsets from the start of the block.  Each variable's
+    /// location is a static address within the block, so offsets are
+    /// relative to the block's own location, or to the lowest address
+    /// of its variables if the block has none.
+    fn common_block_variables(&self) -> Vec<(usize, Self)> {
+        let encoding = self.unit.encoding();
+        let address = |entry: &Self| {
+            entry
+                .entry
+                .attr_value(gimli::DW_AT_location)
+                .unwrap()
+                .and_then(|location| dwarf_compat::static_address(location, encoding))
+        };
+        let variables: Vec<(u64, Self)> = self
+            .iter_children()
+            .filter(|child| child.tag() == gimli::DW_TAG_variable)
+            .filter_map(|variable| Some((address(&variable)?, variable)))
+            .collect();
+        let Some(start) = address(self).or_else(|| variables.iter().map(|(addr, _)| *addr).min())
+        else {
+            return Vec::new();
+        };
+        variables
+            .into_iter()
+            .filter(|(addr, _)| *addr >= start)
+            .map(|(addr, variable)| ((addr - start) as usize, variable))
+            .collect()
+    }
+
+    /// Collect the layout of the data member or base class described
+    /// by this entry, located at `offset` within the class.
+    fn member_layout(&self, offset: usize, type_names: &Interner) -> MemberLayout {
+        let declared_type = self.class().unwrap();
+        let class = self.class().unwrap().expand_type_defs();
+
+        // TODO: Expand anonymous structs.  An anonymous type named by
+        // a typedef is known by the typedef's name, and an anonymous
+        // enum is written out with its enumerators, as in
+        // `enum { A = 0, B = 1 }`.  Go describes named types,
+        // interfaces, maps, and channels as typedefs of their
+        // implementation, such as `runtime.iface` for `io.Reader`, so
+        // the declared name is used instead.
+        let type_name = if self.language() == Some(gimli::DW_LANG_Go) {
+            declared_type.name().or_else(|| class.name())
+        } else {
+            class.name().or_else(|| declared_type.name())
+        };
+        let type_name = type_name
+            .or_else(|| class.anonymous_enum_name())
+            .unwrap_or_else(|| "unknown_class".into());
+        let type_name = if self.is_ada() {
+            ada::decode_name(&type_name)
+        } else {
+            type_name
+        };
+
+        // TODO: Print base classes as base classes, rather than as
+        // members.
+        let is_base_class = self.tag() == gimli::DW_TAG_inheritance;
+        let name = if is_base_class {
+            "_base_class".into()
+        } else {
+            self.name().unwrap_or_else(|| "unknown_name".into())
+        };
+
+        // An empty base class has a size of 1, as does any other
+        // empty class, but the empty base optimization places it at
+        // the same offset as other members, so that it occupies no
+        // storage.
+        let size_bytes = if is_base_class && class.is_empty_class() {
+            0
+        } else {
+            self.member_size_bytes().unwrap_or(0)
+        };
+
+        // A class that is only declared in this unit may be defined in
+        // another, which is checked once all classes have been read.
+        let is_incomplete = match class.pointee() {
+            Some(pointee) => pointee.is_declaration(),
+            None => class.is_declaration(),
+        };
+
+        MemberLayout {
+            name,
+            type_name: type_names.intern(&type_name),
+            kind: class.type_kind(),
+            is_base_class,
+            offset,
+            size_bytes,
+            alignment: self.alignment(),
+            enumerators: class.enumerators(),
+            pointee: class.pointee_name(),
+            pointee_class: class.pointee().and_then(|pointee| pointee.class_kind()),
+            is_flexible_array: false,
+            is_incomplete,
+            bitfield: self.bitfield(),
+            decl_file: self.decl_file(),
+            decl_line: self.decl_line(),
+        }
+    }
+
+    /// Collect the variants of a Rust enum or an Ada variant record,
+    /// which are described by a `DW_TAG_variant_part` that names the
+    /// member holding the discriminant.  `rustc` places each variant's
+    /// fields in a structure, held by a single member of the
+    /// `DW_TAG_variant`, while GNAT places the members of each variant
+    /// directly within the `DW_TAG_variant`.  Returns `None` for
+    /// classes without a variant part.
+    fn variant_part(&self, type_names: &Interner) -> Option<VariantPart> {
+        let variant_part = self
+            .iter_children()
+            .find(|child| child.tag() == gimli::DW_TAG_variant_part)?;
+
+        // The discriminant of a Rust enum is within the variant part,
+        // while that of an Ada record is one of the record's members.
+        let discriminant_offset = match variant_part.entry.attr_value(gimli::DW_AT_discr).unwrap() {
+            Some(gimli::AttributeValue::UnitRef(offset)) => Some(offset),
+            _ => None,
+        };
+        let discriminant = variant_part
+            .iter_children()
+            .chain(self.iter_children())
+            .filter(|child| child.tag() == gimli::DW_TAG_member)
+            .find(|child| Some(child.entry.offset()) == discriminant_offset)
+            .and_then(|child| {
+                let offset = child.member_location()?;
+                let mut member = child.member_layout(offset, type_names);
+                if child.name().is_none() {
+                    member.name = "discriminant".into();
+                }
+                Some(member)
+            });
+        let is_signed = discriminant
+            .as_ref()
+            .is_some_and(|discriminant| discriminant.kind == TypeKind::Signed);
+
+        let is_rust = self.language() == Some(gimli::DW_LANG_Rust);
+        let variants = variant_part
+            .iter_children()
+            .filter(|child| child.tag() == gimli::DW_TAG_variant)
+            .filter_map(|variant| {
+                let discriminant_values = variant.discriminant_values(is_signed);
+                if !is_rust {
+                    return Some(VariantLayout {
+                        name: String::new(),
+                        discriminant_values,
+                        members: variant.members(0, type_names),
+                    });
+                }
+                let member = variant
+                    .iter_children()
+                    .find(|child| child.tag() == gimli::DW_TAG_member)?;
+                let offset = member.member_location()?;
+                let fields = member.class()?.expand_type_defs();
+                Some(VariantLayout {
+                    name: member.name().unwrap_or_else(|| "unknown_name".into()),
+                    discriminant_values,
+                    members: fields.members(offset, type_names),
+                })
+            })
+            .collect();
+
+        Some(VariantPart {
+            discriminant,
+            variants,
+        })
+    }
+
+    /// Returns the ranges of discriminant values that select a
+    /// `DW_TAG_variant`, given either as a single `DW_AT_discr_value`
+    /// or as a `DW_AT_discr_list` of values and ranges, as for Ada's
+    /// `when 1 | 3 .. 5`.  The values in the list are LEB128-encoded,
+    /// signed if the discriminant's type is signed.  Returns an empty
+    /// list for the variant selected by any other value.
+    fn discriminant_values(&self, is_signed: bool) -> Vec<(i64, i64)> {
+        if let Some(value) = self.entry.attr_value(gimli::DW_AT_discr_value).unwrap() {
+            return dwarf_compat::constant(value, self.unit.encoding())
+                .map(|value| vec![(value, value)])
+                .unwrap_or_default();
+        }
+        let Some(gimli::AttributeValue::Block(mut list)) =
+            self.entry.attr_value(gimli::DW_AT_discr_list).unwrap()
+        else {
+            return Vec::new();
+        };
+
+        let read_value = |list: &mut R| -> gimli::Result<i64> {
+            if is_signed {
+                list.read_sleb128()
+            } else {
+                list.read_uleb128().map(|value| value as i64)
+            }
+        };
+        let mut values = Vec::new();
+        while !list.is_empty() {
+            let Ok(descriptor) = list.read_u8() else {
+                break;
+            };
+            let Ok(first) = read_value(&mut list) else {
+                break;
+            };
+            let last = if gimli::DwDsc(descriptor) == gimli::DW_DSC_range {
+                let Ok(last) = read_value(&mut list) else {
+                    break;
+                };
+                last
+            } else {
+                first
+            };
+            values.push((first, last));
+        }
+        values
+    }
 }
 
 struct EntryChildrenIterator<'a, 'b, R: Reader> {
@@ -334,176 +2393,1064 @@ impl<'a, 'b, R: Reader> Iterator for EntryChildrenIterator<'a, 'b, R> {
     }
 }
 
-fn dump_file<R: Reader>(dwarf: &Dwarf<R>, search_filter: &SearchFilter) -> Result<(), Error> {
+/// Collect the layout of every named class and struct in the DWARF
+/// information, from all compilation units.  Compilation units are
+/// processed in parallel, but the classes of each unit are passed to
+/// `emit` in the order in which they occur in the debug information,
+/// as soon as all earlier units have been processed.
+///
+/// If a `lookup` from the accelerator tables is provided, then in the
+/// compilation units it covers, only the entries it found are used,
+/// and units without any such entries are skipped.  Units that it
+/// doesn't cover are read in full.
+fn collect_classes<R>(
+    dwarf: &Dwarf<R>,
+    load_options: &LoadOptions,
+    lookup: Option<&NameLookup>,
+    emit: &mut dyn FnMut(Vec<ClassLayout>),
+) -> Result<(), Error>
+where
+    R: Reader + Send + Sync,
+    R::Offset: Send + Sync,
+{
     let dwarf_units = DwarfUnits::new(dwarf)?;
+    let jobs = load_options.jobs;
 
+    let is_class = |entry: &ContextEntry<_>| {
+        let is_aggregate = matches!(
+            entry.tag(),
+            gimli::DW_TAG_class_type
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_interface_type
+                | gimli::DW_TAG_union_type
+                | gimli::DW_TAG_common_block
+        );
+        is_aggregate && !entry.is_go_builtin_header()
+    };
+
+    // The units that must be read, given the offsets of the entries
+    // that are needed from the units covered by the lookup.
+    let select_units = |entries: &HashSet<u64>| -> Option<HashSet<usize>> {
+        let lookup = lookup?;
+        let uncovered =
+            dwarf_units
+                .all_units()
+                .enumerate()
+                .filter(|(_, (lazy_unit, offset_base))| {
+                    lazy_unit.start().is_none_or(|start| {
+                        !lookup.units.contains(&(offset_base + start.into_u64()))
+                    })
+                });
+        let selected = uncovered.map(|(i, _)| i).chain(
+            entries
+                .iter()
+                .filter_map(|&offset| dwarf_units.find_index(offset)),
+        );
+        Some(selected.collect())
+    };
+    let is_selected = |entry: &ContextEntry<_>, entries: &HashSet<u64>| {
+        let Some(lookup) = lookup else {
+            return true;
+        };
+        let is_covered = entry
+            .unit_offset()
+            .is_some_and(|start| lookup.units.contains(&start));
+        !is_covered
+            || entry
+                .debug_info_offset()
+                .is_some_and(|offset| entries.contains(&offset))
+    };
+
+    // The typedef names of each class, keyed by the location of the
+    // class within .debug_info.  When using the lookup, the typedefs
+    // that it found are also collected separately, since the classes
+    // they name are needed even if the lookup didn't find them.
+    let mut aliases: HashMap<u64, Vec<String>> = HashMap::new();
+    let mut wanted = lookup
+        .map(|lookup| lookup.entries.clone())
+        .unwrap_or_default();
+    let empty = HashSet::new();
+    let typedef_entries = lookup.map_or(&empty, |lookup| &lookup.entries);
     dwarf_units
-        .iter()
-        .flat_map(|unit| unit.iter())
-        .filter(|entry| entry.tag() == gimli::DW_TAG_class_type)
-        .filter(|entry| entry.size_bytes().is_some())
-        .filter(|entry| {
-            if let Some(required_class_name) = search_filter.class_name.as_ref() {
-                entry
-                    .name()
-                    .map(|name| &name == required_class_name)
-                    .unwrap_or(false)
-            } else {
-                true
-            }
-        })
-        .filter(|entry| {
-            if let Some(required_base_class) = search_filter.base_class_name.as_ref() {
-                entry
-                    .iter_base_classes()
-                    .any(|base_class| base_class.name().as_ref() == Some(required_base_class))
-            } else {
-                true
-            }
+        .par_flat_map(jobs, select_units(typedef_entries).as_ref(), |unit| {
+            unit.iter_namespaced()
+                .filter(|(_, entry)| entry.tag() == gimli::DW_TAG_typedef)
+                .filter_map(|(namespace, entry)| {
+                    let alias = entry.name_from_tag()?;
+                    let alias = if namespace.is_empty() {
+                        alias
+                    } else {
+                        format!("{namespace}::{alias}")
+                    };
+                    let is_wanted = is_selected(&entry, typedef_entries);
+                    let class = entry.class()?.expand_type_defs();
+                    is_class(&class).then(|| (class.debug_info_offset(), alias, is_wanted))
+                })
+                .collect()
         })
-        .filter(|entry| {
-            if let Some(required_member_class) = search_filter.contained_class_name.as_ref() {
-                entry
-                    .iter_class_members()
-                    .filter_map(|member| member.class())
-                    .any(|class| class.name().as_ref() == Some(required_member_class))
-            } else {
-                true
+        .into_iter()
+        .for_each(|(offset, alias, is_wanted)| {
+            if let Some(offset) = offset {
+                let class_aliases = aliases.entry(offset).or_default();
+                if !class_aliases.contains(&alias) {
+                    class_aliases.push(alias);
+                }
+                if is_wanted {
+                    wanted.insert(offset);
+                }
             }
-        })
-        .unique_by(|entry| entry.name())
-        .enumerate()
-        .for_each(|(i, entry)| {
-            if i > 0 {
-                println!("");
+        });
+
+    let type_names = Interner::default();
+    dwarf_units.par_for_each(
+        jobs,
+        select_units(&wanted).as_ref(),
+        |unit| {
+            unit.iter_namespaced()
+                .filter(|(_, entry)| is_class(entry))
+                .filter(|(_, entry)| is_selected(entry, &wanted))
+                .filter(|(_, entry)| entry.size_bytes().is_some())
+                .map(|(namespace, entry)| {
+                    let class_aliases = entry
+                        .debug_info_offset()
+                        .and_then(|offset| aliases.get(&offset))
+                        .cloned()
+                        .unwrap_or_default();
+                    (namespace, entry, class_aliases)
+                })
+                .filter(|(_, entry, aliases)| entry.name().is_some() || !aliases.is_empty())
+                .map(|(namespace, entry, aliases)| entry.layout(namespace, aliases, &type_names))
+                .collect()
+        },
+        emit,
+    );
+
+    Ok(())
+}
+
+/// Perform the action requested on the command line.  Actions other
+/// than `diff` and `abi-check` use the classes found in the `inputs`,
+/// as if they had been read from a single file.  If there is more than
+/// one input, each class records the input it was found in.
+fn process_classes(inputs: &[PathBuf], options: &Options) -> Result<(), Error> {
+    match &options.command {
+        None if options.address.is_some() => inspect_memory(inputs, options),
+        None if options.sort.is_none()
+            && !options.with_deps
+            && options.expand_depth == 0
+            && !options.recursive
+            && !options.collapse_templates
+            && !options.format.reports_incomplete_types()
+            && !options.search_filter.needs_class_index() =>
+        {
+            stream_classes(inputs, options)
+        }
+        None => print_classes(
+            load_inputs(inputs, options, |input| {
+                load_classes(input, &options.load_options)
+            })?
+            .into_iter(),
+            options,
+            options.format,
+        ),
+        Some(Command::Resolve { path }) => resolve::resolve_offsets(
+            load_inputs(inputs, options, |input| {
+                load_classes(input, &options.load_options)
+            })?
+            .into_iter(),
+            path.as_deref(),
+        ),
+        Some(Command::Index) => inputs.iter().try_for_each(|input| {
+            type_index::write_index(input, &load_classes(input, &options.load_options)?)
+        }),
+        Some(Command::Query) => print_classes(
+            load_inputs(inputs, options, |input| query_classes(input, options))?.into_iter(),
+            options,
+            OutputFormat::List,
+        ),
+        Some(Command::OdrCheck) => odr_check::check_odr(
+            load_inputs(inputs, options, |input| {
+                load_classes(input, &options.load_options)
+            })?
+            .into_iter(),
+        ),
+        Some(Command::Overlay { class_name, path }) => inspect::print_overlay(
+            load_inputs(inputs, options, |input| {
+                load_classes(input, &options.load_options)
+            })?
+            .into_iter(),
+            class_name,
+            &std::fs::read(path)?,
+            inputs.first().is_none_or(|input| is_little_endian(input)),
+        ),
+        Some(Command::Diff { old, new }) => diff::print_diff(
+            load_classes(&download::local_path(old)?, &options.load_options)?.into_iter(),
+            load_classes(&download::local_path(new)?, &options.load_options)?.into_iter(),
+            &options.search_filter,
+        ),
+        Some(Command::AbiCheck { old, new }) => abi_check::check_abi(
+            load_classes(&download::local_path(old)?, &options.load_options)?.into_iter(),
+            load_classes(&download::local_path(new)?, &options.load_options)?.into_iter(),
+            &options.search_filter,
+        ),
+    }
+}
+
+/// Print the member values of the object at `--at`, read from the
+/// process given by `--pid` or from the core dump given by `--core`.
+/// For a core dump, the input binaries are used both for their debug
+/// information and for the memory they had mapped that the core dump
+/// omits.
+fn inspect_memory(inputs: &[PathBuf], options: &Options) -> Result<(), Error> {
+    let Some(address) = options.address else {
+        return Ok(());
+    };
+    let memory: Box<dyn inspect::Memory> = match (options.pid, &options.core_path) {
+        (Some(pid), _) => Box::new(ProcessMemory::new(pid)),
+        (None, Some(core_path)) => Box::new(CoreDump::open(
+            core_path,
+            inputs,
+            options.load_options.use_mmap,
+        )?),
+        (None, None) => return Ok(()),
+    };
+    inspect::print_objects(
+        load_inputs(inputs, options, |input| {
+            load_classes(input, &options.load_options)
+        })?
+        .into_iter(),
+        &options.search_filter,
+        &*memory,
+        address,
+    )
+}
+
+/// Returns true if the binary stores multi-byte values least
+/// significant byte first, as do files that aren't object files, such
+/// as BTF and PDB files.
+fn is_little_endian(path: &Path) -> bool {
+    FileContents::open(path, false)
+        .ok()
+        .and_then(|bytes| Some(object::File::parse(&*bytes).ok()?.is_little_endian()))
+        .unwrap_or(true)
+}
+
+/// Read the classes from each input, using `load`.  If there is more
+/// than one input, each class records the input it was found in, so
+/// that the output can say which binary defines each class.  With
+/// `--with-deps`, members whose type is only declared in one input are
+/// completed from the input that defines it.
+fn load_inputs(
+    inputs: &[PathBuf],
+    options: &Options,
+    load: impl Fn(&Path) -> Result<Vec<ClassLayout>, Error>,
+) -> Result<Vec<ClassLayout>, Error> {
+    let mut classes = Vec::new();
+    for input in inputs {
+        let binary = binary_name(input, inputs, options);
+        let input_classes = match load(input) {
+            Ok(input_classes) => input_classes,
+            Err(err) if options.scanned_inputs.contains(input) => {
+                eprintln!("warning: skipping {}: {err}", input.display());
+                continue;
             }
+            Err(err) => return Err(err),
+        };
+        classes.extend(input_classes.into_iter().map(|class| ClassLayout {
+            binary: binary.clone(),
+            ..class
+        }));
+    }
+    if options.with_deps {
+        dependencies::complete_declarations(&mut classes);
+    }
+    Ok(classes)
+}
 
-            let name = entry.name().unwrap();
-            let size_bytes = entry.size_bytes().unwrap();
+/// The name recorded as the binary of each class read from `input`.
+/// Classes only record their binary if there is more than one input,
+/// or if the inputs were found by `--scan`.
+fn binary_name(input: &Path, inputs: &[PathBuf], options: &Options) -> Option<String> {
+    (inputs.len() > 1 || !options.scanned_inputs.is_empty()).then(|| input.display().to_string())
+}
 
-            // TODO: Align the comments for readability.
+/// Returns the classes for the `query` subcommand.  These are read
+/// from the cached index of the binary, which only records the name,
+/// size, and members of each class.  If the search options depend on
+/// anything else, or if the binary has not been indexed, the classes
+/// are read from the debug information instead.
+fn query_classes(input: &Path, options: &Options) -> Result<Vec<ClassLayout>, Error> {
+    if !options.search_filter.is_answerable_from_index() {
+        return load_classes(input, &options.load_options);
+    }
 
-            println!("struct {name} {{ // {size_bytes} bytes");
+    let index_path = type_index::binary_index_path(input)?;
+    let cached_index = match &index_path {
+        Some(index_path) => TypeIndex::read(index_path)?,
+        None => None,
+    };
+    if let Some(index) = cached_index {
+        return Ok(index.entries.iter().map(|entry| entry.layout()).collect());
+    }
 
-            entry
-                .iter_children()
-                .filter(|child| {
-                    child.tag() == gimli::DW_TAG_member || child.tag() == gimli::DW_TAG_inheritance
-                })
-                .filter(|child| child.member_location().is_some())
-                .for_each(|child| {
-                    let class = child.class().unwrap().expand_type_defs();
+    let classes = load_classes(input, &options.load_options)?;
+    if let Some((index_path, index)) = index_path.zip(TypeIndex::new(&classes)) {
+        index.write(&index_path)?;
+    }
+    Ok(classes)
+}
 
-                    // TODO: Expand anonymous enums and structs
-                    let class_name = class.name().unwrap_or_else(|| "unknown_class".into());
+/// Print each class that matches the search filter, using the given
+/// output format and the requested order.  Without an order, classes
+/// printed as code follow their dependencies.  With `--recursive`, the
+/// classes that they depend on are printed as well.  With
+/// `--expand-depth`, embedded members are replaced by their own
+/// members before printing.  With `--collapse-templates`, the
+/// instantiation of each template whose members are shared by the most
+/// others is printed in place of the others, followed by a list of
+/// them, as described in `group_instantiations`.  Identical definitions are
+/// printed once, since the same class is typically defined in every
+/// compilation unit that uses it.  If compilation units disagree on
+/// the layout of a class, each variant is printed, and a warning
+/// listing where each variant was found is written to stderr.
+fn print_classes(
+    classes: impl Iterator<Item = ClassLayout>,
+    options: &Options,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut printer = format.printer(&options.format_options);
+    printer.header(&mut out)?;
 
-                    // TODO: Print base classes as base classes,
-                    // rather than as members.
-                    let name = if child.tag() == gimli::DW_TAG_inheritance {
-                        "_base_class".into()
-                    } else {
-                        child.name().unwrap_or_else(|| "unknown_name".into())
-                    };
+    let index = ClassIndex::new(classes);
+    let mut matching: Vec<_> = index
+        .iter()
+        .filter(|class| options.search_filter.matches(class, &index))
+        .unique_by(|class| {
+            (
+                distinct_binary(class, format),
+                class.qualified_name(),
+                class.layout_hash(),
+            )
+        })
+        .collect();
 
-                    // TODO: Check for the `DW_AT_bit_size` and
-                    // `DW_AT_bit_offset` fields, to see if this
-                    // member is part of a bitfield.
-                    let field_size = class.size_bytes().unwrap_or(0);
+    if options.recursive {
+        matching = index.with_dependencies(matching, options.recursive_pointers);
+    }
 
-                    let field_start = child.member_location().unwrap();
-                    let field_end = field_start + field_size;
+    warn_conflicting_layouts(&matching, format);
+    if let Some(sort) = options.sort {
+        matching.sort_by(|a, b| sort.compare(a, b));
+        if options.descending {
+            matching.reverse();
+        }
+    } else if format.generates_code() {
+        matching = index.dependency_order(matching);
+    }
+    let groups = if options.collapse_templates {
+        group_instantiations(matching)
+    } else {
+        matching
+            .into_iter()
+            .map(|class| (class, Vec::new()))
+            .collect()
+    };
+    for (class, others) in groups {
+        match options.expand_depth {
+            0 => printer.class(&mut out, class)?,
+            depth => printer.class(&mut out, &index.expand_members(class, depth))?,
+        }
+        if !others.is_empty() {
+            printer.instantiations(&mut out, class, &others)?;
+        }
+    }
 
-                    // TODO: Highlight the part of the structure that
-                    // matched the SearchFilter.
-                    println!(
-                        "    {class_name} {name}; \
-                         // {field_size} bytes, \
-                         {field_start}-{field_end}"
-                    );
-                });
+    printer.footer(&mut out)?;
+
+    Ok(())
+}
+
+/// Print the classes that match the search filter as each
+/// compilation unit is read, so that the first results appear before
+/// a large binary has been read in full.  The output is the same as
+/// for `print_classes`, and so this may only be used if the output is
+/// unsorted, and if neither the search filter nor the format needs to
+/// look up other classes.
+fn stream_classes(inputs: &[PathBuf], options: &Options) -> Result<(), Error> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut printer = options.format.printer(&options.format_options);
+    printer.header(&mut out)?;
 
-            println!("}};");
+    let no_classes = ClassIndex::new(std::iter::empty());
+    let mut printed: HashSet<(Option<String>, String, u64)> = HashSet::new();
+    let mut matching = Vec::new();
+    for input in inputs {
+        let binary = binary_name(input, inputs, options);
+        let mut result = Ok(());
+        let read = read_classes(input, &options.load_options, &mut |unit_classes| {
+            for class in unit_classes {
+                let class = ClassLayout {
+                    binary: binary.clone(),
+                    ..class
+                };
+                let is_new = result.is_ok()
+                    && options.search_filter.matches(&class, &no_classes)
+                    && printed.insert((
+                        distinct_binary(&class, options.format),
+                        class.qualified_name(),
+                        class.layout_hash(),
+                    ));
+                if is_new {
+                    result = printer
+                        .class(&mut out, &class)
+                        .and_then(|()| std::io::Write::flush(&mut out));
+                    matching.push(class);
+                }
+            }
         });
+        match read {
+            Err(err) if options.scanned_inputs.contains(input) => {
+                eprintln!("warning: skipping {}: {err}", input.display());
+            }
+            read => read?,
+        }
+        result?;
+    }
+
+    warn_conflicting_layouts(&matching.iter().collect::<Vec<_>>(), options.format);
+    printer.footer(&mut out)?;
 
     Ok(())
 }
 
-fn main() -> Result<(), Error> {
-    let cli_args = CommandLineInterface::parse();
+/// Group the instantiations of each template, keeping the order in
+/// which each template first appears.  Each group is represented by
+/// the instantiation whose members are shared by the most others, so
+/// that a specialization doesn't stand in for the general template,
+/// and is paired with the others.  Classes that aren't instantiations
+/// are each in a group of their own.
+fn group_instantiations(classes: Vec<&ClassLayout>) -> Vec<(&ClassLayout, Vec<&ClassLayout>)> {
+    let mut groups: Vec<Vec<&ClassLayout>> = Vec::new();
+    let mut templates: HashMap<_, usize> = HashMap::new();
+    for class in classes {
+        let Some(template_name) = class.template_name() else {
+            groups.push(vec![class]);
+            continue;
+        };
+        let key = (class.binary.clone(), class.namespace.clone(), template_name);
+        match templates.get(&key) {
+            Some(&i) => groups[i].push(class),
+            None => {
+                templates.insert(key, groups.len());
+                groups.push(vec![class]);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            let shared = |class: &ClassLayout| {
+                group
+                    .iter()
+                    .filter(|other| other.same_structure(class))
+                    .count()
+            };
+            // The maximum that comes last is returned, and so the
+            // first instantiation is preferred by searching in reverse.
+            let representative = (0..group.len())
+                .rev()
+                .max_by_key(|&i| shared(group[i]))
+                .unwrap();
+            let class = group.remove(representative);
+            (class, group)
+        })
+        .collect()
+}
 
-    let shared_obj_path = if let Some(path) = cli_args.shared_object_path {
-        path
+/// The binary that distinguishes a class from others of the same name,
+/// when printed in the given format.  Formats that define a name for
+/// each class, such as a type or a macro, can't define it again for
+/// each binary, so their classes aren't distinguished by binary, and
+/// a class found in several binaries with the same layout is printed
+/// once.
+fn distinct_binary(class: &ClassLayout, format: OutputFormat) -> Option<String> {
+    if format.defines_names() {
+        None
     } else {
-        let home_dir = std::env::var("HOME").map_err(|_| Error::NoHomeDirectoryFound)?;
-        let mut path: std::path::PathBuf = home_dir.into();
-        path.push(".steam");
-        path.push("steam");
-        path.push("steamapps");
-        path.push("common");
-        path.push("Stardew Valley");
-        path.push("libcoreclr.so");
-        path
-    };
+        class.binary.clone()
+    }
+}
 
-    let search_filter = SearchFilter {
-        class_name: cli_args.class_name,
-        base_class_name: cli_args.base_class_name,
-        contained_class_name: cli_args.contained_class_name,
-    };
+/// Warn about each class that has more than one layout among the
+/// classes to be printed, listing where each layout was found.
+fn warn_conflicting_layouts(classes: &[&ClassLayout], format: OutputFormat) {
+    classes
+        .iter()
+        .into_group_map_by(|class| (distinct_binary(class, format), class.qualified_name()))
+        .into_iter()
+        .filter(|(_, variants)| variants.len() > 1)
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .for_each(|((_, name), variants)| {
+            eprintln!("warning: {} different layouts of {name}", variants.len());
+            for variant in variants {
+                eprintln!(
+                    "    {} bytes, from {}",
+                    variant.size_bytes,
+                    variant.origin()
+                );
+            }
+        });
+}
+
+/// Read the classes from a file, which may be an object file with
+/// DWARF or BTF debug information, a standalone BTF file, or a PDB
+/// file.  If a separate debug file is found, as described in
+/// `debug_file::find_debug_file`, sections missing from the object
+/// file are read from it.
+fn load_classes(path: &Path, load_options: &LoadOptions) -> Result<Vec<ClassLayout>, Error> {
+    let mut classes = Vec::new();
+    read_classes(path, load_options, &mut |unit_classes| {
+        classes.extend(unit_classes)
+    })?;
+    Ok(classes)
+}
+
+/// Read the classes from a file, as for `load_classes`, passing them
+/// to `emit` as each compilation unit is read.
+fn read_classes(
+    path: &Path,
+    load_options: &LoadOptions,
+    emit: &mut dyn FnMut(Vec<ClassLayout>),
+) -> Result<(), Error> {
+    let shared_obj_bytes = FileContents::open(path, load_options.use_mmap)?;
 
-    let shared_obj_bytes = std::fs::read(&shared_obj_path)?;
-    let object = object::File::parse(&*shared_obj_bytes)?;
+    // Standalone BTF files, such as /sys/kernel/btf/vmlinux, are not
+    // wrapped in an object file.
+    if btf::is_btf(&shared_obj_bytes) {
+        return read_btf_classes(path, &shared_obj_bytes, load_options, emit);
+    }
+
+    // PDB files, as written by MSVC, hold only debug information.
+    if pdb::is_pdb(&shared_obj_bytes) {
+        let pdb = Pdb::parse(&shared_obj_bytes)?;
+        emit(pdb.classes().collect());
+        return Ok(());
+    }
+
+    // The system libraries of macOS are only found within the dyld
+    // shared cache, from which a single image is read.
+    let subcache_bytes = if dyld_cache::is_dyld_cache(&shared_obj_bytes) {
+        dyld_cache::subcache_paths(path, &shared_obj_bytes)?
+            .iter()
+            .map(|subcache_path| FileContents::open(subcache_path, load_options.use_mmap))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+    let object = if dyld_cache::is_dyld_cache(&shared_obj_bytes) {
+        let subcache_data: Vec<&[u8]> = subcache_bytes.iter().map(|bytes| &**bytes).collect();
+        dyld_cache::parse_image(
+            path,
+            &shared_obj_bytes,
+            &subcache_data,
+            load_options.dyld_image.as_deref(),
+        )?
+    } else {
+        object::File::parse(&*shared_obj_bytes)?
+    };
     let endian = if object.is_little_endian() {
         gimli::RunTimeEndian::Little
     } else {
         gimli::RunTimeEndian::Big
     };
 
-    let debug_bytes = object
-        .gnu_debuglink()?
-        .map(|(name, _crc)| {
-            let name = std::str::from_utf8(name).unwrap();
-            let relative_path = std::path::Path::new(name);
-            let full_path = shared_obj_path.with_file_name(relative_path);
+    let debug_path = debug_file::find_debug_file(path, &object, load_options)?;
+    let debug_bytes = debug_path
+        .as_ref()
+        .map(|debug_path| FileContents::open(debug_path, load_options.use_mmap))
+        .transpose()?;
 
-            full_path
+    // Executables built by MSVC keep their debug information in a
+    // separate PDB file, rather than in an object file.
+    if let Some(debug_bytes) = debug_bytes.as_ref().filter(|bytes| pdb::is_pdb(bytes)) {
+        let pdb = Pdb::parse(debug_bytes)?;
+        emit(pdb.classes().collect());
+        return Ok(());
+    }
+
+    let debug_obj = debug_bytes
+        .as_ref()
+        .map(|bytes| object::File::parse(&**bytes))
+        .transpose()?;
+    if let Some((debug_path, debug_obj)) = debug_path.as_ref().zip(debug_obj.as_ref()) {
+        if debug_obj.is_little_endian() != object.is_little_endian() {
+            return Err(Error::MismatchedEndianness(debug_path.clone()));
+        }
+    }
+
+    // Debug information processed by `dwz` refers to entries and
+    // strings in a supplementary file.
+    let debug_file = debug_path.as_deref().zip(debug_obj.as_ref());
+    let sup_bytes = debug_file::find_sup_file(path, &object, debug_file, load_options)?
+        .map(|sup_path| FileContents::open(&sup_path, load_options.use_mmap))
+        .transpose()?;
+    let sup_obj = sup_bytes
+        .as_ref()
+        .map(|bytes| object::File::parse(&**bytes))
+        .transpose()?;
+
+    // Objects without DWARF information may still have BTF
+    // information, as is common for kernels and eBPF-oriented
+    // binaries.
+    let has_dwarf = std::iter::once(&object)
+        .chain(debug_obj.as_ref())
+        .any(|obj| debug_section_by_name(obj, ".debug_info").is_some());
+    if !has_dwarf {
+        if let Some(section) = object.section_by_name(".BTF") {
+            let btf_bytes = section.uncompressed_data()?;
+            return read_btf_classes(path, &btf_bytes, load_options, emit);
+        }
+    }
+
+    let objects: Vec<_> = std::iter::once(&object).chain(debug_obj.as_ref()).collect();
+    let load_section = |name: &str| load_object_section(&objects, name);
+    let dwarf_sections = gimli::DwarfSections::load(|id| load_section(id.name()))?;
+    let sup_sections = sup_obj
+        .as_ref()
+        .map(|sup_obj| gimli::DwarfSections::load(|id| load_object_section(&[sup_obj], id.name())))
+        .transpose()?;
+    let dwarf = match &sup_sections {
+        Some(sup_sections) => {
+            dwarf_sections.borrow_with_sup(sup_sections, |section| section_reader(section, endian))
+        }
+        None => dwarf_sections.borrow(|section| section_reader(section, endian)),
+    };
+
+    // A cached index, or the binary's accelerator tables, allow the
+    // requested classes to be found without reading every compilation
+    // unit.  Since both are optional, every unit is read if they are
+    // absent or unreadable.
+    let cached_index = if load_options.lookup_names.is_empty() {
+        None
+    } else {
+        type_index::index_path(&object)
+            .ok()
+            .flatten()
+            .and_then(|index_path| TypeIndex::read(&index_path).ok().flatten())
+    };
+    let lookup = if load_options.lookup_names.is_empty() {
+        None
+    } else if let Some(index) = cached_index {
+        Some(index.lookup(&load_options.lookup_names))
+    } else {
+        let names = &load_options.lookup_names;
+        let debug_names = load_section(".debug_names")?;
+        let debug_pubtypes = load_section(".debug_pubtypes")?;
+        let lookup = if !debug_names.0.is_empty() {
+            Some(name_index::lookup_debug_names(
+                section_reader(&debug_names, endian),
+                &dwarf.debug_str,
+                names,
+            ))
+        } else if !debug_pubtypes.0.is_empty() {
+            Some(name_index::lookup_pubtypes(
+                &section_reader(&debug_pubtypes, endian).into(),
+                names,
+            ))
+        } else {
+            None
+        };
+        lookup.and_then(|lookup| {
+            lookup
+                .map_err(|err| eprintln!("warning: ignoring accelerator tables: {err}"))
+                .ok()
         })
-        .filter(|path| std::path::Path::exists(path))
-        .map(|path| std::fs::read(path).unwrap());
+    };
 
-    let debug_obj = debug_bytes.as_ref().map(|bytes| {
-        let dbg_obj = object::File::parse(bytes.as_slice()).unwrap();
-        assert_eq!(object.is_little_endian(), dbg_obj.is_little_endian());
-        dbg_obj
-    });
+    // The ivars of Objective-C classes are recorded at offset 0, and
+    // are moved to the offsets stored in the binary.
+    let ivar_offsets = objc::IvarOffsets::read(&object)?;
+    if ivar_offsets.is_empty() {
+        collect_classes(&dwarf, load_options, lookup.as_ref(), emit)?;
+    } else {
+        collect_classes(&dwarf, load_options, lookup.as_ref(), &mut |mut classes| {
+            for class in &mut classes {
+                ivar_offsets.apply(class);
+            }
+            emit(classes)
+        })?;
+    }
 
-    let dwarf_sections = gimli::DwarfSections::load(|id| -> Result<_, Error> {
-        let name = id.name();
-        let data = object
-            .section_by_name(name)
-            .or_else(|| {
-                debug_obj
-                    .as_ref()
-                    .map(|obj| obj.section_by_name(name))
-                    .flatten()
-            })
-            .map(|section| -> Result<_, Error> {
-                Ok((
-                    section.uncompressed_data()?,
-                    RelocationMap(section.relocation_map()?),
-                ))
-            })
-            .transpose()?
-            .unwrap_or_else(Default::default);
-        Ok(data)
+    // With split DWARF, the units in the binary are only skeletons,
+    // and the classes are in the `.dwo` file named by each skeleton.
+    for dwo_path in dwo_paths(&dwarf, load_options)? {
+        if let Err(err) = read_dwo_classes(&dwo_path, endian, load_options, emit) {
+            eprintln!("warning: skipping {}: {err}", dwo_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the paths of the `.dwo` files named by the skeleton units
+/// of split DWARF, in the order of the units.  A relative name is
+/// relative to the unit's compilation directory, and the path is
+/// resolved within the `--sysroot`, as are other paths recorded by
+/// the target's toolchain.
+fn dwo_paths<R: Reader>(
+    dwarf: &Dwarf<R>,
+    load_options: &LoadOptions,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut dwo_paths = Vec::new();
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let is_skeleton = match header.type_() {
+            gimli::UnitType::Skeleton(_) => true,
+            gimli::UnitType::Compilation => header.version() < 5,
+            _ => false,
+        };
+        if !is_skeleton {
+            continue;
+        }
+        let unit = dwarf.unit(header)?;
+        let Some(dwo_name) = unit.dwo_name()? else {
+            continue;
+        };
+        let dwo_name = dwarf.attr_string(&unit, dwo_name)?;
+        let dwo_name = Path::new(&*dwo_name.to_string_lossy()?).to_path_buf();
+        let comp_dir = unit
+            .comp_dir
+            .as_ref()
+            .map(|comp_dir| comp_dir.to_string_lossy().map(|dir| PathBuf::from(&*dir)))
+            .transpose()?;
+        let dwo_path = load_options.target_path(match comp_dir {
+            Some(comp_dir) => comp_dir.join(dwo_name),
+            None => dwo_name,
+        });
+        if !dwo_paths.contains(&dwo_path) {
+            dwo_paths.push(dwo_path);
+        }
+    }
+    Ok(dwo_paths)
+}
+
+/// Read the classes from a `.dwo` file of split DWARF, passing them to
+/// `emit`.  Type information within the file doesn't refer to the
+/// addresses held by the binary, and so it is read on its own.
+fn read_dwo_classes(
+    dwo_path: &Path,
+    endian: gimli::RunTimeEndian,
+    load_options: &LoadOptions,
+    emit: &mut dyn FnMut(Vec<ClassLayout>),
+) -> Result<(), Error> {
+    let dwo_bytes = FileContents::open(dwo_path, load_options.use_mmap)?;
+    let dwo_obj = object::File::parse(&*dwo_bytes)?;
+    if dwo_obj.is_little_endian() != (endian == gimli::RunTimeEndian::Little) {
+        return Err(Error::MismatchedEndianness(dwo_path.to_path_buf()));
+    }
+    let dwarf_sections = gimli::DwarfSections::load(|id| match id.dwo_name() {
+        Some(name) => load_object_section(&[&dwo_obj], name),
+        None => Ok(Default::default()),
     })?;
+    let mut dwarf = dwarf_sections.borrow(|section| section_reader(section, endian));
+    dwarf.file_type = gimli::DwarfFileType::Dwo;
+    collect_classes(&dwarf, load_options, None, emit)
+}
+
+/// Read the classes from the BTF of a file, passing them to `emit`.
+/// The BTF of a kernel module extends the BTF of the kernel, which is
+/// read from either a standalone BTF file or the `.BTF` section of an
+/// object file.
+fn read_btf_classes(
+    path: &Path,
+    btf_bytes: &[u8],
+    load_options: &LoadOptions,
+    emit: &mut dyn FnMut(Vec<ClassLayout>),
+) -> Result<(), Error> {
+    let base_path = kernel::btf_base(path, load_options);
+    let base_file = base_path
+        .as_ref()
+        .map(|base_path| FileContents::open(base_path, load_options.use_mmap))
+        .transpose()?;
+    let base_bytes = match &base_file {
+        Some(bytes) if !btf::is_btf(bytes) => Some(
+            object::File::parse(&**bytes)?
+                .section_by_name(".BTF")
+                .ok_or_else(|| Error::InvalidBtf("base file has no .BTF section".into()))?
+                .uncompressed_data()?,
+        ),
+        Some(bytes) => Some(std::borrow::Cow::Borrowed(&**bytes)),
+        None => None,
+    };
+    let base = base_bytes.as_deref().map(Btf::parse).transpose()?;
+    let btf = Btf::parse_split(btf_bytes, base)?;
+    emit(btf.classes().collect());
+    Ok(())
+}
+
+/// Load a section from the first of the `objects` that contains it,
+/// along with its relocations.  Missing sections are empty.
+fn load_object_section<'data>(
+    objects: &[&object::File<'data>],
+    name: &str,
+) -> Result<(std::borrow::Cow<'data, [u8]>, RelocationMap), Error> {
+    let data = objects
+        .iter()
+        .find_map(|obj| debug_section_by_name(obj, name))
+        .map(|section| -> Result<_, Error> {
+            Ok((
+                debug_section_data(&section)?,
+                RelocationMap(section.relocation_map()?),
+            ))
+        })
+        .transpose()?
+        .unwrap_or_else(Default::default);
+    Ok(data)
+}
+
+/// Returns the section with the given name, such as `.debug_info`,
+/// falling back to the `.zdebug_` name that older toolchains give to
+/// zlib-compressed debug sections.  The `object` crate only falls back
+/// for ELF and Mach-O files, but MinGW's `--compress-debug-sections`
+/// names the sections of PE files the same way.
+fn debug_section_by_name<'data, 'file, R: object::ReadRef<'data>>(
+    object: &'file object::File<'data, R>,
+    name: &str,
+) -> Option<object::Section<'data, 'file, R>> {
+    object.section_by_name(name).or_else(|| {
+        let zdebug_name = format!(".zdebug_{}", name.strip_prefix(".debug_")?);
+        object.section_by_name(&zdebug_name)
+    })
+}
+
+/// Returns the uncompressed contents of a debug section.  A section
+/// named `.zdebug_*` holds `ZLIB`, followed by the uncompressed size
+/// as a big-endian 64-bit integer, followed by the zlib stream.
+fn debug_section_data<'data, R: object::ReadRef<'data>>(
+    section: &object::Section<'data, '_, R>,
+) -> Result<std::borrow::Cow<'data, [u8]>, Error> {
+    if !section
+        .name()
+        .is_ok_and(|name| name.starts_with(".zdebug_"))
+    {
+        return Ok(section.uncompressed_data()?);
+    }
+    let data = section.data()?;
+    let Some(header) = data
+        .strip_prefix(b"ZLIB")
+        .filter(|header| header.len() >= 8)
+    else {
+        return Ok(section.uncompressed_data()?);
+    };
+    let compressed = object::CompressedData {
+        format: object::CompressionFormat::Zlib,
+        data: &header[8..],
+        uncompressed_size: u64::from_be_bytes(header[..8].try_into().unwrap()),
+    };
+    Ok(compressed.decompress()?)
+}
 
-    let dwarf = dwarf_sections.borrow(|section: &(_, _)| {
-        let slice = gimli::EndianSlice::new(std::borrow::Cow::as_ref(&section.0), endian);
-        gimli::RelocateReader::new(slice, &section.1)
+/// Returns a reader for a section loaded from an object file, which
+/// applies the section's relocations.
+fn section_reader<'a>(
+    section: &'a (std::borrow::Cow<[u8]>, RelocationMap),
+    endian: gimli::RunTimeEndian,
+) -> gimli::RelocateReader<gimli::EndianSlice<'a, gimli::RunTimeEndian>, &'a RelocationMap> {
+    let slice = gimli::EndianSlice::new(std::borrow::Cow::as_ref(&section.0), endian);
+    gimli::RelocateReader::new(slice, &section.1)
+}
+
+fn main() -> Result<(), Error> {
+    let cli_args = CommandLineInterface::parse();
+
+    let search_filter = SearchFilter {
+        class_names: cli_args.class_names,
+        base_class_names: cli_args.base_class_names,
+        base_class_recursive: cli_args.base_class_recursive,
+        contained_class_names: cli_args.contained_class_names,
+        contains_recursive: cli_args.contains_recursive,
+        contains_pointers: cli_args.contains_pointers,
+        member_names: cli_args.member_names,
+        member_offset: cli_args.member_offset,
+        member_size: cli_args.member_size,
+        namespaces: cli_args.namespaces,
+        unit_patterns: cli_args.unit_patterns,
+        decl_file_patterns: cli_args.decl_file_patterns,
+        declared_in: cli_args.declared_in,
+        size: cli_args.size,
+        min_size: cli_args.min_size,
+        max_size: cli_args.max_size,
+        min_padding: cli_args.min_padding,
+        has_vtable: cli_args.has_vtable,
+        linkage_names: cli_args.linkage_names,
+        exclude_patterns: cli_args.exclude_patterns,
+        ignore_case: cli_args.ignore_case,
+    };
+
+    let format_options = FormatOptions {
+        macro_prefix: cli_args.macro_prefix,
+        show_cu: cli_args.show_cu,
+        show_decl: cli_args.show_decl,
+        show_member_decl: cli_args.show_member_decl,
+        with_size: cli_args.with_size,
+        header: HeaderScaffold {
+            include_guard: cli_args.include_guard,
+            prefix: cli_args
+                .header_prefix
+                .map(std::fs::read_to_string)
+                .transpose()?,
+        },
+    };
+
+    let format = if cli_args.list {
+        OutputFormat::List
+    } else if cli_args.sizes_only {
+        OutputFormat::Sizes
+    } else {
+        cli_args.format
+    };
+
+    // Classes can only be looked up by name if the output doesn't
+    // depend on any other class, such as a base class or member type,
+    // and if the names are compared exactly.  Decoding an object in
+    // memory looks up the class of each member, `--with-deps` looks up
+    // the definition of each declared member type, and the C format
+    // notes the member types that no class defines.
+    let can_lookup_names = cli_args.command.is_none()
+        && cli_args.address.is_none()
+        && !cli_args.with_deps
+        && !format.reports_incomplete_types()
+        && !search_filter.ignore_case
+        && !search_filter.base_class_recursive
+        && !search_filter.contains_recursive
+        && !search_filter.has_vtable
+        && cli_args.expand_depth == 0
+        && !cli_args.recursive
+        && !search_filter
+            .class_names
+            .iter()
+            .any(|name| name.contains(['*', '?']));
+
+    // Debug packages are installed within the target's filesystem,
+    // so the default debug root follows `--sysroot`.
+    let debug_root = cli_args.debug_root.unwrap_or_else(|| {
+        cli_args
+            .sysroot
+            .as_deref()
+            .unwrap_or(Path::new("/"))
+            .join("usr/lib/debug")
     });
+    let load_options = LoadOptions {
+        jobs: cli_args.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|jobs| jobs.get())
+                .unwrap_or(1)
+        }),
+        use_mmap: !cli_args.no_mmap,
+        debug_root,
+        sysroot: cli_args.sysroot,
+        debug_file: cli_args
+            .debug_file
+            .as_deref()
+            .map(download::local_path)
+            .transpose()?,
+        debug_dirs: cli_args.debug_dirs,
+        ignore_debuglink_crc: cli_args.ignore_debuglink_crc,
+        dsym: cli_args.dsym,
+        btf_base: cli_args.btf_base,
+        dyld_image: cli_args.dyld_image,
+        lookup_names: if can_lookup_names {
+            search_filter.class_names.clone()
+        } else {
+            Vec::new()
+        },
+    };
 
-    dump_file(&dwarf, &search_filter)?;
+    // Directories given by `--scan` add every binary within them to
+    // the inputs, `--pid` adds every binary mapped by the process, and
+    // `--kernel` adds the running kernel.  Without any inputs, the
+    // default is the Stardew Valley runtime.  Inputs given as URLs are
+    // downloaded first.
+    let mut shared_obj_paths = cli_args
+        .shared_object_paths
+        .iter()
+        .map(|path| download::local_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    if cli_args.kernel {
+        shared_obj_paths.push(kernel::find_vmlinux(&load_options)?);
+    }
+    let mut scanned_inputs = Vec::new();
+    for dir in &cli_args.scan_dirs {
+        let binaries = scan::find_binaries(dir, &load_options);
+        scanned_inputs.extend(binaries.iter().cloned());
+        shared_obj_paths.extend(binaries);
+    }
+    if let Some(pid) = cli_args.pid {
+        for path in process::mapped_binaries(pid)? {
+            if !shared_obj_paths.contains(&path) {
+                shared_obj_paths.push(path);
+            }
+        }
+    }
+    if shared_obj_paths.is_empty()
+        && cli_args.scan_dirs.is_empty()
+        && cli_args.pid.is_none()
+        && !cli_args.kernel
+    {
+        let home_dir = std::env::var("HOME").map_err(|_| Error::NoHomeDirectoryFound)?;
+        let mut path: std::path::PathBuf = home_dir.into();
+        path.push(".steam");
+        path.push("steam");
+        path.push("steamapps");
+        path.push("common");
+        path.push("Stardew Valley");
+        path.push("libcoreclr.so");
+        shared_obj_paths.push(path);
+    }
+    if cli_args.with_deps {
+        let mut dependencies: Vec<PathBuf> = Vec::new();
+        for path in &shared_obj_paths {
+            for dependency in dependencies::find_dependencies(path, &load_options)? {
+                if !shared_obj_paths.contains(&dependency) && !dependencies.contains(&dependency) {
+                    dependencies.push(dependency);
+                }
+            }
+        }
+        shared_obj_paths.extend(dependencies);
+    }
 
-    Ok(())
+    // Only the text format lists the other instantiations of a
+    // collapsed template, which every other format would omit.
+    if cli_args.collapse_templates && format != OutputFormat::Text {
+        <CommandLineInterface as clap::CommandFactory>::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--collapse-templates can only be used with the text format",
+            )
+            .exit();
+    }
+
+    let options = Options {
+        command: cli_args.command,
+        search_filter,
+        sort: cli_args.sort,
+        descending: cli_args.descending,
+        format,
+        format_options,
+        load_options,
+        core_path: cli_args.core_path,
+        pid: cli_args.pid,
+        address: cli_args.address.map(|address| address as u64),
+        with_deps: cli_args.with_deps,
+        expand_depth: cli_args.expand_depth,
+        recursive: cli_args.recursive,
+        recursive_pointers: cli_args.recursive_pointers,
+        collapse_templates: cli_args.collapse_templates,
+        scanned_inputs,
+    };
+
+    process_classes(&shared_obj_paths, &options)
 }
@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use gimli::{Reader, ReaderOffset as _};
+
+/// The entries found by looking up class names in an accelerator
+/// table, such as `.debug_names` or `.debug_pubtypes`.  Offsets are
+/// relative to the start of .debug_info.
+#[derive(Default)]
+pub struct NameLookup {
+    /// The start of every compilation unit covered by the table.
+    /// Units that aren't listed, such as those compiled without
+    /// `-gpubnames`, must still be scanned in full.
+    pub units: HashSet<u64>,
+
+    /// The class, struct, and typedef entries whose name matched.
+    pub entries: HashSet<u64>,
+}
+
+/// Returns true if a name from an accelerator table may refer to the
+/// requested class.  GCC's `.debug_pubtypes` lists qualified names,
+/// while `.debug_names` lists the unqualified `DW_AT_name`, so only
+/// the innermost component is compared.  This may find more entries
+/// than requested, which are then removed by the search filter.
+pub fn is_candidate(name: &str, requested: &[String]) -> bool {
+    let name = unqualified(name);
+    requested
+        .iter()
+        .any(|requested| unqualified(requested) == name)
+}
+
+/// Returns the innermost component of a `::`-separated name, ignoring
+/// any `::` within template arguments.
+pub fn unqualified(name: &str) -> &str {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = name.as_bytes();
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => start = i + 2,
+            _ => {}
+        }
+    }
+    &name[start..]
+}
+
+/// Look up the requested class names in a `.debug_pubtypes` section.
+pub fn lookup_pubtypes<R: Reader>(
+    debug_pubtypes: &gimli::DebugPubTypes<R>,
+    requested: &[String],
+) -> gimli::Result<NameLookup> {
+    let mut lookup = NameLookup::default();
+    let mut entries = debug_pubtypes.items();
+    while let Some(entry) = entries.next()? {
+        let unit_start = entry.unit_header_offset().0.into_u64();
+        lookup.units.insert(unit_start);
+        if is_candidate(&entry.name().to_string_lossy()?, requested) {
+            lookup
+                .entries
+                .insert(unit_start + entry.die_offset().0.into_u64());
+        }
+    }
+    Ok(lookup)
+}
+
+/// Look up the requested class names in a DWARF 5 `.debug_names`
+/// section, which may hold one name index per compilation unit, or a
+/// single index covering several units.  Entries in type units are
+/// skipped, since type units are not read.
+pub fn lookup_debug_names<R: Reader>(
+    mut debug_names: R,
+    debug_str: &gimli::DebugStr<R>,
+    requested: &[String],
+) -> gimli::Result<NameLookup> {
+    let mut lookup = NameLookup::default();
+    while !debug_names.is_empty() {
+        let (length, format) = debug_names.read_initial_length()?;
+        let mut index = debug_names.split(length)?;
+        NameIndex::parse(&mut index, format)?.lookup(debug_str, requested, &mut lookup)?;
+    }
+    Ok(lookup)
+}
+
+/// A single name index within `.debug_names`.
+struct NameIndex<R: Reader> {
+    format: gimli::Format,
+
+    /// The start of each compilation unit covered by the index.
+    comp_units: Vec<u64>,
+
+    /// For each name, its offset within .debug_str.
+    name_offsets: Vec<R::Offset>,
+
+    /// For each name, the offset of its entries within the entry
+    /// pool.
+    entry_offsets: Vec<R::Offset>,
+
+    abbreviations: Vec<Abbreviation>,
+
+    entry_pool: R,
+}
+
+/// Describes the tag and attributes of the entries in a name index
+/// that use the abbreviation's code.
+struct Abbreviation {
+    code: u64,
+    tag: gimli::DwTag,
+
+    /// Pairs of a `DW_IDX_*` index attribute and its form.
+    attributes: Vec<(u16, gimli::DwForm)>,
+}
+
+impl<R: Reader> NameIndex<R> {
+    /// Parse the header and tables of a name index, following its
+    /// initial length.
+    fn parse(index: &mut R, format: gimli::Format) -> gimli::Result<Self> {
+        let version = index.read_u16()?;
+        if version != 5 {
+            return Err(gimli::Error::UnknownVersion(version.into()));
+        }
+        let _padding = index.read_u16()?;
+        let comp_unit_count = index.read_u32()?;
+        let local_type_unit_count = index.read_u32()?;
+        let foreign_type_unit_count = index.read_u32()?;
+        let bucket_count = index.read_u32()?;
+        let name_count = index.read_u32()?;
+        let abbrev_table_size = index.read_u32()?;
+        let augmentation_string_size = index.read_u32()?;
+        index.skip(R::Offset::from_u32(augmentation_string_size))?;
+
+        let comp_units = (0..comp_unit_count)
+            .map(|_| Ok(index.read_offset(format)?.into_u64()))
+            .collect::<gimli::Result<_>>()?;
+        for _ in 0..local_type_unit_count {
+            index.read_offset(format)?;
+        }
+        for _ in 0..foreign_type_unit_count {
+            index.read_u64()?;
+        }
+
+        // The hash table is only needed to avoid comparing every
+        // name, so it is skipped.
+        let hash_table_size = if bucket_count > 0 {
+            4 * (u64::from(bucket_count) + u64::from(name_count))
+        } else {
+            0
+        };
+        index.skip(R::Offset::from_u64(hash_table_size)?)?;
+
+        let name_offsets = (0..name_count)
+            .map(|_| index.read_offset(format))
+            .collect::<gimli::Result<_>>()?;
+        let entry_offsets = (0..name_count)
+            .map(|_| index.read_offset(format))
+            .collect::<gimli::Result<_>>()?;
+
+        let mut abbrev_table = index.split(R::Offset::from_u32(abbrev_table_size))?;
+        let mut abbreviations = Vec::new();
+        loop {
+            let code = abbrev_table.read_uleb128()?;
+            if code == 0 {
+                break;
+            }
+            let tag = gimli::DwTag(abbrev_table.read_uleb128_u16()?);
+            let mut attributes = Vec::new();
+            loop {
+                let idx = abbrev_table.read_uleb128_u16()?;
+                let form = gimli::DwForm(abbrev_table.read_uleb128_u16()?);
+                if idx == 0 && form.0 == 0 {
+                    break;
+                }
+                attributes.push((idx, form));
+            }
+            abbreviations.push(Abbreviation {
+                code,
+                tag,
+                attributes,
+            });
+        }
+
+        Ok(Self {
+            format,
+            comp_units,
+            name_offsets,
+            entry_offsets,
+            abbreviations,
+            entry_pool: index.clone(),
+        })
+    }
+
+    /// Add the compilation units covered by this index, and the class
+    /// entries whose name matches, to the lookup.
+    fn lookup(
+        &self,
+        debug_str: &gimli::DebugStr<R>,
+        requested: &[String],
+        lookup: &mut NameLookup,
+    ) -> gimli::Result<()> {
+        lookup.units.extend(&self.comp_units);
+
+        for (name_offset, entry_offset) in self.name_offsets.iter().zip(&self.entry_offsets) {
+            let name = debug_str.get_str(gimli::DebugStrOffset(*name_offset))?;
+            if !is_candidate(&name.to_string_lossy()?, requested) {
+                continue;
+            }
+
+            let mut entries = self.entry_pool.clone();
+            entries.skip(*entry_offset)?;
+            loop {
+                let code = entries.read_uleb128()?;
+                if code == 0 {
+                    break;
+                }
+                let Some(abbreviation) = self
+                    .abbreviations
+                    .iter()
+                    .find(|abbreviation| abbreviation.code == code)
+                else {
+                    return Err(gimli::Error::UnknownAbbreviation(code));
+                };
+
+                let mut unit_index = None;
+                let mut die_offset = None;
+                for &(idx, form) in &abbreviation.attributes {
+                    let value = self.read_value(&mut entries, form)?;
+                    match gimli::DwIdx(idx) {
+                        gimli::DW_IDX_compile_unit => unit_index = Some(value),
+                        // Type units are numbered after the
+                        // compilation units, and so are never found
+                        // in `comp_units`.
+                        gimli::DW_IDX_type_unit => {
+                            unit_index = Some(self.comp_units.len() as u64 + value)
+                        }
+                        gimli::DW_IDX_die_offset => die_offset = Some(value),
+                        _ => {}
+                    }
+                }
+
+                let is_class = matches!(
+                    abbreviation.tag,
+                    gimli::DW_TAG_class_type
+                        | gimli::DW_TAG_structure_type
+                        | gimli::DW_TAG_interface_type
+                        | gimli::DW_TAG_union_type
+                        | gimli::DW_TAG_typedef
+                );
+                // An index that covers a single compilation unit may
+                // omit the unit from each entry.
+                let unit_index = unit_index.or((self.comp_units.len() == 1).then_some(0));
+                let unit_start = unit_index.and_then(|i| self.comp_units.get(i as usize));
+                if let (true, Some(unit_start), Some(die_offset)) =
+                    (is_class, unit_start, die_offset)
+                {
+                    lookup.entries.insert(unit_start + die_offset);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the value of an index attribute.  Only the forms that may
+    /// hold a unit index, DIE offset, or flag are supported.
+    fn read_value(&self, entries: &mut R, form: gimli::DwForm) -> gimli::Result<u64> {
+        let value = match form {
+            gimli::DW_FORM_flag_present => 1,
+            gimli::DW_FORM_data1 | gimli::DW_FORM_ref1 | gimli::DW_FORM_flag => {
+                entries.read_u8()?.into()
+            }
+            gimli::DW_FORM_data2 | gimli::DW_FORM_ref2 => entries.read_u16()?.into(),
+            gimli::DW_FORM_data4 | gimli::DW_FORM_ref4 => entries.read_u32()?.into(),
+            gimli::DW_FORM_data8 | gimli::DW_FORM_ref8 | gimli::DW_FORM_ref_sig8 => {
+                entries.read_u64()?
+            }
+            gimli::DW_FORM_udata | gimli::DW_FORM_ref_udata => entries.read_uleb128()?,
+            gimli::DW_FORM_sdata => entries.read_sleb128()? as u64,
+            gimli::DW_FORM_sec_offset => entries.read_offset(self.format)?.into_u64(),
+            _ => return Err(gimli::Error::UnknownForm(form)),
+        };
+        Ok(value)
+    }
+}
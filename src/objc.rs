@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use object::{Object as _, ObjectSection as _, ObjectSymbol as _};
+
+use crate::errors::Error;
+use crate::layout::ClassLayout;
+
+/// The prefix of the symbols that hold the runtime offset of each
+/// Objective-C instance variable, as `OBJC_IVAR_$_Class.ivar`.
+const IVAR_OFFSET_PREFIX: &str = "OBJC_IVAR_$_";
+
+/// The offsets of the Objective-C instance variables defined by a
+/// binary.  With the non-fragile ABI used on all current Apple
+/// platforms, the offset of an ivar isn't known until the class is
+/// loaded, since the superclass may have grown, so clang records
+/// every ivar at offset 0 in the debug information.  The offset that
+/// the class was built with is instead stored in a variable named
+/// after the ivar, which the runtime updates in place.
+#[derive(Default)]
+pub struct IvarOffsets {
+    /// The offset of each ivar, keyed by `Class.ivar`.
+    offsets: HashMap<String, usize>,
+}
+
+impl IvarOffsets {
+    /// Read the ivar offset variables of a binary.  Each variable is
+    /// an `int` on arm64 and a `long` elsewhere, so only the first
+    /// four bytes are read, which hold the offset in either case on
+    /// little-endian targets.
+    pub fn read(object: &object::File) -> Result<Self, Error> {
+        let mut offsets = HashMap::new();
+        for symbol in object.symbols() {
+            let Ok(name) = symbol.name() else {
+                continue;
+            };
+            let name = name.strip_prefix('_').unwrap_or(name);
+            let Some(ivar) = name.strip_prefix(IVAR_OFFSET_PREFIX) else {
+                continue;
+            };
+            let Some(section) = symbol
+                .section_index()
+                .and_then(|index| object.section_by_index(index).ok())
+            else {
+                continue;
+            };
+            let Some(bytes) = section.data_range(symbol.address(), 4)? else {
+                continue;
+            };
+            let bytes = bytes.try_into().unwrap();
+            let offset = if object.is_little_endian() {
+                u32::from_le_bytes(bytes)
+            } else {
+                u32::from_be_bytes(bytes)
+            };
+            offsets.insert(ivar.to_string(), offset as usize);
+        }
+        Ok(Self { offsets })
+    }
+
+    /// Returns true if the binary defines no Objective-C ivars.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Move the ivars of an Objective-C class to their offsets from
+    /// the binary.  The class is made large enough to hold its last
+    /// ivar, since its recorded size may also have been computed
+    /// without the superclass.  Classes without ivar offsets are
+    /// unchanged.
+    pub fn apply(&self, class: &mut ClassLayout) {
+        for member in class
+            .members
+            .iter_mut()
+            .filter(|member| !member.is_base_class)
+        {
+            let key = format!("{}.{}", class.name, member.name);
+            if let Some(&offset) = self.offsets.get(&key) {
+                member.offset = offset;
+            }
+        }
+        let end = class.members.iter().map(|member| member.end()).max();
+        class.size_bytes = class.size_bytes.max(end.unwrap_or(0));
+    }
+}
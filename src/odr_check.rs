@@ -0,0 +1,69 @@
+use itertools::Itertools as _;
+
+use crate::errors::Error;
+use crate::layout::{ClassLayout, MemberLayout};
+
+/// Report every class whose definitions in different compilation
+/// units disagree on the size or member layout, which violates the
+/// One Definition Rule.  For each such class, every distinct layout
+/// is listed along with where it was found, followed by the members
+/// that don't appear identically in all layouts.
+pub fn check_odr(classes: impl Iterator<Item = ClassLayout>) -> Result<(), Error> {
+    let violations: Vec<_> = classes
+        .into_group_map_by(|class| class.qualified_name())
+        .into_iter()
+        .map(|(name, definitions)| {
+            // Variants of the same size are listed in the order in
+            // which they were first found, rather than in the order of
+            // the hash map that groups them.
+            let variants: Vec<_> = definitions
+                .into_iter()
+                .enumerate()
+                .into_group_map_by(|(_, class)| class.layout_hash())
+                .into_values()
+                .sorted_by_key(|variant| (variant[0].1.size_bytes, variant[0].0))
+                .map(|variant| variant.into_iter().map(|(_, class)| class).collect_vec())
+                .collect();
+            (name, variants)
+        })
+        .filter(|(_, variants)| variants.len() > 1)
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .collect();
+
+    for (name, variants) in &violations {
+        println!("{name}: {} different layouts", variants.len());
+
+        let is_shared = |member: &MemberLayout| {
+            variants.iter().all(|variant| {
+                variant[0].members.iter().any(|other| {
+                    other.name == member.name
+                        && other.type_name == member.type_name
+                        && other.offset == member.offset
+                        && other.size_bytes == member.size_bytes
+                })
+            })
+        };
+
+        for definitions in variants {
+            let class = &definitions[0];
+            print!("    {} bytes, from {}", class.size_bytes, class.origin());
+            match definitions.len() {
+                1 => println!(),
+                2 => println!(" and 1 other compilation unit"),
+                n => println!(" and {} other compilation units", n - 1),
+            }
+            for member in class.members.iter().filter(|member| !is_shared(member)) {
+                println!(
+                    "        {} {} at 0x{:x}, {} bytes",
+                    member.type_name, member.name, member.offset, member.size_bytes
+                );
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        println!("No ODR violations found");
+    }
+
+    Ok(())
+}
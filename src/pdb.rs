@@ -0,0 +1,924 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::layout::{
+    Bitfield, ClassKind, ClassLayout, Enumerator, Language, MemberLayout, SizeKind, TypeKind,
+};
+
+const MSF_MAGIC: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+
+/// The fixed stream indices of a PDB file.
+const PDB_STREAM: usize = 1;
+const TPI_STREAM: usize = 2;
+
+/// The first index used by records in the TPI stream.  Smaller
+/// indices are primitive types, which have no record.
+const FIRST_TYPE_INDEX: u32 = 0x1000;
+
+const LF_MODIFIER: u16 = 0x1001;
+const LF_POINTER: u16 = 0x1002;
+const LF_FIELDLIST: u16 = 0x1203;
+const LF_BITFIELD: u16 = 0x1205;
+const LF_BCLASS: u16 = 0x1400;
+const LF_VBCLASS: u16 = 0x1401;
+const LF_IVBCLASS: u16 = 0x1402;
+const LF_INDEX: u16 = 0x1404;
+const LF_VFUNCTAB: u16 = 0x1409;
+const LF_ENUMERATE: u16 = 0x1502;
+const LF_ARRAY: u16 = 0x1503;
+const LF_CLASS: u16 = 0x1504;
+const LF_STRUCTURE: u16 = 0x1505;
+const LF_UNION: u16 = 0x1506;
+const LF_ENUM: u16 = 0x1507;
+const LF_MEMBER: u16 = 0x150d;
+const LF_STMEMBER: u16 = 0x150e;
+const LF_METHOD: u16 = 0x150f;
+const LF_NESTTYPE: u16 = 0x1510;
+const LF_ONEMETHOD: u16 = 0x1511;
+const LF_INTERFACE: u16 = 0x1519;
+
+const LF_CHAR: u16 = 0x8000;
+const LF_SHORT: u16 = 0x8001;
+const LF_USHORT: u16 = 0x8002;
+const LF_LONG: u16 = 0x8003;
+const LF_ULONG: u16 = 0x8004;
+const LF_QUADWORD: u16 = 0x8009;
+const LF_UQUADWORD: u16 = 0x800a;
+
+/// Set in the property field of a class or enum that is only
+/// declared, and defined by a later record.
+const PROPERTY_FORWARD_REF: u16 = 0x80;
+
+/// Set in the property field of a class or union that has a
+/// decorated unique name following its name.
+const PROPERTY_HAS_UNIQUE_NAME: u16 = 0x200;
+
+/// The pointer modes, from bits 5-7 of a pointer's attributes.
+const POINTER_MODE_LVALUE_REF: u32 = 1;
+const POINTER_MODE_RVALUE_REF: u32 = 4;
+
+/// Returns true if the bytes start with the magic number of an MSF
+/// 7.0 container, which holds the streams of a PDB file.
+pub fn is_pdb(bytes: &[u8]) -> bool {
+    bytes.starts_with(MSF_MAGIC)
+}
+
+/// A single data member or base class of a `LF_FIELDLIST`.
+struct PdbMember {
+    name: Option<String>,
+    type_index: u32,
+    offset: u64,
+    is_base_class: bool,
+}
+
+/// A single decoded type record.  Only the information needed to
+/// produce a `ClassLayout` is retained.
+enum PdbType {
+    /// A class, struct, or union.
+    Class {
+        name: String,
+
+        /// The decorated name of the class, which tells apart classes
+        /// of the same name, such as those in anonymous namespaces.
+        unique_name: Option<String>,
+
+        size_bytes: u64,
+        field_list: u32,
+        is_forward_ref: bool,
+
+        /// `Class` for an `LF_CLASS`, declared with `class` rather
+        /// than `struct`, and `Union` for an `LF_UNION`.
+        kind: ClassKind,
+    },
+    Enum {
+        name: String,
+        underlying: u32,
+        field_list: u32,
+    },
+    Pointer {
+        pointee: u32,
+        size_bytes: u64,
+        suffix: &'static str,
+    },
+    Modifier {
+        modified: u32,
+        is_const: bool,
+        is_volatile: bool,
+    },
+    Array {
+        element: u32,
+        size_bytes: u64,
+    },
+    /// The type of a bitfield member, whose bits start at
+    /// `bit_offset` within the storage unit at the member's offset.
+    Bitfield {
+        underlying: u32,
+        bit_size: u8,
+        bit_offset: u8,
+    },
+    FieldList {
+        members: Vec<PdbMember>,
+        enumerators: Vec<(String, i64)>,
+        has_vtable: bool,
+
+        /// The field list that continues this one, for classes with
+        /// too many fields to fit in a single record.
+        continuation: Option<u32>,
+    },
+    Other,
+}
+
+/// Unpacks the type information of a Microsoft Program Database
+/// (PDB), as written by MSVC and by `lld-link`.  Only the TPI stream,
+/// which describes the types, and the PDB info stream, which
+/// identifies the build, are read.
+pub struct Pdb {
+    /// The types, starting from `FIRST_TYPE_INDEX`.
+    types: Vec<PdbType>,
+}
+
+/// Reads little-endian integers and strings from a byte slice.
+struct PdbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PdbReader<'a> {
+    fn bytes(&mut self, num_bytes: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .pos
+            .checked_add(num_bytes)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| Error::InvalidPdb("unexpected end of data".into()))?;
+        self.pos += num_bytes;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Read a numeric leaf, which holds small values directly, and
+    /// larger values after a leaf kind that gives their size.
+    fn numeric(&mut self) -> Result<i64, Error> {
+        let leaf = self.u16()?;
+        let value = match leaf {
+            value if value < LF_CHAR => value as i64,
+            LF_CHAR => self.u8()? as i8 as i64,
+            LF_SHORT => self.u16()? as i16 as i64,
+            LF_USHORT => self.u16()? as i64,
+            LF_LONG => self.u32()? as i32 as i64,
+            LF_ULONG => self.u32()? as i64,
+            LF_QUADWORD | LF_UQUADWORD => self.u64()? as i64,
+            other => {
+                return Err(Error::InvalidPdb(format!(
+                    "unknown numeric leaf {other:#x}"
+                )));
+            }
+        };
+        Ok(value)
+    }
+
+    /// Read a null-terminated string.
+    fn string(&mut self) -> Result<String, Error> {
+        let remaining = self.bytes.get(self.pos..).unwrap_or_default();
+        let len = remaining
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| Error::InvalidPdb("unterminated string".into()))?;
+        self.pos += len + 1;
+        Ok(String::from_utf8_lossy(&remaining[..len]).into())
+    }
+
+    /// Skip the padding between the subrecords of a field list,
+    /// which consists of bytes `LF_PAD0` to `LF_PAD15`.
+    fn skip_padding(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|&b| b >= 0xf0) {
+            self.pos += 1;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+/// Read the streams of an MSF container.  Each stream is stored as a
+/// list of blocks, which need not be contiguous, and so each stream
+/// that is used is copied out of the container.
+fn read_streams(bytes: &[u8], stream_indices: &[usize]) -> Result<Vec<Vec<u8>>, Error> {
+    let mut superblock = PdbReader {
+        bytes,
+        pos: MSF_MAGIC.len(),
+    };
+    let block_size = superblock.u32()? as usize;
+    let _free_block_map = superblock.u32()?;
+    let _num_blocks = superblock.u32()?;
+    let directory_size = superblock.u32()? as usize;
+    let _reserved = superblock.u32()?;
+    let block_map_addr = superblock.u32()? as usize;
+    if block_size == 0 {
+        return Err(Error::InvalidPdb("block size of zero".into()));
+    }
+
+    let block = |index: usize| {
+        index
+            .checked_mul(block_size)
+            .and_then(|start| bytes.get(start..start.checked_add(block_size)?))
+            .ok_or_else(|| Error::InvalidPdb(format!("block {index} past end of file")))
+    };
+    let read_blocks = |block_indices: &[usize], size: usize| -> Result<Vec<u8>, Error> {
+        // Every block must be in the file, so a malformed size can't
+        // reserve more than the file holds.
+        let mut data = Vec::with_capacity(size.min(bytes.len()));
+        for &index in block_indices {
+            data.extend_from_slice(block(index)?);
+        }
+        data.truncate(size);
+        Ok(data)
+    };
+    let num_blocks = |size: usize| size.div_ceil(block_size);
+
+    let mut block_map = PdbReader {
+        bytes: block(block_map_addr)?,
+        pos: 0,
+    };
+    let directory_blocks = (0..num_blocks(directory_size))
+        .map(|_| Ok(block_map.u32()? as usize))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let directory = read_blocks(&directory_blocks, directory_size)?;
+
+    let mut directory = PdbReader {
+        bytes: &directory,
+        pos: 0,
+    };
+    let num_streams = directory.u32()? as usize;
+    let stream_sizes = (0..num_streams)
+        .map(|_| {
+            // Deleted streams have a size of -1, and no blocks.
+            let size = directory.u32()?;
+            Ok(if size == u32::MAX { 0 } else { size as usize })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let stream_blocks = stream_sizes
+        .iter()
+        .map(|&size| {
+            (0..num_blocks(size))
+                .map(|_| Ok(directory.u32()? as usize))
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    stream_indices
+        .iter()
+        .map(|&index| {
+            let size = *stream_sizes
+                .get(index)
+                .ok_or_else(|| Error::InvalidPdb(format!("missing stream {index}")))?;
+            read_blocks(&stream_blocks[index], size)
+        })
+        .collect()
+}
+
+/// Returns the GUID that identifies the build of a PDB file, which is
+/// also recorded in the CodeView debug directory of the matching
+/// executable.  Only the PDB info stream is read.
+pub fn guid(bytes: &[u8]) -> Result<[u8; 16], Error> {
+    if !is_pdb(bytes) {
+        return Err(Error::InvalidPdb("missing MSF magic number".into()));
+    }
+    let streams = read_streams(bytes, &[PDB_STREAM])?;
+    let mut info = PdbReader {
+        bytes: &streams[0],
+        pos: 0,
+    };
+    let _version = info.u32()?;
+    let _signature = info.u32()?;
+    let _age = info.u32()?;
+    Ok(info.bytes(16)?.try_into().unwrap())
+}
+
+impl Pdb {
+    /// Parse a PDB file.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if !is_pdb(bytes) {
+            return Err(Error::InvalidPdb("missing MSF magic number".into()));
+        }
+        let streams = read_streams(bytes, &[TPI_STREAM])?;
+
+        let mut header = PdbReader {
+            bytes: &streams[0],
+            pos: 0,
+        };
+        let _version = header.u32()?;
+        let header_size = header.u32()? as usize;
+        let type_index_begin = header.u32()?;
+        let type_index_end = header.u32()?;
+        let record_bytes = header.u32()? as usize;
+        if type_index_begin != FIRST_TYPE_INDEX {
+            return Err(Error::InvalidPdb(format!(
+                "unexpected first type index {type_index_begin:#x}"
+            )));
+        }
+
+        let record_data = header_size
+            .checked_add(record_bytes)
+            .and_then(|records_end| streams[0].get(header_size..records_end))
+            .ok_or_else(|| Error::InvalidPdb("type records past end of stream".into()))?;
+        let num_types = type_index_end
+            .checked_sub(type_index_begin)
+            .ok_or_else(|| Error::InvalidPdb("type index range ends before it begins".into()))?;
+        let mut reader = PdbReader {
+            bytes: record_data,
+            pos: 0,
+        };
+        // Each record holds at least its length and kind, so a
+        // malformed count can't reserve more than the stream holds.
+        let mut types = Vec::with_capacity((num_types as usize).min(record_data.len() / 4));
+        while !reader.is_empty() {
+            let length = reader.u16()? as usize;
+            let mut record = PdbReader {
+                bytes: reader.bytes(length)?,
+                pos: 0,
+            };
+            types.push(Self::parse_record(&mut record)?);
+        }
+
+        Ok(Self { types })
+    }
+
+    /// Parse a single type record, following its length.
+    fn parse_record(record: &mut PdbReader) -> Result<PdbType, Error> {
+        let kind = record.u16()?;
+        let pdb_type = match kind {
+            LF_CLASS | LF_STRUCTURE | LF_INTERFACE => {
+                let _count = record.u16()?;
+                let property = record.u16()?;
+                let field_list = record.u32()?;
+                let _derived_from = record.u32()?;
+                let _vtable_shape = record.u32()?;
+                let size_bytes = record.numeric()? as u64;
+                let name = record.string()?;
+                let unique_name = if property & PROPERTY_HAS_UNIQUE_NAME != 0 {
+                    Some(record.string()?)
+                } else {
+                    None
+                };
+                PdbType::Class {
+                    name,
+                    unique_name,
+                    size_bytes,
+                    field_list,
+                    is_forward_ref: property & PROPERTY_FORWARD_REF != 0,
+                    kind: if kind == LF_CLASS {
+                        ClassKind::Class
+                    } else {
+                        ClassKind::Struct
+                    },
+                }
+            }
+            LF_UNION => {
+                let _count = record.u16()?;
+                let property = record.u16()?;
+                let field_list = record.u32()?;
+                let size_bytes = record.numeric()? as u64;
+                let name = record.string()?;
+                let unique_name = if property & PROPERTY_HAS_UNIQUE_NAME != 0 {
+                    Some(record.string()?)
+                } else {
+                    None
+                };
+                PdbType::Class {
+                    name,
+                    unique_name,
+                    size_bytes,
+                    field_list,
+                    is_forward_ref: property & PROPERTY_FORWARD_REF != 0,
+                    kind: ClassKind::Union,
+                }
+            }
+            LF_ENUM => {
+                let _count = record.u16()?;
+                let _property = record.u16()?;
+                let underlying = record.u32()?;
+                let field_list = record.u32()?;
+                let name = record.string()?;
+                PdbType::Enum {
+                    name,
+                    underlying,
+                    field_list,
+                }
+            }
+            LF_POINTER => {
+                let pointee = record.u32()?;
+                let attributes = record.u32()?;
+                let suffix = match (attributes >> 5) & 0x7 {
+                    POINTER_MODE_LVALUE_REF => "&",
+                    POINTER_MODE_RVALUE_REF => "&&",
+                    _ => "*",
+                };
+                PdbType::Pointer {
+                    pointee,
+                    size_bytes: ((attributes >> 13) & 0x3f) as u64,
+                    suffix,
+                }
+            }
+            LF_MODIFIER => {
+                let modified = record.u32()?;
+                let modifiers = record.u16()?;
+                PdbType::Modifier {
+                    modified,
+                    is_const: modifiers & 0x1 != 0,
+                    is_volatile: modifiers & 0x2 != 0,
+                }
+            }
+            LF_ARRAY => {
+                let element = record.u32()?;
+                let _index_type = record.u32()?;
+                let size_bytes = record.numeric()? as u64;
+                PdbType::Array {
+                    element,
+                    size_bytes,
+                }
+            }
+            LF_BITFIELD => PdbType::Bitfield {
+                underlying: record.u32()?,
+                bit_size: record.u8()?,
+                bit_offset: record.u8()?,
+            },
+            LF_FIELDLIST => Self::parse_field_list(record)?,
+            _ => PdbType::Other,
+        };
+        Ok(pdb_type)
+    }
+
+    /// Parse the subrecords of a `LF_FIELDLIST`.  Methods, nested
+    /// types, and static members don't affect the layout, and are
+    /// skipped.  Virtual base classes are also skipped, since their
+    /// offset is only known at runtime.
+    fn parse_field_list(record: &mut PdbReader) -> Result<PdbType, Error> {
+        let mut members = Vec::new();
+        let mut enumerators = Vec::new();
+        let mut has_vtable = false;
+        let mut continuation = None;
+        while !record.is_empty() {
+            let kind = record.u16()?;
+            match kind {
+                LF_MEMBER => {
+                    let _attributes = record.u16()?;
+                    let type_index = record.u32()?;
+                    let offset = record.numeric()? as u64;
+                    let name = record.string()?;
+                    members.push(PdbMember {
+                        name: Some(name),
+                        type_index,
+                        offset,
+                        is_base_class: false,
+                    });
+                }
+                LF_BCLASS => {
+                    let _attributes = record.u16()?;
+                    let type_index = record.u32()?;
+                    let offset = record.numeric()? as u64;
+                    members.push(PdbMember {
+                        name: None,
+                        type_index,
+                        offset,
+                        is_base_class: true,
+                    });
+                }
+                LF_VBCLASS | LF_IVBCLASS => {
+                    let _attributes = record.u16()?;
+                    let _base_class = record.u32()?;
+                    let _vbptr_type = record.u32()?;
+                    let _vbptr_offset = record.numeric()?;
+                    let _vbtable_index = record.numeric()?;
+                }
+                LF_ENUMERATE => {
+                    let _attributes = record.u16()?;
+                    let value = record.numeric()?;
+                    let name = record.string()?;
+                    enumerators.push((name, value));
+                }
+                LF_STMEMBER => {
+                    let _attributes = record.u16()?;
+                    let _type_index = record.u32()?;
+                    let _name = record.string()?;
+                }
+                LF_METHOD => {
+                    let _count = record.u16()?;
+                    let _method_list = record.u32()?;
+                    let _name = record.string()?;
+                }
+                LF_ONEMETHOD => {
+                    let attributes = record.u16()?;
+                    let _type_index = record.u32()?;
+                    // Introducing virtual methods also record their
+                    // offset within the vtable.
+                    let method_property = (attributes >> 2) & 0x7;
+                    if method_property == 4 || method_property == 6 {
+                        let _vtable_offset = record.u32()?;
+                    }
+                    let _name = record.string()?;
+                }
+                LF_NESTTYPE => {
+                    let _padding = record.u16()?;
+                    let _type_index = record.u32()?;
+                    let _name = record.string()?;
+                }
+                LF_VFUNCTAB => {
+                    let _padding = record.u16()?;
+                    let _vtable_type = record.u32()?;
+                    has_vtable = true;
+                }
+                LF_INDEX => {
+                    let _padding = record.u16()?;
+                    continuation = Some(record.u32()?);
+                }
+                other => {
+                    return Err(Error::InvalidPdb(format!(
+                        "unknown field list entry {other:#x}"
+                    )));
+                }
+            }
+            record.skip_padding();
+        }
+        Ok(PdbType::FieldList {
+            members,
+            enumerators,
+            has_vtable,
+            continuation,
+        })
+    }
+
+    fn get(&self, type_index: u32) -> Option<&PdbType> {
+        let index = type_index.checked_sub(FIRST_TYPE_INDEX)?;
+        self.types.get(index as usize)
+    }
+
+    /// Returns the entries of a field list, including those of any
+    /// continuation records.
+    fn field_lists(&self, type_index: u32) -> impl Iterator<Item = &PdbType> + '_ {
+        std::iter::successors(self.get(type_index), |field_list| match field_list {
+            PdbType::FieldList {
+                continuation: Some(continuation),
+                ..
+            } => self.get(*continuation),
+            _ => None,
+        })
+        .filter(|field_list| matches!(field_list, PdbType::FieldList { .. }))
+    }
+
+    /// Returns the definition of a class, union, or enum, which may
+    /// occur after forward references to it.  The definition is found
+    /// by its unique name, or by name for classes without one, as
+    /// recorded by the `definitions` map.
+    fn resolve(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> u32 {
+        match self.get(type_index) {
+            Some(PdbType::Class {
+                name,
+                unique_name,
+                is_forward_ref: true,
+                ..
+            }) => definitions
+                .get(unique_name.as_deref().unwrap_or(name))
+                .copied()
+                .unwrap_or(type_index),
+            Some(PdbType::Modifier { modified, .. }) => self.resolve(*modified, definitions),
+            _ => type_index,
+        }
+    }
+
+    /// Returns the name of the type, in C++ syntax.
+    fn type_name(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> String {
+        if type_index < FIRST_TYPE_INDEX {
+            return primitive_name(type_index);
+        }
+        match self.get(type_index) {
+            Some(PdbType::Class { name, .. }) | Some(PdbType::Enum { name, .. }) => name.clone(),
+            Some(PdbType::Pointer {
+                pointee, suffix, ..
+            }) => format!("{}{suffix}", self.type_name(*pointee, definitions)),
+            Some(PdbType::Modifier {
+                modified,
+                is_const,
+                is_volatile,
+            }) => {
+                let mut name = self.type_name(*modified, definitions);
+                if *is_volatile {
+                    name = format!("volatile {name}");
+                }
+                if *is_const {
+                    name = format!("const {name}");
+                }
+                name
+            }
+            Some(PdbType::Array {
+                element,
+                size_bytes,
+            }) => {
+                let element_size = self.size_bytes(*element, definitions).unwrap_or(0);
+                let num_elements = size_bytes.checked_div(element_size).unwrap_or(0);
+                format!("{}[{num_elements}]", self.type_name(*element, definitions))
+            }
+            _ => "unknown_class".into(),
+        }
+    }
+
+    /// Returns the size of the type, in bytes.
+    fn size_bytes(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> Option<u64> {
+        if type_index < FIRST_TYPE_INDEX {
+            return primitive_size(type_index);
+        }
+        match self.get(self.resolve(type_index, definitions))? {
+            PdbType::Class { size_bytes, .. }
+            | PdbType::Pointer { size_bytes, .. }
+            | PdbType::Array { size_bytes, .. } => Some(*size_bytes),
+            PdbType::Enum { underlying, .. } => primitive_size(*underlying),
+            _ => None,
+        }
+    }
+
+    /// Returns the broad category of the type.
+    fn type_kind(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> TypeKind {
+        if type_index < FIRST_TYPE_INDEX {
+            return primitive_kind(type_index);
+        }
+        match self.get(self.resolve(type_index, definitions)) {
+            Some(PdbType::Class { .. }) => TypeKind::Aggregate,
+            Some(PdbType::Enum { .. }) => TypeKind::Enumeration,
+            Some(PdbType::Pointer { .. }) => TypeKind::Pointer,
+            _ => TypeKind::Unknown,
+        }
+    }
+
+    /// If the type is a pointer, returns the index of the type
+    /// ultimately pointed to.
+    fn pointee(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> Option<u32> {
+        let mut pointee = match self.get(self.resolve(type_index, definitions))? {
+            PdbType::Pointer { pointee, .. } => *pointee,
+            _ => return None,
+        };
+        while let Some(PdbType::Pointer {
+            pointee: next_pointee,
+            ..
+        }) = self.get(self.resolve(pointee, definitions))
+        {
+            pointee = *next_pointee;
+        }
+        Some(self.resolve(pointee, definitions))
+    }
+
+    /// If the type is a pointer, returns the name of the type
+    /// ultimately pointed to.
+    fn pointee_name(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> Option<String> {
+        Some(self.type_name(self.pointee(type_index, definitions)?, definitions))
+    }
+
+    /// If the type is a pointer to a class, struct, or union, returns
+    /// the kind of the type pointed to.
+    fn pointee_class(
+        &self,
+        type_index: u32,
+        definitions: &HashMap<&str, u32>,
+    ) -> Option<ClassKind> {
+        match self.get(self.pointee(type_index, definitions)?)? {
+            PdbType::Class { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Returns the enumerators of an enumeration type.
+    fn enumerators(&self, type_index: u32, definitions: &HashMap<&str, u32>) -> Vec<Enumerator> {
+        let Some(PdbType::Enum { field_list, .. }) =
+            self.get(self.resolve(type_index, definitions))
+        else {
+            return Vec::new();
+        };
+        self.field_lists(*field_list)
+            .flat_map(|field_list| match field_list {
+                PdbType::FieldList { enumerators, .. } => enumerators.as_slice(),
+                _ => &[],
+            })
+            .map(|(name, value)| Enumerator {
+                name: name.clone(),
+                value: *value,
+            })
+            .collect()
+    }
+
+    /// Collect the layout of every defined class, struct, and union.
+    /// PDB names are fully qualified, and are split into the
+    /// namespace and the name of the class.
+    pub fn classes(&self) -> impl Iterator<Item = ClassLayout> + '_ {
+        // Members refer to the forward declarations of their types,
+        // so the definitions are found by unique name.
+        let definitions: HashMap<&str, u32> = self
+            .types
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pdb_type)| match pdb_type {
+                PdbType::Class {
+                    name,
+                    unique_name,
+                    is_forward_ref: false,
+                    ..
+                } => Some((
+                    unique_name.as_deref().unwrap_or(name),
+                    u32::try_from(i).ok()?.checked_add(FIRST_TYPE_INDEX)?,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        self.types.iter().filter_map(move |pdb_type| {
+            let PdbType::Class {
+                name: qualified_name,
+                size_bytes,
+                field_list,
+                is_forward_ref: false,
+                kind,
+                ..
+            } = pdb_type
+            else {
+                return None;
+            };
+            // MSVC names anonymous types `<unnamed-tag>` or
+            // `<anonymous-tag>`.
+            if qualified_name.starts_with('<') || qualified_name.contains("::<") {
+                return None;
+            }
+
+            let mut has_vtable = false;
+            let mut members = Vec::new();
+            for field_list in self.field_lists(*field_list) {
+                let PdbType::FieldList {
+                    members: field_members,
+                    has_vtable: field_has_vtable,
+                    ..
+                } = field_list
+                else {
+                    continue;
+                };
+                has_vtable |= field_has_vtable;
+                members.extend(field_members.iter().map(|member| {
+                    // A bitfield's type gives the type of its storage
+                    // unit, and the position of its bits within it.
+                    let (type_index, bitfield) = match self.get(member.type_index) {
+                        Some(PdbType::Bitfield {
+                            underlying,
+                            bit_size,
+                            bit_offset,
+                        }) => (
+                            *underlying,
+                            Some(Bitfield {
+                                bit_offset: (member.offset as usize)
+                                    .saturating_mul(8)
+                                    .saturating_add(*bit_offset as usize),
+                                bit_size: *bit_size as usize,
+                            }),
+                        ),
+                        _ => (member.type_index, None),
+                    };
+                    let underlying = self.resolve(type_index, &definitions);
+                    MemberLayout {
+                        name: if member.is_base_class {
+                            "_base_class".into()
+                        } else {
+                            member.name.clone().unwrap_or_else(|| "unknown_name".into())
+                        },
+                        type_name: self.type_name(type_index, &definitions).into(),
+                        kind: self.type_kind(underlying, &definitions),
+                        is_base_class: member.is_base_class,
+                        offset: member.offset as usize,
+                        size_bytes: self.size_bytes(underlying, &definitions).unwrap_or(0) as usize,
+                        alignment: None,
+                        enumerators: self.enumerators(underlying, &definitions),
+                        pointee: self.pointee_name(underlying, &definitions),
+                        pointee_class: self.pointee_class(underlying, &definitions),
+                        is_flexible_array: false,
+                        is_incomplete: false,
+                        bitfield,
+                        decl_file: None,
+                        decl_line: None,
+                    }
+                }));
+            }
+
+            let name = crate::name_index::unqualified(qualified_name);
+            let namespace = qualified_name[..qualified_name.len() - name.len()]
+                .trim_end_matches("::")
+                .to_string();
+            Some(ClassLayout {
+                name: name.to_string(),
+                namespace,
+                binary: None,
+                unit_name: None,
+                decl_file: None,
+                decl_line: None,
+                unit_offset: None,
+                die_offset: None,
+                has_vtable,
+                aliases: Vec::new(),
+                linkage_names: Vec::new(),
+                size_bytes: *size_bytes as usize,
+                size_kind: SizeKind::Exact,
+                alignment: None,
+                members,
+                variant_part: None,
+                language: Language::Cpp,
+                kind: *kind,
+            })
+        })
+    }
+}
+
+/// Returns the name of a primitive type, which is identified by a
+/// type index below `FIRST_TYPE_INDEX`.  Bits 8-11 give the pointer
+/// mode, with zero for the type itself, and bits 0-7 give the type.
+fn primitive_name(type_index: u32) -> String {
+    let name = match type_index & 0xff {
+        0x03 => "void",
+        0x08 => "HRESULT",
+        0x10 => "signed char",
+        0x20 | 0x69 => "unsigned char",
+        0x68 => "int8_t",
+        0x70 => "char",
+        0x71 => "wchar_t",
+        0x7a => "char16_t",
+        0x7b => "char32_t",
+        0x7c => "char8_t",
+        0x11 | 0x72 => "short",
+        0x21 | 0x73 => "unsigned short",
+        0x12 => "long",
+        0x22 => "unsigned long",
+        0x74 => "int",
+        0x75 => "unsigned int",
+        0x13 | 0x76 => "long long",
+        0x23 | 0x77 => "unsigned long long",
+        0x14 | 0x78 => "__int128",
+        0x24 | 0x79 => "unsigned __int128",
+        0x46 => "_Float16",
+        0x40 => "float",
+        0x41 => "double",
+        0x42 => "long double",
+        0x30..=0x33 => "bool",
+        _ => "unknown_class",
+    };
+    if type_index & 0xf00 == 0 {
+        name.to_string()
+    } else {
+        format!("{name}*")
+    }
+}
+
+/// Returns the size of a primitive type, in bytes.
+fn primitive_size(type_index: u32) -> Option<u64> {
+    match (type_index >> 8) & 0xf {
+        0 => {}
+        // 16-bit near and far pointers.
+        1..=3 => return Some(2),
+        // 32-bit pointers.
+        4 | 5 => return Some(4),
+        // 64-bit pointers.
+        6 => return Some(8),
+        _ => return None,
+    }
+    let size = match type_index & 0xff {
+        0x10 | 0x20 | 0x68 | 0x69 | 0x70 | 0x7c | 0x30 => 1,
+        0x11 | 0x21 | 0x72 | 0x73 | 0x71 | 0x7a | 0x46 | 0x31 => 2,
+        0x08 | 0x12 | 0x22 | 0x74 | 0x75 | 0x7b | 0x40 | 0x32 => 4,
+        0x13 | 0x23 | 0x76 | 0x77 | 0x41 | 0x33 => 8,
+        0x42 => 10,
+        0x14 | 0x24 | 0x78 | 0x79 => 16,
+        _ => return None,
+    };
+    Some(size)
+}
+
+/// Returns the broad category of a primitive type.
+fn primitive_kind(type_index: u32) -> TypeKind {
+    if type_index & 0xf00 != 0 {
+        return TypeKind::Pointer;
+    }
+    match type_index & 0xff {
+        0x10 | 0x20 | 0x70 | 0x71 | 0x7a | 0x7b | 0x7c => TypeKind::Character,
+        0x08 | 0x11 | 0x12 | 0x13 | 0x14 | 0x68 | 0x72 | 0x74 | 0x76 | 0x78 => TypeKind::Signed,
+        0x21 | 0x22 | 0x23 | 0x24 | 0x69 | 0x73 | 0x75 | 0x77 | 0x79 => TypeKind::Unsigned,
+        0x40 | 0x41 | 0x42 | 0x46 => TypeKind::Float,
+        0x30..=0x33 => TypeKind::Boolean,
+        _ => TypeKind::Unknown,
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use crate::errors::Error;
+use crate::inspect::Memory;
+
+/// Returns the ELF files mapped into a running process, starting
+/// with its executable, as listed by `/proc/<pid>/maps`.  Each file is
+/// listed once.  A file that isn't visible at its own path, such as
+/// one in another container, is read through `/proc/<pid>/root`.
+/// Processes can only be read on Linux.
+#[cfg(target_os = "linux")]
+pub fn mapped_binaries(pid: u32) -> Result<Vec<PathBuf>, Error> {
+    let maps = std::fs::read_to_string(format!("/proc/{pid}/maps"))
+        .map_err(|err| Error::ProcessMaps(pid, err))?;
+    let executable = std::fs::read_link(format!("/proc/{pid}/exe")).ok();
+
+    // Each line holds the address range, permissions, offset, device,
+    // and inode of a mapping, followed by the path of the mapped file,
+    // if any.
+    let mapped_paths = maps
+        .lines()
+        .filter_map(|line| line.splitn(6, char::is_whitespace).nth(5))
+        .map(|path| path.trim_start())
+        .filter(|path| path.starts_with('/'))
+        .filter(|path| !path.ends_with(" (deleted)"))
+        .map(PathBuf::from);
+
+    let mut binaries: Vec<PathBuf> = Vec::new();
+    for path in executable.into_iter().chain(mapped_paths) {
+        let path = if path.is_file() {
+            path
+        } else {
+            let relative = path.strip_prefix("/").unwrap_or(&path);
+            PathBuf::from(format!("/proc/{pid}/root")).join(relative)
+        };
+        if !binaries.contains(&path) && crate::scan::is_elf(&path) {
+            binaries.push(path);
+        }
+    }
+    Ok(binaries)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mapped_binaries(_pid: u32) -> Result<Vec<PathBuf>, Error> {
+    Err(Error::UnsupportedProcess)
+}
+
+/// The address space of a running process, read with
+/// `process_vm_readv`.  Reading another process's memory requires the
+/// same permission as attaching a debugger to it.  Processes can only
+/// be read on Linux.
+pub struct ProcessMemory {
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pid: u32,
+}
+
+impl ProcessMemory {
+    pub fn new(pid: u32) -> Self {
+        Self { pid }
+    }
+}
+
+/// The buffer description used by `process_vm_readv`, matching
+/// `struct iovec` from `<sys/uio.h>`.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct IoVec {
+    base: *mut std::ffi::c_void,
+    len: usize,
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn process_vm_readv(
+        pid: i32,
+        local_iov: *const IoVec,
+        local_iov_count: std::ffi::c_ulong,
+        remote_iov: *const IoVec,
+        remote_iov_count: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> isize;
+}
+
+impl Memory for ProcessMemory {
+    #[cfg(target_os = "linux")]
+    fn read(&self, address: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let mut num_read = 0;
+        while num_read < buf.len() {
+            let remaining = &mut buf[num_read..];
+            let local = IoVec {
+                base: remaining.as_mut_ptr().cast(),
+                len: remaining.len(),
+            };
+            let remote = IoVec {
+                base: (address + num_read as u64) as usize as *mut std::ffi::c_void,
+                len: remaining.len(),
+            };
+            // SAFETY: The local buffer is valid for writes of its
+            // length, and the remote address is only read by the
+            // kernel, which checks that it is mapped in the target.
+            let result = unsafe { process_vm_readv(self.pid as i32, &local, 1, &remote, 1, 0) };
+            if result <= 0 {
+                return Err(Error::UnreadableMemory(address + num_read as u64));
+            }
+            num_read += result as usize;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read(&self, _address: u64, _buf: &mut [u8]) -> Result<(), Error> {
+        Err(Error::UnsupportedProcess)
+    }
+
+    fn is_little_endian(&self) -> bool {
+        cfg!(target_endian = "little")
+    }
+}
@@ -1,7 +1,7 @@
 #[derive(Debug, Default)]
 pub struct RelocationMap(pub object::read::RelocationMap);
 
-impl<'a> gimli::read::Relocate for &'a RelocationMap {
+impl gimli::read::Relocate for &RelocationMap {
     fn relocate_address(&self, offset: usize, value: u64) -> gimli::Result<u64> {
         Ok(self.0.relocate(offset as u64, value))
     }
@@ -0,0 +1,102 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use itertools::Itertools as _;
+
+use crate::errors::Error;
+use crate::layout::{ClassIndex, ClassLayout, MemberLayout, TypeKind};
+
+/// Read lines of `ClassName OFFSET` from the file, or from stdin if
+/// no file is given, and print the member found at each offset.  If
+/// the member is itself a class, the chain of nested members is
+/// followed down to the innermost member containing the offset.
+pub fn resolve_offsets(
+    classes: impl Iterator<Item = ClassLayout>,
+    path: Option<&Path>,
+) -> Result<(), Error> {
+    let lookup = ClassIndex::new(classes);
+
+    let reader: Box<dyn BufRead> = match path {
+        Some(path) => Box::new(std::io::BufReader::new(std::fs::File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    };
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Class names may contain spaces (e.g. `Foo<unsigned int>`),
+        // so the offset is taken from the end of the line.
+        let Some((class_name, offset)) =
+            line.rsplit_once(char::is_whitespace)
+                .and_then(|(class_name, offset)| {
+                    Some((class_name.trim(), crate::parse_integer(offset).ok()?))
+                })
+        else {
+            println!("{line}: <could not parse, expected `ClassName OFFSET`>");
+            continue;
+        };
+
+        let Some(class) = lookup.get(class_name) else {
+            println!("{line}: <unknown class {class_name}>");
+            continue;
+        };
+
+        if offset >= class.size_bytes {
+            println!(
+                "{line}: <out of bounds, {class_name} is {} bytes>",
+                class.size_bytes
+            );
+            continue;
+        }
+
+        let chain = member_chain(&lookup, class, offset);
+        let Some(innermost) = chain.last() else {
+            println!("{line}: <padding in {class_name}>");
+            continue;
+        };
+
+        let path = chain.iter().map(|member| member.name.as_str()).join(".");
+        let member_start: usize = chain.iter().map(|member| member.offset).sum();
+        let delta = offset - member_start;
+        let suffix = if delta > 0 {
+            format!("+{delta}")
+        } else {
+            String::new()
+        };
+        println!(
+            "{line}: {class_name}::{path}{suffix} ({}, {} bytes at {member_start:#x})",
+            innermost.type_name, innermost.size_bytes
+        );
+    }
+
+    Ok(())
+}
+
+/// The members containing the offset, from the outermost to the
+/// innermost.  Empty if the offset is in padding.
+fn member_chain<'a>(
+    lookup: &'a ClassIndex,
+    class: &'a ClassLayout,
+    offset: usize,
+) -> Vec<&'a MemberLayout> {
+    let mut chain = Vec::new();
+    let mut class = Some(class);
+    let mut offset = offset;
+    while let Some(member) = class.and_then(|class| {
+        class
+            .members
+            .iter()
+            .find(|member| member.offset <= offset && offset < member.end())
+    }) {
+        chain.push(member);
+        offset -= member.offset;
+        class = (member.kind == TypeKind::Aggregate)
+            .then(|| lookup.get(&member.type_name))
+            .flatten();
+    }
+    chain
+}
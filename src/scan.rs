@@ -0,0 +1,82 @@
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+
+use object::Object as _;
+
+use crate::file_contents::FileContents;
+use crate::LoadOptions;
+
+/// Find every ELF file within the directory tree that has debug
+/// information, either of its own or in a separate debug file found
+/// as for `debug_file::find_debug_file`.  Symbolic links are not
+/// followed, so that a library isn't found once for each of its
+/// versioned names.  Entries that can't be read are skipped with a
+/// warning.  The files are returned in sorted order.
+pub fn find_binaries(dir: &Path, load_options: &LoadOptions) -> Vec<PathBuf> {
+    let mut binaries = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(subdir) = pending.pop() {
+        let entries = match std::fs::read_dir(&subdir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("warning: skipping {}: {err}", subdir.display());
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = entry.and_then(|entry| Ok((entry.file_type()?, entry)));
+            let (file_type, entry) = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    eprintln!("warning: skipping an entry of {}: {err}", subdir.display());
+                    continue;
+                }
+            };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() && has_debug_info(&entry.path(), load_options) {
+                binaries.push(entry.path());
+            }
+        }
+    }
+    if binaries.is_empty() {
+        eprintln!(
+            "warning: found no binaries with debug information in {}",
+            dir.display()
+        );
+    }
+    binaries.sort();
+    binaries
+}
+
+/// Returns true if the file is an ELF file with DWARF or BTF debug
+/// information.  A `--debug-file` names the debug file of a single
+/// binary, and so doesn't count as the debug information of every
+/// file found.
+fn has_debug_info(path: &Path, load_options: &LoadOptions) -> bool {
+    if !is_elf(path) {
+        return false;
+    }
+
+    let Ok(bytes) = FileContents::open(path, load_options.use_mmap) else {
+        return false;
+    };
+    let Ok(object) = object::File::parse(&*bytes) else {
+        return false;
+    };
+    object.section_by_name(".debug_info").is_some()
+        || object.section_by_name(".BTF").is_some()
+        || (load_options.debug_file.is_none()
+            && crate::debug_file::find_local_debug_file(path, &object, load_options)
+                .is_ok_and(|debug_file| debug_file.is_some()))
+}
+
+/// Returns true if the file starts with the ELF magic number.  Only
+/// the magic number is read, since the files being checked may be
+/// large files of other kinds.
+pub fn is_elf(path: &Path) -> bool {
+    let mut magic = [0; 4];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == object::elf::ELFMAG)
+}
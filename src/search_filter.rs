@@ -0,0 +1,450 @@
+use crate::demangle::{strip_template_args, symbol_scope};
+use crate::layout::{ClassIndex, ClassLayout};
+
+/// Represents the user's search options, as specified on the command
+/// line.  Each option may be given multiple times, in which case a
+/// class matches that option if it matches any of the values.
+pub struct SearchFilter {
+    /// If non-empty, only print classes whose name, or one of whose
+    /// typedef aliases, matches one of the `class_names`.  Names
+    /// containing `*` or `?` are template-aware globs, in which a
+    /// wildcard never matches an unbalanced `<` or `>`, so
+    /// `std::vector<*>` matches every instantiation of `std::vector`
+    /// but not `std::vector<int>::iterator`.
+    pub class_names: Vec<String>,
+
+    /// If non-empty, only print classes that inherit from a class
+    /// whose name matches one of the `base_class_names`.
+    pub base_class_names: Vec<String>,
+
+    /// If true, the `base_class_names` may match any direct or
+    /// indirect base class.  Otherwise, only direct base classes are
+    /// considered.
+    pub base_class_recursive: bool,
+
+    /// If non-empty, only print classes that contain at least one
+    /// member whose name matches one of the `contained_class_names`.
+    pub contained_class_names: Vec<String>,
+
+    /// If true, the `contained_class_names` may match a member
+    /// embedded at any depth, such as a member of a member.
+    /// Otherwise, only direct members are considered.
+    pub contains_recursive: bool,
+
+    /// If true, the `contained_class_names` may also match a member
+    /// that is a pointer or reference to the named type.
+    pub contains_pointers: bool,
+
+    /// If non-empty, only print classes that have at least one data
+    /// member whose name matches one of the `member_names`.
+    pub member_names: Vec<String>,
+
+    /// If present, only print classes that have a data member
+    /// starting at the `member_offset`.
+    pub member_offset: Option<usize>,
+
+    /// If present, the data member found at `member_offset` must also
+    /// have a size of `member_size` bytes.
+    pub member_size: Option<usize>,
+
+    /// If non-empty, only print classes declared within one of the
+    /// `namespaces`, or within a namespace nested inside one of them.
+    pub namespaces: Vec<String>,
+
+    /// If non-empty, only print classes found in a compilation unit
+    /// whose name contains one of the `unit_patterns`.
+    pub unit_patterns: Vec<String>,
+
+    /// If non-empty, only print classes declared in a file whose path
+    /// contains one of the `decl_file_patterns`.
+    pub decl_file_patterns: Vec<String>,
+
+    /// If non-empty, only print classes declared in a file whose path
+    /// matches one of the `declared_in` globs.  Within a path
+    /// component, `*` and `?` behave as for `exclude_patterns`, while
+    /// a `**` component matches any number of directories.  Relative
+    /// globs may match any trailing portion of the path.
+    pub declared_in: Vec<String>,
+
+    /// If present, only print classes of exactly `size` bytes.
+    pub size: Option<usize>,
+
+    /// If present, only print classes of at least `min_size` bytes.
+    pub min_size: Option<usize>,
+
+    /// If present, only print classes of at most `max_size` bytes.
+    pub max_size: Option<usize>,
+
+    /// If present, only print classes with at least `min_padding`
+    /// unused bytes, counting both holes between members and padding
+    /// at the end of the class.
+    pub min_padding: Option<usize>,
+
+    /// If true, only print polymorphic classes, which have a vtable
+    /// pointer either directly or through a base class.
+    pub has_vtable: bool,
+
+    /// If non-empty, only print classes that own one of the symbols
+    /// in `linkage_names`.  A symbol may be given either as a mangled
+    /// name, which is compared against the linkage names of the
+    /// class's methods and static members, or as a demangled name
+    /// such as `game::Npc::update(int)`, in which case the enclosing
+    /// scope is compared against the class name.
+    pub linkage_names: Vec<String>,
+
+    /// Classes whose name matches any of the `exclude_patterns` are
+    /// not printed, even if they match all other options.  Patterns
+    /// may use `*` to match any sequence of characters, and `?` to
+    /// match any single character.
+    pub exclude_patterns: Vec<String>,
+
+    /// If true, names are compared without regard to case.
+    pub ignore_case: bool,
+}
+
+impl SearchFilter {
+    /// Returns true if the filter only depends on the names, sizes,
+    /// and members of classes, which are recorded in a `TypeIndex`.
+    pub fn is_answerable_from_index(&self) -> bool {
+        !self.contains_pointers
+            && self.unit_patterns.is_empty()
+            && self.decl_file_patterns.is_empty()
+            && self.declared_in.is_empty()
+            && !self.has_vtable
+            && self.linkage_names.is_empty()
+    }
+
+    /// Returns true if the filter must look up classes other than the
+    /// one being tested, and so needs an index of every class.
+    pub fn needs_class_index(&self) -> bool {
+        (self.base_class_recursive && !self.base_class_names.is_empty())
+            || (self.contains_recursive && !self.contained_class_names.is_empty())
+            || self.has_vtable
+    }
+
+    /// Returns true if the class should be printed.  The `index` is
+    /// used to look up other classes, for options that depend on the
+    /// layout of base classes or members.
+    pub fn matches(&self, class: &ClassLayout, index: &ClassIndex) -> bool {
+        let matches_name = self.class_names.is_empty()
+            || self.class_names.iter().any(|requested| {
+                if requested.contains(['*', '?']) {
+                    // Patterns that name a namespace are compared
+                    // against the qualified name.
+                    let qualified_name = class.qualified_name();
+                    let name = if requested.contains(class.language.namespace_separator()) {
+                        &qualified_name
+                    } else {
+                        &class.name
+                    };
+                    std::iter::once(name)
+                        .chain(&class.aliases)
+                        .any(|name| self.template_glob_match(requested, name))
+                } else {
+                    std::iter::once(&class.name)
+                        .chain(&class.aliases)
+                        .any(|name| self.names_match(requested, name))
+                }
+            });
+
+        let matches_base_class = self.base_class_names.is_empty()
+            || if self.base_class_recursive {
+                index
+                    .ancestors(class)
+                    .any(|base_class| self.matches_any(&self.base_class_names, base_class))
+            } else {
+                class
+                    .members
+                    .iter()
+                    .filter(|member| member.is_base_class)
+                    .any(|base_class| {
+                        self.matches_any(&self.base_class_names, &base_class.type_name)
+                    })
+            };
+
+        let matches_contained_class = self.contained_class_names.is_empty()
+            || if self.contains_recursive {
+                index
+                    .embedded_types(class)
+                    .any(|type_name| self.matches_any(&self.contained_class_names, type_name))
+            } else {
+                class
+                    .members
+                    .iter()
+                    .filter(|member| !member.is_base_class)
+                    .any(|member| {
+                        self.matches_any(&self.contained_class_names, &member.type_name)
+                            || (self.contains_pointers
+                                && member.pointee.as_ref().is_some_and(|pointee| {
+                                    self.matches_any(&self.contained_class_names, pointee)
+                                }))
+                    })
+            };
+
+        let matches_member_name = self.member_names.is_empty()
+            || class
+                .members
+                .iter()
+                .filter(|member| !member.is_base_class)
+                .any(|member| self.matches_any(&self.member_names, &member.name));
+
+        let matches_member_offset = self
+            .member_offset
+            .map(|offset| {
+                class
+                    .members
+                    .iter()
+                    .filter(|member| !member.is_base_class)
+                    .filter(|member| member.offset == offset)
+                    .any(|member| {
+                        self.member_size
+                            .map(|size_bytes| member.size_bytes == size_bytes)
+                            .unwrap_or(true)
+                    })
+            })
+            .unwrap_or(true);
+
+        let matches_namespace = self.namespaces.is_empty()
+            || self.namespaces.iter().any(|namespace| {
+                self.names_match(namespace, &class.namespace)
+                    || class
+                        .namespace
+                        .get(..namespace.len() + 2)
+                        .map(|prefix| self.names_match(&format!("{namespace}::"), prefix))
+                        .unwrap_or(false)
+            });
+
+        let matches_unit =
+            self.matches_any_substring(&self.unit_patterns, class.unit_name.as_deref());
+
+        let matches_decl_file =
+            self.matches_any_substring(&self.decl_file_patterns, class.decl_file.as_deref());
+
+        let matches_declared_in = self.declared_in.is_empty()
+            || class
+                .decl_file
+                .as_ref()
+                .map(|decl_file| {
+                    self.declared_in
+                        .iter()
+                        .any(|pattern| self.path_glob_match(pattern, decl_file))
+                })
+                .unwrap_or(false);
+
+        let matches_size = self
+            .size
+            .map(|size| class.size_bytes == size)
+            .unwrap_or(true)
+            && self
+                .min_size
+                .map(|min_size| class.size_bytes >= min_size)
+                .unwrap_or(true)
+            && self
+                .max_size
+                .map(|max_size| class.size_bytes <= max_size)
+                .unwrap_or(true);
+
+        let matches_padding = self
+            .min_padding
+            .map(|min_padding| class.padding_bytes() >= min_padding)
+            .unwrap_or(true);
+
+        let matches_vtable = !self.has_vtable || index.is_polymorphic(class);
+
+        let matches_linkage_name = self.linkage_names.is_empty()
+            || self.linkage_names.iter().any(|symbol| {
+                class.linkage_names.contains(symbol)
+                    || symbol_scope(symbol).is_some_and(|scope| {
+                        self.names_match(&scope, &strip_template_args(&class.qualified_name()))
+                    })
+            });
+
+        let is_excluded = self
+            .exclude_patterns
+            .iter()
+            .any(|pattern| self.glob_match(pattern, &class.name));
+
+        matches_name
+            && matches_base_class
+            && matches_contained_class
+            && matches_member_name
+            && matches_member_offset
+            && matches_namespace
+            && matches_unit
+            && matches_decl_file
+            && matches_declared_in
+            && matches_size
+            && matches_padding
+            && matches_vtable
+            && matches_linkage_name
+            && !is_excluded
+    }
+
+    /// Returns true if the name matches any of the names requested by
+    /// the user, or if no names were requested.
+    fn matches_any(&self, requested: &[String], name: &str) -> bool {
+        requested.is_empty()
+            || requested
+                .iter()
+                .any(|requested| self.names_match(requested, name))
+    }
+
+    /// Returns true if the text contains any of the patterns, or if
+    /// no patterns were requested.  Missing text never matches.
+    fn matches_any_substring(&self, patterns: &[String], text: Option<&str>) -> bool {
+        if patterns.is_empty() {
+            return true;
+        }
+        let Some(text) = text else {
+            return false;
+        };
+        patterns.iter().any(|pattern| {
+            if self.ignore_case {
+                text.to_lowercase().contains(&pattern.to_lowercase())
+            } else {
+                text.contains(pattern.as_str())
+            }
+        })
+    }
+
+    /// Returns true if the name matches the name requested by the
+    /// user.
+    fn names_match(&self, requested: &str, name: &str) -> bool {
+        if self.ignore_case {
+            requested.eq_ignore_ascii_case(name)
+        } else {
+            requested == name
+        }
+    }
+
+    /// Returns true if the name matches the glob pattern.
+    fn glob_match(&self, pattern: &str, name: &str) -> bool {
+        let chars_match = |a: char, b: char| {
+            if self.ignore_case {
+                a.eq_ignore_ascii_case(&b)
+            } else {
+                a == b
+            }
+        };
+
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+
+        // Standard backtracking glob match, where only the most
+        // recent `*` needs to be revisited on a mismatch.
+        let (mut p, mut n) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p, n));
+                    p += 1;
+                }
+                Some('?') => {
+                    p += 1;
+                    n += 1;
+                }
+                Some(&c) if chars_match(c, name[n]) => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match backtrack {
+                    Some((star_p, star_n)) => {
+                        p = star_p + 1;
+                        n = star_n + 1;
+                        backtrack = Some((star_p, star_n + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+
+    /// Returns true if the name matches the glob pattern, where each
+    /// `*` or `?` may only match text whose angle brackets are
+    /// balanced.  Whitespace is ignored, since compilers differ in
+    /// whether nested template arguments are written as `> >` or
+    /// `>>`.
+    fn template_glob_match(&self, pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+        let name: Vec<char> = name.chars().filter(|c| !c.is_whitespace()).collect();
+        self.template_glob_match_from(&pattern, &name)
+    }
+
+    fn template_glob_match_from(&self, pattern: &[char], name: &[char]) -> bool {
+        let Some((&first, rest)) = pattern.split_first() else {
+            return name.is_empty();
+        };
+
+        match first {
+            '*' | '?' => {
+                // Try each prefix of the name that keeps the angle
+                // brackets balanced, stopping at an unmatched `>`.
+                let mut depth = 0usize;
+                let max_len = if first == '?' { 1 } else { name.len() };
+                if first == '*' && self.template_glob_match_from(rest, name) {
+                    return true;
+                }
+                for (i, &c) in name.iter().enumerate().take(max_len) {
+                    match c {
+                        '<' => depth += 1,
+                        '>' if depth == 0 => return false,
+                        '>' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 && self.template_glob_match_from(rest, &name[i + 1..]) {
+                        return true;
+                    }
+                }
+                false
+            }
+            c => {
+                let chars_match = |a: char, b: char| {
+                    if self.ignore_case {
+                        a.eq_ignore_ascii_case(&b)
+                    } else {
+                        a == b
+                    }
+                };
+                name.split_first().is_some_and(|(&n, name_rest)| {
+                    chars_match(c, n) && self.template_glob_match_from(rest, name_rest)
+                })
+            }
+        }
+    }
+
+    /// Returns true if the path matches the glob pattern, comparing
+    /// one path component at a time.
+    fn path_glob_match(&self, pattern: &str, path: &str) -> bool {
+        let components = |s: &str| -> Vec<String> {
+            s.split(['/', '\\'])
+                .filter(|component| !component.is_empty() && *component != ".")
+                .map(|component| component.to_string())
+                .collect()
+        };
+        let pattern_components = components(pattern);
+        let path_components = components(path);
+
+        if pattern.starts_with('/') {
+            self.components_match(&pattern_components, &path_components)
+        } else {
+            (0..=path_components.len())
+                .any(|start| self.components_match(&pattern_components, &path_components[start..]))
+        }
+    }
+
+    fn components_match(&self, pattern: &[String], path: &[String]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((first, rest)) if first == "**" => {
+                (0..=path.len()).any(|skip| self.components_match(rest, &path[skip..]))
+            }
+            Some((first, rest)) => {
+                !path.is_empty()
+                    && self.glob_match(first, &path[0])
+                    && self.components_match(rest, &path[1..])
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::borrow::Cow;
+
+use crate::inspect::{self, Memory};
+use crate::layout::{ClassIndex, ClassLayout, MemberLayout, TypeKind};
+
+/// The most elements printed from a `std::vector`.
+const MAX_ELEMENTS: usize = 16;
+
+/// The namespaces in which libstdc++ and libc++ define their classes.
+const STD_NAMESPACES: [&str; 3] = ["std", "std::__cxx11", "std::__1"];
+
+/// The smart pointers of the standard library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SmartPointer {
+    Unique,
+    Shared,
+    Weak,
+}
+
+impl SmartPointer {
+    fn name(self) -> &'static str {
+        match self {
+            SmartPointer::Unique => "unique_ptr",
+            SmartPointer::Shared => "shared_ptr",
+            SmartPointer::Weak => "weak_ptr",
+        }
+    }
+}
+
+/// Decode the contents of a `std::string`, `std::vector`, or smart
+/// pointer member, given the bytes of the member, by following its
+/// pointers into memory.  Both libstdc++ and libc++ are recognized.
+/// Returns `None` for members of other types, and for containers
+/// whose pointers don't describe valid contents, such as those of an
+/// object that hasn't been constructed yet.
+pub fn decode(
+    member: &MemberLayout,
+    bytes: &[u8],
+    index: &ClassIndex,
+    memory: &dyn Memory,
+) -> Option<String> {
+    let name = unqualified(&member.type_name);
+    // The first member tells the implementations apart, as their
+    // classes are often the same size.
+    let first_member = std_class(index, member)?
+        .members
+        .first()
+        .map(|first| first.name.as_str());
+    if name == "string" || name.starts_with("basic_string<char,") {
+        let is_libcxx = matches!(first_member, Some("__r_" | "__rep_"));
+        decode_string(bytes, is_libcxx, memory)
+    } else if name.starts_with("vector<") && !name.starts_with("vector<bool,") {
+        decode_vector(name, bytes, index, memory)
+    } else if let Some((pointer, _)) = smart_pointer(name) {
+        let is_libcxx = first_member == Some("__ptr_");
+        decode_smart_pointer(pointer, bytes, is_libcxx, memory)
+    } else {
+        None
+    }
+}
+
+/// The name of a type as it would be written in C++ source.  The
+/// names of smart pointers are given by the compiler without `std::`
+/// and with their default deleter, as for `unique_ptr<Texture,
+/// std::default_delete<Texture> >`, which hides the type they point
+/// to.  Smart pointers are instead named as `std::unique_ptr<Texture>`,
+/// and other names are unchanged.
+pub fn display_name(type_name: &str) -> Cow<'_, str> {
+    let Some((pointer, arguments)) = smart_pointer(unqualified(type_name)) else {
+        return type_name.into();
+    };
+    let pointee = arguments[0];
+    let is_default_deleter = arguments.get(1).is_none_or(|deleter| {
+        unqualified(deleter).strip_prefix("default_delete<") == Some(&format!("{pointee}>"))
+    });
+    let arguments = if is_default_deleter {
+        pointee.to_string()
+    } else {
+        arguments.join(", ")
+    };
+    format!("std::{}<{arguments}>", pointer.name()).into()
+}
+
+/// Find the class of a standard library type in the index.  Members
+/// name their type without the namespaces in which it is declared,
+/// so the namespaces of libstdc++ and libc++ are each tried in turn.
+///
+/// Since the namespace isn't known, the member is only taken to be of
+/// the standard library's class if no class of the same name is
+/// defined outside of the standard library, and if the class has the
+/// size of the member.  Otherwise, a user's `string` or `vector` would
+/// be decoded as if it were the standard library's.
+fn std_class<'a>(index: &'a ClassIndex, member: &MemberLayout) -> Option<&'a ClassLayout> {
+    let name = unqualified(&member.type_name);
+    let class = STD_NAMESPACES
+        .into_iter()
+        .find_map(|namespace| index.get(&format!("{namespace}::{name}")))?;
+    let is_ambiguous = index
+        .iter()
+        .any(|other| other.name == name && !STD_NAMESPACES.contains(&other.namespace.as_str()));
+    (!is_ambiguous && class.size_bytes == member.size_bytes).then_some(class)
+}
+
+/// Remove the namespaces in which the standard libraries define their
+/// containers, such as `std::__cxx11::` or `std::__1::`.
+fn unqualified(type_name: &str) -> &str {
+    let name = type_name.strip_prefix("std::").unwrap_or(type_name);
+    name.strip_prefix("__cxx11::")
+        .or_else(|| name.strip_prefix("__1::"))
+        .unwrap_or(name)
+}
+
+/// Decode a `std::string` as its characters and length.
+///
+/// - libstdc++ stores a pointer to the characters, the length, and a
+///   16-byte buffer that holds short strings, to which the pointer
+///   then points.
+/// - libstdc++ before the C++11 ABI stores only a pointer to the
+///   characters, preceded in memory by the length, the capacity, and
+///   a reference count.
+/// - libc++ stores short strings inline, with their length shifted
+///   left by one in the first byte, and otherwise stores the
+///   capacity, length, and a pointer to the characters.  The lowest
+///   bit of the first byte is set for the latter.  Only the layout
+///   used by little-endian targets is decoded.
+fn decode_string(bytes: &[u8], is_libcxx: bool, memory: &dyn Memory) -> Option<String> {
+    let little_endian = memory.is_little_endian();
+    let unsigned = |bytes: &[u8]| inspect::unsigned_value(bytes, little_endian);
+
+    let (data, len) = if is_libcxx {
+        if !little_endian || !bytes.len().is_multiple_of(3) {
+            return None;
+        }
+        let pointer_size = bytes.len() / 3;
+        if bytes[0] & 1 == 0 {
+            let len = (bytes[0] >> 1) as usize;
+            let chars = bytes.get(1..1 + len)?;
+            return Some(format!("{} (length {len})", inspect::quote(chars)));
+        }
+        let len = unsigned(&bytes[pointer_size..2 * pointer_size])?;
+        (unsigned(&bytes[2 * pointer_size..])?, len)
+    } else if bytes.len() <= 8 {
+        let data = unsigned(bytes)?;
+        let mut len_bytes = vec![0; bytes.len()];
+        memory
+            .read(data.checked_sub(3 * bytes.len() as u64)?, &mut len_bytes)
+            .ok()?;
+        (data, unsigned(&len_bytes)?)
+    } else {
+        let pointer_size = bytes.len().checked_sub(16)? / 2;
+        let len = unsigned(&bytes[pointer_size..2 * pointer_size])?;
+        (unsigned(&bytes[..pointer_size])?, len)
+    };
+
+    let shown = len.min(inspect::MAX_STRING_LEN as u64) as usize;
+    let mut chars = vec![0; shown];
+    if data == 0 || memory.read(data, &mut chars).is_err() {
+        return None;
+    }
+    let suffix = if shown < len as usize { "..." } else { "" };
+    Some(format!("{}{suffix} (length {len})", inspect::quote(&chars)))
+}
+
+/// Decode a smart pointer as the address that it holds.  A
+/// `std::shared_ptr` or `std::weak_ptr` holds that address followed
+/// by a pointer to its control block, which is followed to show the
+/// number of owners and of weak references.
+///
+/// - libstdc++ stores, after the vtable pointer of the control block,
+///   an `int` with the number of owners, then an `int` with the
+///   number of weak references, plus one while there are owners.
+/// - libc++ stores, after the vtable pointer, a `long` with the number
+///   of owners minus one, then a `long` with the number of weak
+///   references, minus one if there are no owners.
+///
+/// A `std::unique_ptr` with a deleter that has state is larger than a
+/// pointer, and isn't decoded.
+fn decode_smart_pointer(
+    pointer: SmartPointer,
+    bytes: &[u8],
+    is_libcxx: bool,
+    memory: &dyn Memory,
+) -> Option<String> {
+    let little_endian = memory.is_little_endian();
+    let pointer_size = match pointer {
+        SmartPointer::Unique => bytes.len(),
+        SmartPointer::Shared | SmartPointer::Weak => bytes.len() / 2,
+    };
+    if !matches!(pointer_size, 4 | 8) {
+        return None;
+    }
+    let address = inspect::unsigned_value(&bytes[..pointer_size], little_endian)?;
+    let mut text = match address {
+        0 => "nullptr".to_string(),
+        _ => format!("{address:#x}"),
+    };
+    if pointer == SmartPointer::Unique {
+        return Some(text);
+    }
+
+    let control_block = inspect::unsigned_value(&bytes[pointer_size..], little_endian)?;
+    if control_block == 0 {
+        return Some(text);
+    }
+    let count_size = if is_libcxx { pointer_size } else { 4 };
+    let mut counts = vec![0; 2 * count_size];
+    memory
+        .read(control_block + pointer_size as u64, &mut counts)
+        .ok()?;
+    let owners = inspect::signed_value(&counts[..count_size], little_endian)?;
+    let weak = inspect::signed_value(&counts[count_size..], little_endian)?;
+    let (use_count, weak_count) = if is_libcxx {
+        (owners + 1, weak + (owners < 0) as i64)
+    } else {
+        (owners, weak - (owners > 0) as i64)
+    };
+    text += &format!(", use count {use_count}, weak count {weak_count}");
+    Some(text)
+}
+
+/// Decode a `std::vector` as its size, capacity, and, for vectors of
+/// primitive types, its first elements.  Both libstdc++ and libc++
+/// store pointers to the first element, the end of the elements, and
+/// the end of the allocation.
+fn decode_vector(
+    name: &str,
+    bytes: &[u8],
+    index: &ClassIndex,
+    memory: &dyn Memory,
+) -> Option<String> {
+    let little_endian = memory.is_little_endian();
+    if !bytes.len().is_multiple_of(3) {
+        return None;
+    }
+    let pointer_size = bytes.len() / 3;
+    let mut pointers = bytes
+        .chunks(pointer_size)
+        .map(|pointer| inspect::unsigned_value(pointer, little_endian));
+    let start = pointers.next()??;
+    let finish = pointers.next()??;
+    let end = pointers.next()??;
+
+    let element = element_type(name)?;
+    let primitive = primitive_type(element, pointer_size);
+    let element_size = match primitive {
+        Some((size, _)) => size,
+        None => index.get(element)?.size_bytes,
+    };
+    if element_size == 0 || finish < start || end < finish {
+        return None;
+    }
+    let len = (finish - start) / element_size as u64;
+    let capacity = (end - start) / element_size as u64;
+    let mut text = format!("size {len}, capacity {capacity}");
+
+    if let Some((_, kind)) = primitive.filter(|_| len > 0) {
+        let shown = len.min(MAX_ELEMENTS as u64) as usize;
+        let mut elements = vec![0; shown * element_size];
+        if memory.read(start, &mut elements).is_ok() {
+            let values = elements
+                .chunks(element_size)
+                .map(|element| inspect::typed_value(kind, &[], element, little_endian))
+                .collect::<Vec<_>>();
+            let suffix = if shown < len as usize { ", ..." } else { "" };
+            text += &format!(" {{{}{suffix}}}", values.join(", "));
+        }
+    }
+    Some(text)
+}
+
+/// The element type of a `vector<T, std::allocator<T> >`.
+fn element_type(name: &str) -> Option<&str> {
+    template_arguments(name.strip_prefix("vector<")?)
+        .first()
+        .copied()
+}
+
+/// Recognize a smart pointer, returning its template arguments, the
+/// first of which is the type it points to.
+fn smart_pointer(name: &str) -> Option<(SmartPointer, Vec<&str>)> {
+    let (pointer, args) = [
+        SmartPointer::Unique,
+        SmartPointer::Shared,
+        SmartPointer::Weak,
+    ]
+    .into_iter()
+    .find_map(|pointer| {
+        let args = name.strip_prefix(pointer.name())?.strip_prefix('<')?;
+        Some((pointer, args))
+    })?;
+    let arguments = template_arguments(args);
+    (!arguments.is_empty()).then_some((pointer, arguments))
+}
+
+/// Split the template arguments that follow the opening `<` of a
+/// template's name, up to its closing `>`.
+fn template_arguments(args: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' if depth > 0 => depth -= 1,
+            ',' | '>' if depth == 0 => {
+                arguments.push(args[start..i].trim());
+                if c == '>' {
+                    return arguments;
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    Vec::new()
+}
+
+/// The size and kind of a primitive type, given its name as written
+/// by GCC and Clang.  A `long` is taken to be the size of a pointer,
+/// as on every target but Windows.
+fn primitive_type(name: &str, pointer_size: usize) -> Option<(usize, TypeKind)> {
+    let primitive = match name {
+        "bool" => (1, TypeKind::Boolean),
+        "char" | "signed char" | "unsigned char" | "char8_t" => (1, TypeKind::Character),
+        "short" | "short int" => (2, TypeKind::Signed),
+        "unsigned short" | "short unsigned int" | "char16_t" => (2, TypeKind::Unsigned),
+        "int" => (4, TypeKind::Signed),
+        "unsigned int" | "char32_t" => (4, TypeKind::Unsigned),
+        "long" | "long int" => (pointer_size, TypeKind::Signed),
+        "unsigned long" | "long unsigned int" => (pointer_size, TypeKind::Unsigned),
+        "long long" | "long long int" => (8, TypeKind::Signed),
+        "unsigned long long" | "long long unsigned int" => (8, TypeKind::Unsigned),
+        "float" => (4, TypeKind::Float),
+        "double" => (8, TypeKind::Float),
+        _ if name.ends_with('*') => (pointer_size, TypeKind::Pointer),
+        _ => return None,
+    };
+    Some(primitive)
+}
@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead as _, Write as _};
+use std::path::{Path, PathBuf};
+
+use object::Object as _;
+
+use crate::errors::Error;
+use crate::layout::{ClassKind, ClassLayout, Language, MemberLayout, SizeKind, TypeKind};
+use crate::name_index::{is_candidate, NameLookup};
+
+/// The first line of every index file, which identifies the format.
+/// Index files that start with anything else are ignored.
+const INDEX_HEADER: &str = "dwarf-to-struct index v4";
+
+/// A compact summary of the classes in a binary, which is written to
+/// a cache directory so that later queries against the same binary
+/// can find the requested classes without reading every compilation
+/// unit.
+pub struct TypeIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+/// The summary of a single class within a `TypeIndex`.
+pub struct IndexEntry {
+    /// The name of the class, without any enclosing namespace.
+    pub name: String,
+
+    /// The namespace that contains the class.
+    pub namespace: String,
+
+    /// The source language of the class.
+    pub language: Language,
+
+    /// The keyword with which the class was declared.
+    pub kind: ClassKind,
+
+    /// The typedef names that refer to the class.
+    pub aliases: Vec<String>,
+
+    /// The offset within .debug_info of the compilation unit that
+    /// contains the class.
+    pub unit_offset: u64,
+
+    /// The offset within .debug_info of the class's entry.
+    pub die_offset: u64,
+
+    /// The name of the compilation unit that contains the class.
+    pub unit_name: Option<String>,
+
+    /// The file and line at which the class was declared.
+    pub decl_file: Option<String>,
+    pub decl_line: Option<u64>,
+
+    /// The size of the class, in bytes.
+    pub size_bytes: usize,
+
+    /// The class's `ClassLayout::layout_hash`.
+    pub layout_hash: u64,
+
+    /// The data members and base classes of the class.
+    pub members: Vec<IndexMember>,
+}
+
+/// The summary of a single data member or base class within an
+/// `IndexEntry`.
+pub struct IndexMember {
+    pub name: String,
+    pub type_name: String,
+    pub kind: TypeKind,
+    pub is_base_class: bool,
+    pub offset: usize,
+    pub size_bytes: usize,
+}
+
+/// The names used for each `Language` within an index file.
+const LANGUAGE_NAMES: [(Language, &str); 7] = [
+    (Language::C, "c"),
+    (Language::Cpp, "c++"),
+    (Language::Rust, "rust"),
+    (Language::Go, "go"),
+    (Language::Fortran, "fortran"),
+    (Language::Ada, "ada"),
+    (Language::ObjC, "objc"),
+];
+
+/// The names used for each `TypeKind` within an index file.
+const KIND_NAMES: [(TypeKind, &str); 9] = [
+    (TypeKind::Signed, "signed"),
+    (TypeKind::Unsigned, "unsigned"),
+    (TypeKind::Float, "float"),
+    (TypeKind::Boolean, "bool"),
+    (TypeKind::Character, "char"),
+    (TypeKind::Pointer, "pointer"),
+    (TypeKind::Enumeration, "enum"),
+    (TypeKind::Aggregate, "aggregate"),
+    (TypeKind::Unknown, "unknown"),
+];
+
+impl TypeIndex {
+    /// Summarize the classes read from a binary.  Returns `None` if
+    /// any class was not read from DWARF, as for BTF and PDB input,
+    /// since it has no location to record, and an index without it
+    /// would silently answer queries with fewer classes.
+    pub fn new(classes: &[ClassLayout]) -> Option<Self> {
+        let entries = classes
+            .iter()
+            .map(|class| {
+                Some(IndexEntry {
+                    name: class.name.clone(),
+                    namespace: class.namespace.clone(),
+                    language: class.language,
+                    kind: class.kind,
+                    aliases: class.aliases.clone(),
+                    unit_offset: class.unit_offset?,
+                    die_offset: class.die_offset?,
+                    unit_name: class.unit_name.clone(),
+                    decl_file: class.decl_file.clone(),
+                    decl_line: class.decl_line,
+                    size_bytes: class.size_bytes,
+                    layout_hash: class.layout_hash(),
+                    members: class
+                        .members
+                        .iter()
+                        .map(|member| IndexMember {
+                            name: member.name.clone(),
+                            type_name: member.type_name.to_string(),
+                            kind: member.kind,
+                            is_base_class: member.is_base_class,
+                            offset: member.offset,
+                            size_bytes: member.size_bytes,
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { entries })
+    }
+
+    /// Read an index file.  Returns `None` if the file doesn't exist,
+    /// or was written in a different format.
+    pub fn read(path: &Path) -> Result<Option<Self>, Error> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut lines = std::io::BufReader::new(file).lines();
+        if lines.next().transpose()?.as_deref() != Some(INDEX_HEADER) {
+            return Ok(None);
+        }
+
+        let mut unit_names: HashMap<u64, String> = HashMap::new();
+        let mut files: Vec<String> = Vec::new();
+        let mut entries: Vec<IndexEntry> = Vec::new();
+        for line in lines {
+            let line = line?;
+            let fields: Vec<_> = line.split('\t').collect();
+            let is_valid = match fields.as_slice() {
+                ["unit", offset, name] => parse_hex(offset)
+                    .map(|offset| unit_names.insert(offset, name.to_string()))
+                    .is_some(),
+                ["file", path] => {
+                    files.push(path.to_string());
+                    true
+                }
+                ["class", unit_offset, die_offset, size_bytes, layout_hash, decl_file, decl_line, language, keyword, namespace, name] => {
+                    (|| {
+                        let unit_offset = parse_hex(unit_offset)?;
+                        let (language, _) = LANGUAGE_NAMES
+                            .iter()
+                            .find(|(_, language_name)| language_name == language)?;
+                        let kind = [ClassKind::Struct, ClassKind::Class, ClassKind::Union]
+                            .into_iter()
+                            .find(|kind| kind.keyword() == *keyword)?;
+                        entries.push(IndexEntry {
+                            name: name.to_string(),
+                            namespace: namespace.to_string(),
+                            language: *language,
+                            kind,
+                            aliases: Vec::new(),
+                            unit_offset,
+                            die_offset: parse_hex(die_offset)?,
+                            unit_name: unit_names.get(&unit_offset).cloned(),
+                            decl_file: match parse_optional::<usize>(decl_file)? {
+                                Some(i) => Some(files.get(i)?.clone()),
+                                None => None,
+                            },
+                            decl_line: parse_optional(decl_line)?,
+                            size_bytes: size_bytes.parse().ok()?,
+                            layout_hash: parse_hex(layout_hash)?,
+                            members: Vec::new(),
+                        });
+                        Some(())
+                    })()
+                    .is_some()
+                }
+                ["alias", alias] => entries
+                    .last_mut()
+                    .map(|entry| entry.aliases.push(alias.to_string()))
+                    .is_some(),
+                ["member", offset, size_bytes, role, kind, type_name, name] => (|| {
+                    let (kind, _) = KIND_NAMES.iter().find(|(_, kind_name)| kind_name == kind)?;
+                    entries.last_mut()?.members.push(IndexMember {
+                        name: name.to_string(),
+                        type_name: type_name.to_string(),
+                        kind: *kind,
+                        is_base_class: *role == "base",
+                        offset: offset.parse().ok()?,
+                        size_bytes: size_bytes.parse().ok()?,
+                    });
+                    Some(())
+                })()
+                .is_some(),
+                _ => false,
+            };
+            if !is_valid {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(Self { entries }))
+    }
+
+    /// Write the index to a file, creating its directory if needed.
+    /// Each class is written as a tab-separated line, followed by a
+    /// line for each of its aliases and members.  The names of
+    /// compilation units and declaration files are written once, the
+    /// first time they are used, since they are shared by many
+    /// classes.
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        // Write to a temporary file first, so that a concurrent query
+        // never reads a partially-written index.
+        let tmp_path = path.with_extension("tmp");
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+        writeln!(out, "{INDEX_HEADER}")?;
+
+        let mut written_units = HashSet::new();
+        let mut file_indices: HashMap<&str, usize> = HashMap::new();
+        for entry in &self.entries {
+            if let Some(unit_name) = &entry.unit_name {
+                if written_units.insert(entry.unit_offset) {
+                    writeln!(out, "unit\t{:x}\t{unit_name}", entry.unit_offset)?;
+                }
+            }
+            let decl_file = match &entry.decl_file {
+                Some(decl_file) => {
+                    let num_files = file_indices.len();
+                    let file_index = *file_indices.entry(decl_file).or_insert(num_files);
+                    if file_index == num_files {
+                        writeln!(out, "file\t{decl_file}")?;
+                    }
+                    file_index.to_string()
+                }
+                None => "-".to_string(),
+            };
+            let decl_line = entry
+                .decl_line
+                .map_or_else(|| "-".to_string(), |line| line.to_string());
+
+            let (_, language) = LANGUAGE_NAMES
+                .iter()
+                .find(|(language, _)| *language == entry.language)
+                .unwrap();
+            let keyword = entry.kind.keyword();
+
+            writeln!(
+                out,
+                "class\t{:x}\t{:x}\t{}\t{:016x}\t{decl_file}\t{decl_line}\t{language}\t{keyword}\t{}\t{}",
+                entry.unit_offset,
+                entry.die_offset,
+                entry.size_bytes,
+                entry.layout_hash,
+                entry.namespace,
+                entry.name
+            )?;
+            for alias in &entry.aliases {
+                writeln!(out, "alias\t{alias}")?;
+            }
+            for member in &entry.members {
+                let (_, kind) = KIND_NAMES
+                    .iter()
+                    .find(|(kind, _)| *kind == member.kind)
+                    .unwrap();
+                writeln!(
+                    out,
+                    "member\t{}\t{}\t{}\t{kind}\t{}\t{}",
+                    member.offset,
+                    member.size_bytes,
+                    if member.is_base_class { "base" } else { "data" },
+                    member.type_name,
+                    member.name
+                )?;
+            }
+        }
+        out.into_inner().map_err(|err| err.into_error())?;
+        std::fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Find the classes whose name or alias may match one of the
+    /// requested names, as for the accelerator tables.  Every
+    /// compilation unit that contains a class is covered.
+    pub fn lookup(&self, requested: &[String]) -> NameLookup {
+        let mut lookup = NameLookup::default();
+        for entry in &self.entries {
+            lookup.units.insert(entry.unit_offset);
+            let is_match = std::iter::once(&entry.name)
+                .chain(&entry.aliases)
+                .any(|name| is_candidate(name, requested));
+            if is_match {
+                lookup.entries.insert(entry.die_offset);
+            }
+        }
+        lookup
+    }
+}
+
+impl IndexEntry {
+    /// Returns the layout of the class, as far as it is recorded in
+    /// the index.  Enumerators, pointees, linkage names, alignments,
+    /// the variants of Rust enums, whether the size of the class is
+    /// dynamic, and whether the class has a vtable are not recorded.
+    pub fn layout(&self) -> ClassLayout {
+        let members = self
+            .members
+            .iter()
+            .map(|member| MemberLayout {
+                name: member.name.clone(),
+                type_name: member.type_name.as_str().into(),
+                kind: member.kind,
+                is_base_class: member.is_base_class,
+                offset: member.offset,
+                size_bytes: member.size_bytes,
+                alignment: None,
+                enumerators: Vec::new(),
+                pointee: None,
+                pointee_class: None,
+                is_flexible_array: false,
+                is_incomplete: false,
+                bitfield: None,
+                decl_file: None,
+                decl_line: None,
+            })
+            .collect();
+
+        ClassLayout {
+            name: self.name.clone(),
+            namespace: self.namespace.clone(),
+            binary: None,
+            unit_name: self.unit_name.clone(),
+            decl_file: self.decl_file.clone(),
+            decl_line: self.decl_line,
+            unit_offset: Some(self.unit_offset),
+            die_offset: Some(self.die_offset),
+            has_vtable: false,
+            aliases: self.aliases.clone(),
+            linkage_names: Vec::new(),
+            size_bytes: self.size_bytes,
+            size_kind: SizeKind::Exact,
+            alignment: None,
+            members,
+            variant_part: None,
+            language: self.language,
+            kind: self.kind,
+        }
+    }
+}
+
+/// Returns the path at which the index of a binary is cached, which
+/// is named after the binary's build-id.  Indices are cached in
+/// `$XDG_CACHE_HOME/dwarf-to-struct`, defaulting to
+/// `~/.cache/dwarf-to-struct`.  Returns `None` if the binary has no
+/// build-id, since there would be no way to tell whether a cached
+/// index is for the same build.
+pub fn index_path<'data, R: object::ReadRef<'data>>(
+    object: &object::File<'data, R>,
+) -> Result<Option<PathBuf>, Error> {
+    let Some(build_id) = object.build_id()? else {
+        return Ok(None);
+    };
+
+    let file_name = crate::hex_string(build_id) + ".index";
+    Ok(Some(
+        crate::cache_home()?.join("dwarf-to-struct").join(file_name),
+    ))
+}
+
+/// Returns the path at which the index of the binary at `path` is
+/// cached, as for `index_path`.  Only the parts of the file needed to
+/// find the build-id are read.  Returns `None` for files that are not
+/// object files, such as standalone BTF.
+pub fn binary_index_path(path: &Path) -> Result<Option<PathBuf>, Error> {
+    let cache = object::ReadCache::new(std::fs::File::open(path)?);
+    match object::File::parse(&cache) {
+        Ok(object) => index_path(&object),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Write the index of the classes read from a binary into the cache
+/// directory, where later queries against the same binary will find
+/// it.
+pub fn write_index(path: &Path, classes: &[ClassLayout]) -> Result<(), Error> {
+    let index_path =
+        binary_index_path(path)?.ok_or_else(|| Error::NoBuildId(path.to_path_buf()))?;
+
+    let index = TypeIndex::new(classes).ok_or_else(|| Error::NotIndexable(path.to_path_buf()))?;
+    index.write(&index_path)?;
+    println!(
+        "Wrote index of {} classes to {}",
+        index.entries.len(),
+        index_path.display()
+    );
+
+    Ok(())
+}
+
+/// Parse a hexadecimal offset from an index file.
+fn parse_hex(field: &str) -> Option<u64> {
+    u64::from_str_radix(field, 16).ok()
+}
+
+/// Parse an optional decimal field from an index file, which is
+/// written as `-` when absent.  Returns `None` if the field is
+/// invalid.
+fn parse_optional<T: std::str::FromStr>(field: &str) -> Option<Option<T>> {
+    if field == "-" {
+        Some(None)
+    } else {
+        field.parse().ok().map(Some)
+    }
+}
@@ -0,0 +1,66 @@
+use std::process::Command;
+
+/// Print the classes of a fixture in the given format.
+fn dwarf_to_struct(fixture: &str, format: &str) -> String {
+    let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &path, "--format", format])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn bitfields_are_shown_with_their_width() {
+    for fixture in ["bitfields-dwarf3.o", "bitfields-dwarf5.o"] {
+        let text = dwarf_to_struct(fixture, "text");
+        assert!(text.contains("    char x; // 1 bytes, 0-1\n"), "{text}");
+        assert!(
+            text.contains("    unsigned int a : 4; // 4 bytes, 0-4, bits 8-12\n"),
+            "{text}"
+        );
+        assert!(
+            text.contains("    unsigned int b : 28; // 4 bytes, 4-8, bits 0-28\n"),
+            "{text}"
+        );
+        assert!(text.contains("    char tail; // 1 bytes, 8-9\n"), "{text}");
+    }
+}
+
+#[test]
+fn bitfields_keep_the_struct_unpacked() {
+    for fixture in ["bitfields-dwarf3.o", "bitfields-dwarf5.o"] {
+        let c = dwarf_to_struct(fixture, "c");
+        assert!(!c.contains("#pragma pack"), "{c}");
+        assert!(!c.contains("_overlap"), "{c}");
+        assert!(c.contains("    uint32_t b; // unsigned int\n"), "{c}");
+    }
+}
+
+#[test]
+fn bitfields_are_decoded_from_their_storage_unit() {
+    // `x` is 'A', `a` is 15, and `b` is 0x1234567, with the other bits
+    // of each storage unit set so that they would show if not masked.
+    let mut bytes = Vec::new();
+    bytes.extend((0x41u32 | (15 << 8) | (0xf0 << 12)).to_le_bytes());
+    bytes.extend((0x1234567u32 | (0xe << 28)).to_le_bytes());
+    bytes.extend(*b"Z\0\0\0");
+    let path = format!("{}/bitfields.bin", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&path, bytes).unwrap();
+
+    for fixture in ["bitfields-dwarf3.o", "bitfields-dwarf5.o"] {
+        let fixture = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+        let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+            .args(["--shared-object", &fixture, "overlay", "BF", &path])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let overlay = String::from_utf8(output.stdout).unwrap();
+        assert!(overlay.contains("a: unsigned int = 15\n"), "{overlay}");
+        assert!(
+            overlay.contains("b: unsigned int = 19088743\n"),
+            "{overlay}"
+        );
+    }
+}
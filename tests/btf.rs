@@ -0,0 +1,57 @@
+use std::process::Command;
+
+/// Print the classes of a fixture as text.
+fn dwarf_to_struct(fixture: &str) -> String {
+    let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &path, "--format", "text"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn members_are_read_from_btf() {
+    for fixture in ["btf.o", "btf.raw"] {
+        let text = dwarf_to_struct(fixture);
+        assert!(text.contains("struct Holder { // 32 bytes"), "{text}");
+        assert!(text.contains("    BF bf; // 12 bytes, 0-12\n"), "{text}");
+        assert!(text.contains("    U u; // 4 bytes, 12-16\n"), "{text}");
+        assert!(
+            text.contains("    Color color; // 4 bytes, 16-20\n"),
+            "{text}"
+        );
+        assert!(
+            text.contains("    const Holder* next; // 8 bytes, 24-32\n"),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn bitfields_are_read_from_btf() {
+    for fixture in ["btf.o", "btf.raw"] {
+        let text = dwarf_to_struct(fixture);
+        assert!(text.contains("    char x; // 1 bytes, 0-1\n"), "{text}");
+        assert!(
+            text.contains("    unsigned int a : 4; // 4 bytes, 0-4, bits 8-12\n"),
+            "{text}"
+        );
+        assert!(
+            text.contains("    unsigned int b : 28; // 4 bytes, 4-8, bits 0-28\n"),
+            "{text}"
+        );
+        assert!(text.contains("    char tail; // 1 bytes, 8-9\n"), "{text}");
+    }
+}
+
+#[test]
+fn unions_are_read_from_btf() {
+    for fixture in ["btf.o", "btf.raw"] {
+        let text = dwarf_to_struct(fixture);
+        assert!(text.contains("union U { // 4 bytes"), "{text}");
+        assert!(text.contains("    int i; // 4 bytes, at 0\n"), "{text}");
+        assert!(text.contains("    float f; // 4 bytes, at 0\n"), "{text}");
+    }
+}
@@ -0,0 +1,38 @@
+use std::process::Command;
+
+/// Print the named class of debug-names.o, which is found through
+/// the `.debug_names` accelerator table.
+fn lookup(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/debug-names.o",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &path, "--name", name])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    // An unreadable table is skipped with a warning, falling back to
+    // reading every unit.
+    assert!(output.stderr.is_empty(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn classes_are_found_through_debug_names() {
+    let text = lookup("Outer");
+    assert!(text.contains("struct Outer { // 16 bytes"), "{text}");
+    assert!(text.contains("    int x; // 4 bytes, 0-4\n"), "{text}");
+    assert!(text.contains("    Inner in; // 8 bytes, 8-16\n"), "{text}");
+    assert!(!text.contains("struct Other"), "{text}");
+
+    let text = lookup("Other");
+    assert!(text.contains("struct Other { // 1 bytes"), "{text}");
+    assert!(text.contains("    char c; // 1 bytes, 0-1\n"), "{text}");
+    assert!(!text.contains("struct Outer"), "{text}");
+}
+
+#[test]
+fn classes_missing_from_debug_names_are_not_found() {
+    assert_eq!(lookup("Missing"), "");
+}
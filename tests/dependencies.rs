@@ -0,0 +1,18 @@
+use std::process::Command;
+
+/// Print the classes of a fixture and the libraries it depends on.
+fn dwarf_to_struct_with_deps(fixture: &str, name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &path, "--with-deps", "--name", name])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn declared_members_are_completed_from_dependencies() {
+    let text = dwarf_to_struct_with_deps("withdeps", "Foo");
+    assert!(text.contains("    Dep d; // 24 bytes, 0-24\n"), "{text}");
+}
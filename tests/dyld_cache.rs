@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The file offset and address of the image within the cache.
+const IMAGE_OFFSET: usize = 0x1000;
+
+/// The install path of the image within the cache.
+const IMAGE_PATH: &str = "/usr/lib/libfixture.dylib";
+
+/// Write a minimal x86-64 dyld shared cache with the given file name,
+/// and return its path.  The cache holds the Mach-O object
+/// dyld-image.o as its only image, and has a single mapping whose
+/// addresses are the file offsets.
+fn write_cache(name: &str) -> PathBuf {
+    let fixture = format!("{}/tests/fixtures/dyld-image.o", env!("CARGO_MANIFEST_DIR"));
+    let mut image = std::fs::read(fixture).unwrap();
+    relocate_segments(&mut image, IMAGE_OFFSET as u32);
+
+    let mapping_offset = 0x100;
+    let images_offset = mapping_offset + 32;
+    let path_offset = images_offset + 32;
+    let file_size = IMAGE_OFFSET + image.len();
+
+    let mut cache = Vec::new();
+    cache.extend(b"dyld_v1  x86_64\0");
+    cache.extend((mapping_offset as u32).to_le_bytes());
+    cache.extend(1u32.to_le_bytes()); // mapping_count
+    cache.extend((images_offset as u32).to_le_bytes());
+    cache.extend(1u32.to_le_bytes()); // images_count
+    cache.resize(mapping_offset, 0);
+
+    cache.extend(0u64.to_le_bytes()); // address
+    cache.extend((file_size as u64).to_le_bytes()); // size
+    cache.extend(0u64.to_le_bytes()); // file_offset
+    cache.extend(5u32.to_le_bytes()); // max_prot
+    cache.extend(5u32.to_le_bytes()); // init_prot
+
+    cache.extend((IMAGE_OFFSET as u64).to_le_bytes()); // address
+    cache.extend(0u64.to_le_bytes()); // mod_time
+    cache.extend(0u64.to_le_bytes()); // inode
+    cache.extend((path_offset as u32).to_le_bytes());
+    cache.extend(0u32.to_le_bytes()); // pad
+
+    cache.extend(IMAGE_PATH.as_bytes());
+    cache.push(0);
+    cache.resize(IMAGE_OFFSET, 0);
+    cache.extend(image);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, cache).unwrap();
+    path
+}
+
+/// Move the file offsets of a 64-bit Mach-O object by `delta`, since
+/// the images of a dyld shared cache give offsets within the cache.
+fn relocate_segments(image: &mut [u8], delta: u32) {
+    let u32_at =
+        |image: &[u8], pos: usize| u32::from_le_bytes(image[pos..pos + 4].try_into().unwrap());
+    let add = |image: &mut [u8], pos: usize, width: usize| {
+        let mut bytes = [0; 8];
+        bytes[..width].copy_from_slice(&image[pos..pos + width]);
+        let value = u64::from_le_bytes(bytes);
+        if value != 0 {
+            let value = value + delta as u64;
+            image[pos..pos + width].copy_from_slice(&value.to_le_bytes()[..width]);
+        }
+    };
+
+    let num_commands = u32_at(image, 16);
+    let mut command = 32;
+    for _ in 0..num_commands {
+        // LC_SEGMENT_64, whose sections follow it.
+        if u32_at(image, command) == 0x19 {
+            add(image, command + 40, 8); // fileoff
+            let num_sections = u32_at(image, command + 64) as usize;
+            for i in 0..num_sections {
+                let section = command + 72 + 80 * i;
+                add(image, section + 48, 4); // offset
+                add(image, section + 56, 4); // reloff
+            }
+        }
+        command += u32_at(image, command + 4) as usize;
+    }
+}
+
+fn dwarf_to_struct(cache_name: &str, args: &[&str]) -> std::process::Output {
+    let cache = write_cache(cache_name);
+    Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .arg("--shared-object")
+        .arg(&cache)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn images_are_read_from_the_cache() {
+    for image in [IMAGE_PATH, "libfixture.dylib"] {
+        let output = dwarf_to_struct("dyld_shared_cache_x86_64", &["--dyld-image", image]);
+        assert!(output.status.success(), "{output:?}");
+        let text = String::from_utf8(output.stdout).unwrap();
+        assert!(text.contains("struct Outer { // 16 bytes"), "{text}");
+        assert!(text.contains("    int x; // 4 bytes, 0-4\n"), "{text}");
+        assert!(text.contains("    Inner in; // 8 bytes, 8-16\n"), "{text}");
+        assert!(text.contains("    long y; // 8 bytes, 0-8\n"), "{text}");
+    }
+}
+
+#[test]
+fn unknown_images_are_reported() {
+    let output = dwarf_to_struct(
+        "dyld_shared_cache_x86_64.missing",
+        &["--dyld-image", "libmissing.dylib"],
+    );
+    assert!(!output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("No image named libmissing.dylib"),
+        "{stderr}"
+    );
+}
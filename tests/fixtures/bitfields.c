@@ -0,0 +1,10 @@
+// Compiled with `gcc -g -gdwarf-3 -c` and `gcc -g -gdwarf-5 -c` into
+// bitfields-dwarf3.o and bitfields-dwarf5.o, whose bitfields are
+// described by `DW_AT_bit_offset` and `DW_AT_data_bit_offset`.
+struct BF {
+    char x;
+    unsigned a : 4;
+    unsigned b : 28;
+    char tail;
+};
+struct BF bf;
@@ -0,0 +1,22 @@
+// Compiled with `gcc -gbtf -c` into btf.o, which has a .BTF section
+// and no DWARF.  The section is extracted into btf.raw, a standalone
+// BTF file as found in /sys/kernel/btf, with `objcopy -O binary
+// --set-section-flags .BTF=alloc --only-section=.BTF btf.o btf.raw`.
+struct BF {
+    char x;
+    unsigned a : 4;
+    unsigned b : 28;
+    char tail;
+};
+union U {
+    int i;
+    float f;
+};
+enum Color { RED, GREEN = 5 };
+struct Holder {
+    struct BF bf;
+    union U u;
+    enum Color color;
+    const struct Holder *next;
+};
+struct Holder holder;
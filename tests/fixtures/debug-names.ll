@@ -0,0 +1,47 @@
+; Compiled with `llc -filetype=obj -dwarf-version=5 -accel-tables=Dwarf`
+; into debug-names.o, whose `.debug_names` section lets a class be
+; found by name without reading every compilation unit, and with
+; `llc -filetype=obj -mtriple=x86_64-apple-macosx10.15
+; -accel-tables=Disable` into dyld-image.o, a Mach-O object that
+; tests/dyld_cache.rs places in a dyld shared cache.  The debug
+; information is that of
+;
+;     struct Inner { long y; };
+;     struct Outer { int x; Inner in; };
+;     struct Other { char c; };
+;     Outer outer;
+;     Other other;
+target triple = "x86_64-unknown-linux-gnu"
+
+%struct.Outer = type { i32, %struct.Inner }
+%struct.Inner = type { i64 }
+%struct.Other = type { i8 }
+
+@outer = dso_local global %struct.Outer zeroinitializer, align 8, !dbg !0
+@other = dso_local global %struct.Other zeroinitializer, align 1, !dbg !14
+
+!llvm.dbg.cu = !{!2}
+!llvm.module.flags = !{!20, !21}
+
+!0 = !DIGlobalVariableExpression(var: !1, expr: !DIExpression())
+!1 = distinct !DIGlobalVariable(name: "outer", scope: !2, file: !3, line: 4, type: !5, isLocal: false, isDefinition: true)
+!2 = distinct !DICompileUnit(language: DW_LANG_C_plus_plus_14, file: !3, producer: "llc", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !4)
+!3 = !DIFile(filename: "debug-names.cpp", directory: "/")
+!4 = !{!0, !14}
+!5 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "Outer", file: !3, line: 2, size: 128, flags: DIFlagTypePassByValue, elements: !6, identifier: "_ZTS5Outer")
+!6 = !{!7, !9}
+!7 = !DIDerivedType(tag: DW_TAG_member, name: "x", scope: !5, file: !3, line: 2, baseType: !8, size: 32)
+!8 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!9 = !DIDerivedType(tag: DW_TAG_member, name: "in", scope: !5, file: !3, line: 2, baseType: !10, size: 64, offset: 64)
+!10 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "Inner", file: !3, line: 1, size: 64, flags: DIFlagTypePassByValue, elements: !11, identifier: "_ZTS5Inner")
+!11 = !{!12}
+!12 = !DIDerivedType(tag: DW_TAG_member, name: "y", scope: !10, file: !3, line: 1, baseType: !13, size: 64)
+!13 = !DIBasicType(name: "long", size: 64, encoding: DW_ATE_signed)
+!14 = !DIGlobalVariableExpression(var: !15, expr: !DIExpression())
+!15 = distinct !DIGlobalVariable(name: "other", scope: !2, file: !3, line: 5, type: !16, isLocal: false, isDefinition: true)
+!16 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "Other", file: !3, line: 3, size: 8, flags: DIFlagTypePassByValue, elements: !17, identifier: "_ZTS5Other")
+!17 = !{!18}
+!18 = !DIDerivedType(tag: DW_TAG_member, name: "c", scope: !16, file: !3, line: 3, baseType: !19, size: 8)
+!19 = !DIBasicType(name: "char", size: 8, encoding: DW_ATE_signed_char)
+!20 = !{i32 7, !"Dwarf Version", i32 5}
+!21 = !{i32 2, !"Debug Info Version", i32 3}
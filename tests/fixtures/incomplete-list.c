@@ -0,0 +1,9 @@
+// Compiled with `gcc -g -gpubnames -c` along with incomplete-node.c,
+// and linked with `ld -r` into incomplete.o.  `Node` is only declared
+// in this compilation unit, and is defined in the other.
+struct Node;
+struct List {
+    struct Node *head;
+    int length;
+};
+struct List list;
@@ -0,0 +1,7 @@
+// Compiled with `gcc -g -gpubnames -c` along with incomplete-list.c,
+// and linked with `ld -r` into incomplete.o.
+struct Node {
+    struct Node *next;
+    int value;
+};
+struct Node node;
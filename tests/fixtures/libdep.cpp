@@ -0,0 +1,9 @@
+// Compiled with `g++ -g -gpubnames -shared -fPIC` into libdep.so, the
+// library that withdeps depends on.  Its destructor is the key
+// function of `Dep`, so only libdep.so defines the class.
+struct Dep {
+    virtual ~Dep();
+    long x;
+    long y;
+};
+Dep::~Dep() {}
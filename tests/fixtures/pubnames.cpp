@@ -0,0 +1,14 @@
+// Compiled with `g++ -g -gpubnames -gz -c` into pubnames.o, whose
+// `.debug_pubtypes` section lets a class be found by name without
+// reading every compilation unit.
+#include <string>
+struct Inner {
+    int x;
+    int y;
+};
+struct Obj {
+    std::string s;
+    Inner in;
+    int z;
+};
+Obj obj;
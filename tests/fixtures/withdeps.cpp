@@ -0,0 +1,13 @@
+// Compiled with `g++ -g -gpubnames -L. -ldep -Wl,-rpath,'$ORIGIN'` into
+// withdeps.  `Dep` is only declared in its debug information, since
+// its key function is defined in libdep.so.
+struct Dep {
+    virtual ~Dep();
+    long x;
+    long y;
+};
+struct Foo {
+    Dep d;
+};
+Foo foo;
+int main() { return 0; }
@@ -0,0 +1,21 @@
+use std::process::Command;
+
+/// Print the named class of a fixture in the C format.
+fn c_definition(fixture: &str, name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &path, "--format", "c", "--name", name])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn types_defined_in_another_unit_are_complete() {
+    for name in ["List", "Li*"] {
+        let c = c_definition("incomplete.o", name);
+        assert!(c.contains("struct Node;\n"), "{c}");
+        assert!(c.contains("    struct Node* head; // Node*\n"), "{c}");
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The path to a fixture.
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Write a minimal x86-64 ELF core dump and return its path.  The
+/// core dump has an `NT_FILE` note with the given description, and a
+/// single segment of `memory_size` bytes at the `address`, of which
+/// the `bytes` are written to the core dump.
+fn write_core(
+    name: &str,
+    nt_file: &[u8],
+    address: u64,
+    memory_size: usize,
+    bytes: &[u8],
+) -> PathBuf {
+    let mut note = Vec::new();
+    note.extend(5u32.to_le_bytes()); // n_namesz
+    note.extend((nt_file.len() as u32).to_le_bytes()); // n_descsz
+    note.extend(0x46494c45u32.to_le_bytes()); // n_type: NT_FILE
+    note.extend(b"CORE\0\0\0\0");
+    note.extend(nt_file);
+
+    let note_offset: u64 = 64 + 2 * 56;
+    let data_offset = note_offset + note.len() as u64;
+
+    let mut core = Vec::new();
+    core.extend(b"\x7fELF\x02\x01\x01");
+    core.resize(16, 0);
+    core.extend(4u16.to_le_bytes()); // e_type: ET_CORE
+    core.extend(62u16.to_le_bytes()); // e_machine: EM_X86_64
+    core.extend(1u32.to_le_bytes()); // e_version
+    core.extend(0u64.to_le_bytes()); // e_entry
+    core.extend(64u64.to_le_bytes()); // e_phoff
+    core.extend(0u64.to_le_bytes()); // e_shoff
+    core.extend(0u32.to_le_bytes()); // e_flags
+    core.extend(64u16.to_le_bytes()); // e_ehsize
+    core.extend(56u16.to_le_bytes()); // e_phentsize
+    core.extend(2u16.to_le_bytes()); // e_phnum
+    core.extend([0; 6]); // e_shentsize, e_shnum, e_shstrndx
+
+    core.extend(4u32.to_le_bytes()); // p_type: PT_NOTE
+    core.extend(4u32.to_le_bytes()); // p_flags: read
+    core.extend(note_offset.to_le_bytes()); // p_offset
+    core.extend([0; 16]); // p_vaddr, p_paddr
+    core.extend((note.len() as u64).to_le_bytes()); // p_filesz
+    core.extend(0u64.to_le_bytes()); // p_memsz
+    core.extend(4u64.to_le_bytes()); // p_align
+
+    core.extend(1u32.to_le_bytes()); // p_type: PT_LOAD
+    core.extend(6u32.to_le_bytes()); // p_flags: read and write
+    core.extend(data_offset.to_le_bytes()); // p_offset
+    core.extend(address.to_le_bytes()); // p_vaddr
+    core.extend(0u64.to_le_bytes()); // p_paddr
+    core.extend((bytes.len() as u64).to_le_bytes()); // p_filesz
+    core.extend((memory_size as u64).to_le_bytes()); // p_memsz
+    core.extend(0x1000u64.to_le_bytes()); // p_align
+
+    core.extend(note);
+    core.extend(bytes);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, core).unwrap();
+    path
+}
+
+/// The description of an `NT_FILE` note listing the files mapped at
+/// each address range, as `(start, end, path)`, each mapped from the
+/// start of the file.
+fn nt_file(mappings: &[(u64, u64, &Path)]) -> Vec<u8> {
+    let mut desc = Vec::new();
+    desc.extend((mappings.len() as u64).to_le_bytes());
+    desc.extend(4096u64.to_le_bytes());
+    for (start, end, _) in mappings {
+        desc.extend(start.to_le_bytes());
+        desc.extend(end.to_le_bytes());
+        desc.extend(0u64.to_le_bytes());
+    }
+    for (_, _, path) in mappings {
+        desc.extend(path.to_str().unwrap().as_bytes());
+        desc.push(0);
+    }
+    desc
+}
+
+/// The bytes of an `Obj` at the `address`, whose `std::string` holds
+/// "hello" in its local buffer.
+fn obj_bytes(address: u64) -> Vec<u8> {
+    let mut obj = Vec::new();
+    obj.extend((address + 16).to_le_bytes());
+    obj.extend(5u64.to_le_bytes());
+    obj.extend(*b"hello\0\0\0\0\0\0\0\0\0\0\0");
+    obj.extend(7i32.to_le_bytes());
+    obj.extend(8i32.to_le_bytes());
+    obj.extend(9i32.to_le_bytes());
+    obj.extend([0; 4]);
+    obj
+}
+
+/// Decode the `Obj` at 0x10000 in the core dump.
+fn inspect_obj(core: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", &fixture("pubnames.o"), "--core"])
+        .arg(core)
+        .args(["--at", "0x10000", "--name", "Obj"])
+        .output()
+        .unwrap()
+}
+
+/// Check that the `Obj` from `obj_bytes` was decoded.
+fn assert_obj_decoded(output: &std::process::Output) {
+    assert!(output.status.success(), "{output:?}");
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(text.contains(" s = \"hello\" (length 5); "), "{text}");
+    assert!(
+        text.contains("        int x = 7; // 4 bytes, 0-4\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("        int y = 8; // 4 bytes, 4-8\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("    int z = 9; // 4 bytes, 40-44\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn members_are_decoded_when_classes_are_looked_up_by_name() {
+    let obj = obj_bytes(0x10000);
+    let core = write_core("pubnames.core", &nt_file(&[]), 0x10000, obj.len(), &obj);
+    assert_obj_decoded(&inspect_obj(&core));
+}
+
+#[test]
+fn memory_omitted_from_a_core_dump_is_read_from_the_mapped_file() {
+    let obj = obj_bytes(0x10000);
+    let mapped = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("obj.bin");
+    std::fs::write(&mapped, &obj).unwrap();
+    let end = 0x10000 + obj.len() as u64;
+    let core = write_core(
+        "mapped.core",
+        &nt_file(&[(0x10000, end, &mapped)]),
+        0x10000,
+        obj.len(),
+        &[],
+    );
+    assert_obj_decoded(&inspect_obj(&core));
+}
+
+#[test]
+fn malformed_core_dumps_are_reported() {
+    // A count of mappings so large that their size overflows.
+    let mut desc = Vec::new();
+    desc.extend((1u64 << 62).to_le_bytes());
+    desc.extend(4096u64.to_le_bytes());
+    let core = write_core("malformed.core", &desc, 0x10000, 0, &[]);
+
+    let output = inspect_obj(&core);
+    assert!(!output.status.success(), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid core dump"), "{stderr}");
+}
@@ -0,0 +1,68 @@
+use std::process::Command;
+
+/// Run dwarf-to-struct on a PDB file, printing its classes as text.
+fn dwarf_to_struct(path: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_dwarf-to-struct"))
+        .args(["--shared-object", path, "--format", "text"])
+        .output()
+        .unwrap()
+}
+
+fn fixture() -> String {
+    format!("{}/tests/fixtures/types.pdb", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn classes() -> String {
+    let output = dwarf_to_struct(&fixture());
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn members_are_resolved_through_forward_references() {
+    let text = classes();
+    assert!(text.contains("struct ns::Holder { // 24 bytes"), "{text}");
+    assert!(text.contains("    BF bf; // 12 bytes, 0-12\n"), "{text}");
+    assert!(text.contains("    U u; // 4 bytes, 12-16\n"), "{text}");
+    assert!(
+        text.contains("    ns::Holder* next; // 8 bytes, 16-24\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn bitfields_are_shown_with_their_width() {
+    let text = classes();
+    assert!(text.contains("struct BF { // 12 bytes"), "{text}");
+    assert!(text.contains("    char x; // 1 bytes, 0-1\n"), "{text}");
+    assert!(
+        text.contains("    unsigned int a : 4; // 4 bytes, 4-8, bits 0-4\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("    unsigned int b : 28; // 4 bytes, 4-8, bits 4-32\n"),
+        "{text}"
+    );
+    assert!(text.contains("    char tail; // 1 bytes, 8-9\n"), "{text}");
+}
+
+#[test]
+fn unions_are_shown() {
+    let text = classes();
+    assert!(text.contains("union U { // 4 bytes"), "{text}");
+    assert!(text.contains("    int i; // 4 bytes, at 0\n"), "{text}");
+    assert!(text.contains("    float f; // 4 bytes, at 0\n"), "{text}");
+}
+
+#[test]
+fn truncated_pdb_files_are_reported() {
+    let bytes = std::fs::read(fixture()).unwrap();
+    for len in [40, 4096, bytes.len() - 1] {
+        let path = format!("{}/truncated-{len}.pdb", env!("CARGO_TARGET_TMPDIR"));
+        std::fs::write(&path, &bytes[..len]).unwrap();
+        let output = dwarf_to_struct(&path);
+        assert!(!output.status.success(), "{output:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("PDB"), "{stderr}");
+    }
+}